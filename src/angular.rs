@@ -0,0 +1,295 @@
+//! Generation of Angular services: the models of the TypeScript target along
+//! with an injectable service per route tag, e.g. `UsersService`, whose
+//! methods send requests with `HttpClient` and return `Observable`s.
+//!
+//! Routes of merged specs get a service per service instead, and routes
+//! without tags are methods of `ApiService`. Paths are relative to the URL
+//! provided as `API_BASE_URL`. Like in the fetch client, routes without a
+//! path are sent to `/{route}` and routes without a method with `POST`.
+
+use std::collections::BTreeMap;
+
+use crate::config::TypeScriptConfig;
+use crate::{
+    flatten_extends, render_doc, render_header, render_path, route_namespaces, typescript_models,
+    ApiDocs, ApiDocsRoute, ContentType, TypeScriptRenderer,
+};
+
+pub(crate) fn render_angular(api_docs: &ApiDocs, config: &TypeScriptConfig) -> String {
+    let models = typescript_models(&api_docs.models, config);
+    let renderer = TypeScriptRenderer::new(config);
+    let mut sections = vec![
+        renderer.render_interfaces(&models),
+        renderer.render_factories(&flatten_extends(&models)),
+        render_services(&renderer, &api_docs.routes),
+    ];
+    // Brands are only known once everything using them is rendered.
+    sections.insert(0, renderer.render_brands());
+    let body = sections
+        .into_iter()
+        .filter(|section| !section.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut imports = String::new();
+    if !api_docs.routes.is_empty() {
+        let rxjs = if config.null_prototype {
+            "type Observable, map"
+        } else {
+            "type Observable"
+        };
+        imports.push_str(&format!(
+            "import {{ Injectable, InjectionToken, inject }} from \"@angular/core\";\n\
+             import {{ HttpClient }} from \"@angular/common/http\";\n\
+             import {{ {rxjs} }} from \"rxjs\";\n"
+        ));
+    }
+    imports.push_str(&renderer.render_imports());
+    if !imports.is_empty() {
+        imports.push('\n');
+    }
+    format!("{}{imports}{body}", render_header(config))
+}
+
+/// Renders the helpers used by the routes, `API_BASE_URL` and a service per
+/// namespace of the routes.
+fn render_services(
+    renderer: &TypeScriptRenderer, routes: &BTreeMap<String, ApiDocsRoute>,
+) -> String {
+    if routes.is_empty() {
+        return String::new();
+    }
+
+    let config = renderer.config;
+    let indent = config.indent(1);
+
+    let mut helpers = String::new();
+    if config.null_prototype {
+        helpers.push_str(&format!("{}\n", renderer.render_without_prototypes()));
+    }
+    if routes.values().any(|route| route.query.is_some()) {
+        helpers.push_str(&format!("{}\n", renderer.render_serialize_query()));
+    }
+    if routes.values().any(|route| route.headers.is_some()) {
+        helpers.push_str(&format!("{}\n", renderer.render_serialize_headers()));
+    }
+    if routes
+        .values()
+        .any(|route| route.content_type == Some(ContentType::Multipart))
+    {
+        helpers.push_str(&format!("{}\n", renderer.render_to_form_data()));
+    }
+
+    let mut namespaces = route_namespaces(routes);
+    let ungrouped = routes
+        .iter()
+        .filter(|(_, route)| route.service.is_none() && route.tags.is_empty())
+        .map(|(name, _)| (name.as_str(), heck::AsLowerCamelCase(name).to_string()))
+        .collect::<Vec<_>>();
+    if !ungrouped.is_empty() {
+        namespaces
+            .entry("api".to_string())
+            .or_default()
+            .extend(ungrouped);
+    }
+
+    let services = namespaces
+        .iter()
+        .map(|(namespace, members)| {
+            let methods = members
+                .iter()
+                .map(|(name, member)| render_method(renderer, name, member, &routes[*name]))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "@Injectable({{ providedIn: \"root\" }})\n\
+                 export class {}Service {{\n\
+                 {indent}private readonly http = inject(HttpClient);\n\
+                 {indent}private readonly baseUrl = inject(API_BASE_URL, {{ optional: true }}) ?? \"\";\n\
+                 \n\
+                 {methods}\
+                 }}\n",
+                heck::AsPascalCase(namespace)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "{helpers}\
+         /** URL the paths of routes are relative to, e.g. `https://example.com/api`. */\n\
+         export const API_BASE_URL = new InjectionToken<string>(\"API_BASE_URL\");\n\
+         \n\
+         {services}"
+    )
+}
+
+/// Renders the method of a service sending a request to the route `name`.
+fn render_method(
+    renderer: &TypeScriptRenderer, name: &str, member: &str, route: &ApiDocsRoute,
+) -> String {
+    let config = renderer.config;
+    let indent = config.indent(1);
+    let returns = heck::AsPascalCase(&route.returns);
+    let parameters = renderer.render_route_parameters(route)[1..].join(", ");
+
+    let method = route.method.map_or("POST", |method| method.as_str());
+    let path = match &route.path {
+        Some(path) => render_path(path).trim_matches('`').to_string(),
+        None => format!("/{name}"),
+    };
+    let query = if route.query.is_some() {
+        "${serializeQuery(query)}"
+    } else {
+        ""
+    };
+    let mut options = Vec::new();
+    if route.headers.is_some() {
+        options.push("headers: serializeHeaders(headers)");
+    }
+    match route.content_type {
+        _ if !route.has_body() => {},
+        Some(ContentType::Multipart) => options.push("body: toFormData(body)"),
+        _ => options.push("body"),
+    }
+    let options = if options.is_empty() {
+        String::new()
+    } else {
+        format!(", {{ {} }}", options.join(", "))
+    };
+    let sanitize = if config.null_prototype {
+        format!(".pipe(map((body) => withoutPrototypes(body) as {returns}))")
+    } else {
+        String::new()
+    };
+
+    format!(
+        "{}{indent}{member}({parameters}): Observable<{returns}> {{\n\
+         {indent}{indent}return this.http.request<{returns}>(\"{method}\", \
+         `${{this.baseUrl}}{path}{query}`{options}){sanitize};\n\
+         {indent}}}\n",
+        render_doc(
+            route.description.as_deref(),
+            route.deprecated.as_ref(),
+            &indent
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_angular() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": { "id": { "type": "Number", "required": true } },
+                "new_user": { "name": { "type": "String", "required": true } },
+                "user_filter": { "name": { "type": "String", "required": false } },
+                "tenant": { "X-Tenant-Id": { "type": "String", "required": true } },
+                "empty": {}
+            },
+            "routes": {
+                "get_user": {
+                    "accepts": "empty",
+                    "returns": "user",
+                    "method": "GET",
+                    "path": "/users/{id}",
+                    "params": { "id": { "type": "Number", "required": true } },
+                    "query": "user_filter",
+                    "tags": ["users"],
+                    "description": "Returns a user."
+                },
+                "create_user": {
+                    "accepts": "new_user",
+                    "returns": "user",
+                    "method": "POST",
+                    "path": "/users",
+                    "headers": "tenant",
+                    "tags": ["users"]
+                },
+                "ping": { "accepts": "empty", "returns": "empty" }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_angular(&api_docs, &TypeScriptConfig::default()),
+            r#"
+import { Injectable, InjectionToken, inject } from "@angular/core";
+import { HttpClient } from "@angular/common/http";
+import { type Observable } from "rxjs";
+
+export interface Empty {}
+
+export interface NewUser {
+  name: string;
+}
+
+export interface Tenant {
+  "X-Tenant-Id": string;
+}
+
+export interface User {
+  id: number;
+}
+
+export interface UserFilter {
+  name?: string | undefined;
+}
+
+function serializeQuery(query: object): string {
+  const search = new URLSearchParams();
+  for (const [key, value] of Object.entries(query)) {
+    for (const item of Array.isArray(value) ? value : [value]) {
+      if (item !== undefined && item !== null) {
+        search.append(key, String(item));
+      }
+    }
+  }
+  const serialized = search.toString();
+  return serialized === "" ? "" : `?${serialized}`;
+}
+
+function serializeHeaders(headers: object): Record<string, string> {
+  const serialized: Record<string, string> = {};
+  for (const [name, value] of Object.entries(headers)) {
+    if (value !== undefined && value !== null) {
+      serialized[name] = String(value);
+    }
+  }
+  return serialized;
+}
+
+/** URL the paths of routes are relative to, e.g. `https://example.com/api`. */
+export const API_BASE_URL = new InjectionToken<string>("API_BASE_URL");
+
+@Injectable({ providedIn: "root" })
+export class ApiService {
+  private readonly http = inject(HttpClient);
+  private readonly baseUrl = inject(API_BASE_URL, { optional: true }) ?? "";
+
+  ping(body: Empty): Observable<Empty> {
+    return this.http.request<Empty>("POST", `${this.baseUrl}/ping`, { body });
+  }
+}
+
+@Injectable({ providedIn: "root" })
+export class UsersService {
+  private readonly http = inject(HttpClient);
+  private readonly baseUrl = inject(API_BASE_URL, { optional: true }) ?? "";
+
+  create(headers: Tenant, body: NewUser): Observable<User> {
+    return this.http.request<User>("POST", `${this.baseUrl}/users`, { headers: serializeHeaders(headers), body });
+  }
+
+  /** Returns a user. */
+  get(params: { id: number }, query: UserFilter): Observable<User> {
+    return this.http.request<User>("GET", `${this.baseUrl}/users/${encodeURIComponent(params.id)}${serializeQuery(query)}`);
+  }
+}
+
+"#
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! What each target can express. Targets render specs using features they
+//! lack with a documented fallback, and [`check`] warns about every place a
+//! fallback is used so the output isn't silently less precise than the spec.
+
+use std::collections::BTreeMap;
+
+use crate::diagnostics::Diagnostic;
+use crate::{ApiDocs, ApiDocsModel, ApiDocsModelObject, ApiDocsModelObjectType, Target};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Capability {
+    /// Client functions for routes. Without it, routes are skipped.
+    Routes,
+    /// Enums and literals with number or boolean values. Without it, they are
+    /// typed by their values' type and any value of that type is accepted.
+    LiteralEnums,
+    /// Validation constraints like `min_length` or `pattern`. Without it,
+    /// they are ignored.
+    Constraints,
+    /// Files. Without it, they are typed as bytes.
+    Files,
+}
+
+impl Capability {
+    /// What a target lacking the capability does instead.
+    fn fallback(self) -> &'static str {
+        match self {
+            Capability::Routes => "can't generate routes; they are skipped",
+            Capability::LiteralEnums => {
+                "can't restrict values to non-string enum members or literals; any value of \
+                 their type is accepted"
+            },
+            Capability::Constraints => "can't enforce validation constraints; they are ignored",
+            Capability::Files => "can't type files; they are typed as bytes",
+        }
+    }
+}
+
+impl Target {
+    pub fn supports(self, capability: Capability) -> bool {
+        match (self, capability) {
+            (
+                Target::TypeScript
+                | Target::FetchClient
+                | Target::ReactQuery
+                | Target::Swr
+                | Target::Angular,
+                Capability::Routes | Capability::LiteralEnums | Capability::Files,
+            ) => true,
+            (
+                Target::TypeScript
+                | Target::FetchClient
+                | Target::ReactQuery
+                | Target::Swr
+                | Target::Angular,
+                Capability::Constraints,
+            ) => false,
+            (Target::Zod, Capability::Routes) => false,
+            (
+                Target::Zod,
+                Capability::LiteralEnums | Capability::Constraints | Capability::Files,
+            ) => true,
+            (Target::Msw, Capability::Routes | Capability::LiteralEnums) => true,
+            // Payloads only honor the bounds of lengths and numbers, and can't
+            // carry files.
+            (Target::Msw, Capability::Constraints | Capability::Files) => false,
+            (Target::Rust, _) => false,
+            // Only calls routes, whose models are checked by the Zod target.
+            (Target::Smoke, _) => true,
+        }
+    }
+}
+
+/// Returns a warning for every use of a feature `target` doesn't support and
+/// every model name lost to another one when converted to a type name.
+pub fn check(api_docs: &ApiDocs, target: Target) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        target,
+        diagnostics: Vec::new(),
+    };
+    let mut type_names = BTreeMap::new();
+    for name in api_docs.models.keys() {
+        let type_name = heck::AsPascalCase(name).to_string();
+        if let Some(other) = type_names.insert(type_name.clone(), name) {
+            checker.diagnostics.push(Diagnostic::warning(
+                name,
+                format!(
+                    "the {target} target names both `{other}` and `{name}` `{type_name}`; only \
+                     one of them is generated"
+                ),
+            ));
+        }
+    }
+    for (name, model) in &api_docs.models {
+        checker.check_fields(name, &model.fields);
+    }
+    if !api_docs.routes.is_empty() {
+        checker.require("routes", Capability::Routes);
+    }
+    if !api_docs.sockets.is_empty() {
+        checker.require("sockets", Capability::Routes);
+    }
+    checker.diagnostics
+}
+
+struct Checker {
+    target: Target,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    fn require(&mut self, path: &str, capability: Capability) {
+        if !self.target.supports(capability) {
+            self.diagnostics.push(Diagnostic::warning(
+                path,
+                format!("the {} target {}", self.target, capability.fallback()),
+            ));
+        }
+    }
+
+    fn check_fields(&mut self, path: &str, fields: &ApiDocsModelObject) {
+        for (name, model) in fields {
+            self.check_model(&format!("{path}.{name}"), model);
+        }
+    }
+
+    fn check_model(&mut self, path: &str, model: &ApiDocsModel) {
+        if model.min_length.is_some()
+            || model.max_length.is_some()
+            || model.minimum.is_some()
+            || model.maximum.is_some()
+            || model.pattern.is_some()
+        {
+            self.require(path, Capability::Constraints);
+        }
+        match model.r#type {
+            ApiDocsModelObjectType::Object => {
+                if let Some(fields) = &model.fields {
+                    self.check_fields(path, fields);
+                }
+            },
+            ApiDocsModelObjectType::Array => {
+                if let Some(item) = &model.model {
+                    self.check_model(&format!("{path}[]"), item);
+                }
+            },
+            ApiDocsModelObjectType::Map => {
+                if let Some(value) = &model.values {
+                    self.check_model(&format!("{path}{{}}"), value);
+                }
+            },
+            ApiDocsModelObjectType::Tuple => {
+                for (index, item) in model.items.iter().flatten().enumerate() {
+                    self.check_model(&format!("{path}[{index}]"), item);
+                }
+            },
+            ApiDocsModelObjectType::Union => {
+                for (segment, alternative) in model.alternatives() {
+                    self.check_model(&format!("{path}|{segment}"), alternative);
+                }
+            },
+            ApiDocsModelObjectType::Enum => {
+                if model
+                    .members
+                    .iter()
+                    .flatten()
+                    .any(|member| !member.is_string())
+                {
+                    self.require(path, Capability::LiteralEnums);
+                }
+            },
+            ApiDocsModelObjectType::Literal => {
+                if model.value.as_ref().is_some_and(|value| !value.is_string()) {
+                    self.require(path, Capability::LiteralEnums);
+                }
+            },
+            ApiDocsModelObjectType::File => self.require(path, Capability::Files),
+            ApiDocsModelObjectType::String
+            | ApiDocsModelObjectType::Number
+            | ApiDocsModelObjectType::Boolean
+            | ApiDocsModelObjectType::Ref => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_rust() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "role": { "type": "Enum", "required": true, "members": ["admin"] },
+                    "levels": {
+                        "type": "Array",
+                        "required": true,
+                        "model": { "type": "Enum", "required": true, "members": [1, 2] }
+                    }
+                }
+            },
+            "routes": { "get_user": { "accepts": "user", "returns": "user" } }
+        }))
+        .unwrap();
+
+        assert!(check(&api_docs, Target::TypeScript).is_empty());
+        k9::snapshot!(
+            check(&api_docs, Target::Rust)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            r#"
+warning: user.levels[]: the rust target can't restrict values to non-string enum members or literals; any value of their type is accepted
+warning: routes: the rust target can't generate routes; they are skipped
+"#
+        );
+    }
+
+    #[test]
+    fn test_check_type_name_collisions() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": { "user_id": {}, "userId": {} },
+            "routes": {}
+        }))
+        .unwrap();
+        k9::snapshot!(check(&api_docs, Target::Zod)[0].to_string(), "warning: user_id: the zod target names both `userId` and `user_id` `UserId`; only one of them is generated");
+    }
+}
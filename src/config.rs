@@ -0,0 +1,386 @@
+//! Generator configuration, read from a JSON file passed with `--config`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ApiDocsModelFormat;
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub limits: LimitsConfig,
+    pub lint: LintConfig,
+    pub typescript: TypeScriptConfig,
+    pub rust: RustConfig,
+    pub smoke: SmokeConfig,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypeScriptConfig {
+    /// Number of spaces per indentation level.
+    pub tab_width: usize,
+    /// Indent with tabs instead of spaces.
+    pub use_tabs: bool,
+    /// Keep nested objects on a single line when the line fits within this
+    /// many characters, instead of always breaking them across lines.
+    /// Signatures, calls and unions are broken at this width, or at
+    /// prettier's default of 80 without it.
+    pub max_line_width: Option<usize>,
+    /// Type used for integers instead of `number`, e.g. a branded type
+    /// provided through `imports`.
+    pub integer_type: Option<String>,
+    /// Types used for strings with a format instead of `string`, e.g.
+    /// `Date` or `Temporal.Instant`.
+    pub formats: BTreeMap<ApiDocsModelFormat, String>,
+    /// Type `uuid`, `email` and `uri` strings with branded types, e.g.
+    /// `Uuid`, which plain strings can't be assigned to without a check.
+    pub branded_formats: bool,
+    /// Emit an `ApiError` class hierarchy for transports to throw, so callers
+    /// can tell HTTP, network and validation failures apart with
+    /// `instanceof`.
+    pub error_classes: bool,
+    /// Copy responses into objects without a prototype, dropping `__proto__`,
+    /// `constructor` and `prototype` keys, so hostile responses can't
+    /// pollute prototypes.
+    pub null_prototype: bool,
+    /// Lift objects nested in models into interfaces named after their path,
+    /// e.g. `UserAddress`, instead of rendering them inline.
+    pub hoist_inline_objects: bool,
+    /// Also group client functions into an `api` object with a namespace per
+    /// route tag, e.g. `api.users.create`. Words of the tag are left out of
+    /// the names of members when that doesn't make two of them clash.
+    pub namespaces: bool,
+    /// Keep the client small in bundles: helpers are written tersely and
+    /// `namespaces` is ignored, so that unused client functions can be
+    /// tree-shaken.
+    pub optimize_size: bool,
+    /// Style of the generated client.
+    pub client: TypeScriptClient,
+    /// Base URLs of the services of merged specs in the fetch client, keyed
+    /// by service. The `services` option of `ApiClient` takes precedence.
+    pub service_urls: BTreeMap<String, String>,
+    /// Let the fetch client report every call to an analytics sink, given as
+    /// the `analytics` option of `ApiClient`.
+    pub analytics: bool,
+    /// How the types of non-required models are wrapped.
+    pub optional: OptionalWrapper,
+    /// Modules providing types used but not defined by the generated code,
+    /// keyed by type name. Imports are only emitted for types in use.
+    pub imports: BTreeMap<String, String>,
+    /// Comments turning off linters and type checking for the generated
+    /// file, emitted at its top.
+    pub suppressions: Vec<TypeScriptSuppression>,
+    /// Forbid suppressions, for projects that lint generated code like any
+    /// other code, and render objects without fields as
+    /// `Record<string, never>` instead of `{}`, which typescript-eslint
+    /// rejects.
+    pub lint_clean: bool,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RustConfig {
+    /// Types used for strings with a format instead of `String`, e.g.
+    /// `chrono::DateTime<chrono::Utc>`.
+    pub formats: BTreeMap<ApiDocsModelFormat, String>,
+    /// Attributes turning off lints for the generated file, emitted at its
+    /// top.
+    pub suppressions: Vec<RustSuppression>,
+    /// Forbid suppressions, for projects that lint generated code like any
+    /// other code, and box the variants of unions, which clippy warns about
+    /// when they differ in size.
+    pub lint_clean: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionalWrapper {
+    /// `T | undefined`
+    #[default]
+    Undefined,
+    /// `T | null`
+    Null,
+    /// A generic type provided by the project, e.g. `Optional<T>`.
+    Generic(String),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeScriptClient {
+    /// A function per route.
+    #[default]
+    Functions,
+    /// A constant per route and a single generic `request` function, for
+    /// the smallest bundles.
+    Minimal,
+}
+
+impl std::str::FromStr for TypeScriptClient {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "functions" => Ok(Self::Functions),
+            "minimal" => Ok(Self::Minimal),
+            _ => Err(format!(
+                "unknown client `{s}`, expected `functions` or `minimal`"
+            )),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TypeScriptSuppression {
+    Eslint,
+    TsNocheck,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RustSuppression {
+    Clippy,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    pub naming: NamingConfig,
+    pub descriptions: DescriptionsConfig,
+    /// Fail if models and routes that aren't deprecated use deprecated
+    /// models.
+    pub fail_on_deprecated_use: bool,
+}
+
+/// Options of the smoke test script.
+#[derive(Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmokeConfig {
+    /// Module the Zod target is generated to, whose schemas responses are
+    /// checked against.
+    pub schemas: String,
+    /// Routes to call, keyed by name. When empty, every `GET` route without
+    /// parameters is called.
+    pub routes: BTreeMap<String, SmokeRoute>,
+}
+
+impl Default for SmokeConfig {
+    fn default() -> Self {
+        Self {
+            schemas: "./schemas".to_string(),
+            routes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Values to call a route with in the smoke test script.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmokeRoute {
+    /// Values of the path parameters, keyed by name.
+    pub params: BTreeMap<String, serde_json::Value>,
+    /// Values of the query parameters, keyed by name. Arrays repeat the
+    /// parameter.
+    pub query: BTreeMap<String, serde_json::Value>,
+}
+
+/// Limits on the size of specs. None are enforced by default.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Maximum size of the spec file in bytes.
+    pub max_file_size: Option<u64>,
+    pub max_models: Option<usize>,
+    /// Maximum number of fields of a model or inline object.
+    pub max_fields: Option<usize>,
+    pub max_enum_members: Option<usize>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DescriptionsConfig {
+    /// Warn about every model, field and route without a description.
+    pub required: bool,
+    /// Fail if the percentage of documented models, fields and routes is
+    /// below this threshold.
+    pub min_coverage: Option<f64>,
+}
+
+/// Naming conventions enforced by `lint`. Every rule is opt-in.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamingConfig {
+    /// Rule for the keys of `models`.
+    pub models: NamingRule,
+    /// Rule for field names of models and inline objects.
+    pub fields: NamingRule,
+    pub banned_prefixes: Vec<String>,
+    pub banned_suffixes: Vec<String>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamingRule {
+    pub case: Option<Case>,
+    #[serde(
+        deserialize_with = "deserialize_pattern",
+        serialize_with = "serialize_pattern"
+    )]
+    pub pattern: Option<regex::Regex>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum Case {
+    #[serde(rename = "snake_case")]
+    Snake,
+    #[serde(rename = "camelCase")]
+    Camel,
+    #[serde(rename = "PascalCase")]
+    Pascal,
+    #[serde(rename = "kebab-case")]
+    Kebab,
+    #[serde(rename = "SCREAMING_SNAKE_CASE")]
+    ScreamingSnake,
+}
+
+impl Default for TypeScriptConfig {
+    fn default() -> Self {
+        Self {
+            tab_width: 2,
+            use_tabs: false,
+            max_line_width: None,
+            integer_type: None,
+            formats: BTreeMap::new(),
+            branded_formats: false,
+            error_classes: false,
+            hoist_inline_objects: false,
+            namespaces: false,
+            optimize_size: false,
+            client: TypeScriptClient::default(),
+            service_urls: BTreeMap::new(),
+            analytics: false,
+            null_prototype: false,
+            optional: OptionalWrapper::default(),
+            imports: BTreeMap::new(),
+            suppressions: Vec::new(),
+            lint_clean: false,
+        }
+    }
+}
+
+impl Config {
+    pub fn validate(&self) -> eyre::Result<()> {
+        if self.typescript.lint_clean && !self.typescript.suppressions.is_empty() {
+            eyre::bail!("`typescript.suppressions` can't be used with `typescript.lint_clean`");
+        }
+        if self.rust.lint_clean && !self.rust.suppressions.is_empty() {
+            eyre::bail!("`rust.suppressions` can't be used with `rust.lint_clean`");
+        }
+        Ok(())
+    }
+}
+
+impl TypeScriptConfig {
+    /// Indentation for a line nested `level` deep.
+    pub(crate) fn indent(&self, level: usize) -> String {
+        if self.use_tabs {
+            "\t".repeat(level)
+        } else {
+            " ".repeat(self.tab_width * level)
+        }
+    }
+
+    /// Width lines are broken at, prettier's default unless
+    /// `max_line_width` is set.
+    pub(crate) fn line_width(&self) -> usize {
+        self.max_line_width.unwrap_or(80)
+    }
+}
+
+impl TypeScriptSuppression {
+    pub fn comment(self) -> &'static str {
+        match self {
+            TypeScriptSuppression::Eslint => "/* eslint-disable */",
+            TypeScriptSuppression::TsNocheck => "// @ts-nocheck",
+        }
+    }
+}
+
+impl RustSuppression {
+    pub fn attribute(self) -> &'static str {
+        match self {
+            RustSuppression::Clippy => "#![allow(clippy::all)]",
+        }
+    }
+}
+
+impl Case {
+    pub fn convert(self, name: &str) -> String {
+        match self {
+            Case::Snake => heck::AsSnakeCase(name).to_string(),
+            Case::Camel => heck::AsLowerCamelCase(name).to_string(),
+            Case::Pascal => heck::AsPascalCase(name).to_string(),
+            Case::Kebab => heck::AsKebabCase(name).to_string(),
+            Case::ScreamingSnake => heck::AsShoutySnakeCase(name).to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Case {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Case::Snake => "snake_case",
+            Case::Camel => "camelCase",
+            Case::Pascal => "PascalCase",
+            Case::Kebab => "kebab-case",
+            Case::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+        })
+    }
+}
+
+fn deserialize_pattern<'de, D>(deserializer: D) -> Result<Option<regex::Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|pattern| regex::Regex::new(&pattern).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+fn serialize_pattern<S>(pattern: &Option<regex::Regex>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    pattern
+        .as_ref()
+        .map(regex::Regex::as_str)
+        .serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_clean_rejects_suppressions() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "typescript": { "suppressions": ["eslint"], "lint_clean": true }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            config.validate().unwrap_err().to_string(),
+            "`typescript.suppressions` can't be used with `typescript.lint_clean`"
+        );
+
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "rust": { "suppressions": ["clippy"], "lint_clean": true }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            config.validate().unwrap_err().to_string(),
+            "`rust.suppressions` can't be used with `rust.lint_clean`"
+        );
+    }
+}
@@ -0,0 +1,537 @@
+//! Generation of a ready-to-use client: the TypeScript target along with an
+//! `ApiClient` class sending requests with `fetch`, so that the transport
+//! doesn't have to be written by hand.
+//!
+//! Bodies are sent and responses read as JSON, except for multipart bodies.
+//! Responses with an error status are thrown as `HttpError`s carrying their
+//! body, and failed requests as `NetworkError`s.
+//!
+//! Routes of merged specs are sent to the base URL of their service, if it
+//! has one. Headers, including those of `auth`, are shared by all services.
+//!
+//! With `analytics`, every call is reported as an `ApiEvent` to the sink
+//! given to the client, including calls that failed.
+
+use crate::config::{TypeScriptClient, TypeScriptConfig};
+use crate::{
+    render_doc, render_property_name, render_services, render_typescript, ApiDocs,
+    TypeScriptRenderer,
+};
+
+pub(crate) fn render_fetch_client(api_docs: &ApiDocs, config: &TypeScriptConfig) -> String {
+    // The client maps failed responses to the error classes, and its methods
+    // call the route functions.
+    let config = TypeScriptConfig {
+        error_classes: true,
+        client: TypeScriptClient::Functions,
+        ..config.clone()
+    };
+    let renderer = TypeScriptRenderer::without_docs(&config);
+    let indent = config.indent(1);
+
+    let methods = api_docs
+        .routes
+        .iter()
+        .map(|(name, route)| {
+            let name = heck::AsLowerCamelCase(name).to_string();
+            let parameters = renderer.render_route_parameters(route);
+            let arguments = parameters
+                .iter()
+                .map(|parameter| match parameter.split_once(':') {
+                    Some(("request", _)) => "this.request",
+                    Some((parameter, _)) => parameter,
+                    None => parameter,
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let parameters = parameters[1..].join(", ");
+            let returns = heck::AsPascalCase(&route.returns);
+            let mut method = format!(
+                "\n{}{indent}{name}({parameters}): Promise<{returns}> {{\n\
+                 {indent}{indent}return {name}({arguments});\n\
+                 {indent}}}\n",
+                render_doc(
+                    route.description.as_deref(),
+                    route.deprecated.as_ref(),
+                    &indent
+                ),
+            );
+            if route.pagination.is_some() {
+                method.push_str(&format!(
+                    "\n{indent}{name}Pages({parameters}): AsyncGenerator<{returns}, void> {{\n\
+                     {indent}{indent}return {name}Pages({arguments});\n\
+                     {indent}}}\n",
+                ));
+            }
+            method
+        })
+        .collect::<String>();
+
+    let transport = if api_docs.routes.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\n{indent}/** Sends requests for the route functions, e.g. `getUser(client.request, ...)`. */\n\
+             {indent}readonly request: ApiRequest = (route, body, options?: RequestOptions) =>\n\
+             {indent}{indent}this.send(route, body, options);\n"
+        )
+    };
+    let services = render_services(&api_docs.routes);
+    let (service_type, service_option, service_urls, service_url) = if services.is_empty() {
+        Default::default()
+    } else {
+        let urls = config
+            .service_urls
+            .iter()
+            .map(|(service, url)| {
+                format!(
+                    "{indent}{}: {},\n",
+                    render_property_name(service),
+                    serde_json::Value::from(url.as_str())
+                )
+            })
+            .collect::<String>();
+        (
+            format!("export type Service = {services};\n\n"),
+            format!(
+                "{indent}/** Base URLs of services, replacing `baseUrl` for their routes. */\n\
+                 {indent}services?: Partial<Record<Service, string>>;\n"
+            ),
+            format!("const serviceUrls: Partial<Record<Service, string>> = {{\n{urls}}};\n\n"),
+            format!(
+                "{indent}{indent}const serviceUrl =\n\
+                 {indent}{indent}{indent}options?.service &&\n\
+                 {indent}{indent}{indent}(this.options.services?.[options.service] ?? \
+                 serviceUrls[options.service]);\n"
+            ),
+        )
+    };
+    let analytics = config.analytics;
+    let tracked = |line: String| if analytics { line } else { String::new() };
+    let event_type = tracked(format!(
+        "export interface ApiEvent {{\n\
+         {indent}route: string;\n\
+         {indent}/** Milliseconds from sending the request to receiving the whole response. */\n\
+         {indent}duration: number;\n\
+         {indent}/** Unset if the request failed without a response. */\n\
+         {indent}status?: number;\n\
+         {indent}/** Bytes of the JSON body, unset without one or for multipart bodies. */\n\
+         {indent}requestSize?: number;\n\
+         {indent}/** Bytes of the response body. */\n\
+         {indent}responseSize?: number;\n\
+         }}\n\n"
+    ));
+    let track = tracked(format!(
+        "\n{indent}private track(\n\
+         {indent}{indent}route: string,\n\
+         {indent}{indent}start: number,\n\
+         {indent}{indent}payload: string | FormData | undefined,\n\
+         {indent}{indent}response?: {{ status: number; text: string }},\n\
+         {indent}) {{\n\
+         {indent}{indent}const size = (text: string) => new TextEncoder().encode(text).length;\n\
+         {indent}{indent}this.options.analytics?.({{\n\
+         {indent}{indent}{indent}route,\n\
+         {indent}{indent}{indent}duration: performance.now() - start,\n\
+         {indent}{indent}{indent}status: response?.status,\n\
+         {indent}{indent}{indent}requestSize: typeof payload === \"string\" ? size(payload) : undefined,\n\
+         {indent}{indent}{indent}responseSize: response && size(response.text),\n\
+         {indent}{indent}}});\n\
+         {indent}}}\n"
+    ));
+    let client = format!(
+        "{service_type}\
+         {event_type}\
+         export interface ApiClientOptions {{\n\
+         {indent}/** URL the paths of routes are relative to, e.g. `https://example.com/api`. */\n\
+         {indent}baseUrl: string;\n\
+         {service_option}\
+         {indent}/** Headers sent with every request, e.g. `Accept-Language`. */\n\
+         {indent}headers?: Record<string, string>;\n\
+         {indent}/** Returns headers authenticating every request, e.g. a fresh `Authorization`. */\n\
+         {indent}auth?: () => Record<string, string> | Promise<Record<string, string>>;\n\
+         {indent}/** Replaces the global `fetch`, e.g. to add retries. */\n\
+         {indent}fetch?: typeof fetch;\n\
+         {analytics_option}\
+         }}\n\
+         \n\
+         export interface RequestOptions {{\n\
+         {service_field}\
+         {indent}method?: string;\n\
+         {indent}contentType?: string;\n\
+         {indent}path?: string;\n\
+         {indent}query?: string;\n\
+         {indent}headers?: Record<string, string>;\n\
+         }}\n\
+         \n\
+         {service_urls}\
+         export class ApiClient {{\n\
+         {indent}constructor(private readonly options: ApiClientOptions) {{}}\n\
+         {transport}\
+         \n\
+         {indent}/** Sends a request to `route`, by default a `POST` to `/route`. */\n\
+         {indent}async send(route: string, body: unknown, options?: RequestOptions): Promise<unknown> {{\n\
+         {start}\
+         {service_url}\
+         {indent}{indent}const baseUrl = {base_url}.replace(/\\/$/, \"\");\n\
+         {indent}{indent}const query = options?.query ? `?${{options.query}}` : \"\";\n\
+         {indent}{indent}const json = body !== undefined && options?.contentType !== \"multipart/form-data\";\n\
+         {indent}{indent}const payload = json ? JSON.stringify(body) : (body as FormData | undefined);\n\
+         {indent}{indent}let response: Response;\n\
+         {indent}{indent}try {{\n\
+         {indent}{indent}{indent}response = await (this.options.fetch ?? fetch)(\n\
+         {indent}{indent}{indent}{indent}`${{baseUrl}}${{options?.path ?? `/${{route}}`}}${{query}}`,\n\
+         {indent}{indent}{indent}{indent}{{\n\
+         {indent}{indent}{indent}{indent}{indent}method: options?.method ?? \"POST\",\n\
+         {indent}{indent}{indent}{indent}{indent}headers: {{\n\
+         {indent}{indent}{indent}{indent}{indent}{indent}...(json ? {{ \"Content-Type\": \"application/json\" }} : {{}}),\n\
+         {indent}{indent}{indent}{indent}{indent}{indent}...this.options.headers,\n\
+         {indent}{indent}{indent}{indent}{indent}{indent}...options?.headers,\n\
+         {indent}{indent}{indent}{indent}{indent}{indent}...(await this.options.auth?.()),\n\
+         {indent}{indent}{indent}{indent}{indent}}},\n\
+         {indent}{indent}{indent}{indent}{indent}body: payload,\n\
+         {indent}{indent}{indent}{indent}}},\n\
+         {indent}{indent}{indent});\n\
+         {indent}{indent}}} catch (error) {{\n\
+         {track_failure}\
+         {indent}{indent}{indent}throw new NetworkError(`Request to \\`${{route}}\\` failed`, {{ cause: error }});\n\
+         {indent}{indent}}}\n\
+         {indent}{indent}const text = await response.text();\n\
+         {track_response}\
+         {indent}{indent}const data: unknown = text === \"\" ? undefined : JSON.parse(text);\n\
+         {indent}{indent}if (!response.ok) {{\n\
+         {indent}{indent}{indent}throw new HttpError(response.status, data);\n\
+         {indent}{indent}}}\n\
+         {indent}{indent}return data;\n\
+         {indent}}}\n\
+         {track}\
+         {methods}\
+         }}\n",
+        analytics_option = tracked(format!(
+            "{indent}/** Receives an event for every call, e.g. to send to product analytics. */\n\
+             {indent}analytics?: (event: ApiEvent) => void;\n"
+        )),
+        start = tracked(format!("{indent}{indent}const start = performance.now();\n")),
+        track_failure = tracked(format!(
+            "{indent}{indent}{indent}this.track(route, start, payload);\n"
+        )),
+        track_response = tracked(format!(
+            "{indent}{indent}this.track(route, start, payload, {{ status: response.status, text }});\n"
+        )),
+        base_url = if services.is_empty() {
+            "this.options.baseUrl"
+        } else {
+            "(serviceUrl ?? this.options.baseUrl)"
+        },
+        service_field = if services.is_empty() {
+            String::new()
+        } else {
+            format!("{indent}service?: Service;\n")
+        },
+    );
+    format!("{}\n{client}", render_typescript(api_docs, &config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_fetch_client() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": { "id": { "type": "Number", "required": true } },
+                "user_id": {}
+            },
+            "routes": {
+                "get_user": {
+                    "accepts": "user_id",
+                    "returns": "user",
+                    "method": "GET",
+                    "path": "/users/{id}",
+                    "params": { "id": { "type": "Number", "required": true } },
+                    "description": "Returns a user."
+                },
+                "create_user": { "accepts": "user", "returns": "user" }
+            }
+        }))
+        .unwrap();
+        let output = render_fetch_client(&api_docs, &TypeScriptConfig::default());
+        k9::snapshot!(
+            &output[output.find("interface ApiClientOptions").unwrap()..],
+            r#"
+interface ApiClientOptions {
+  /** URL the paths of routes are relative to, e.g. `https://example.com/api`. */
+  baseUrl: string;
+  /** Headers sent with every request, e.g. `Accept-Language`. */
+  headers?: Record<string, string>;
+  /** Returns headers authenticating every request, e.g. a fresh `Authorization`. */
+  auth?: () => Record<string, string> | Promise<Record<string, string>>;
+  /** Replaces the global `fetch`, e.g. to add retries. */
+  fetch?: typeof fetch;
+}
+
+export interface RequestOptions {
+  method?: string;
+  contentType?: string;
+  path?: string;
+  query?: string;
+  headers?: Record<string, string>;
+}
+
+export class ApiClient {
+  constructor(private readonly options: ApiClientOptions) {}
+
+  /** Sends requests for the route functions, e.g. `getUser(client.request, ...)`. */
+  readonly request: ApiRequest = (route, body, options?: RequestOptions) =>
+    this.send(route, body, options);
+
+  /** Sends a request to `route`, by default a `POST` to `/route`. */
+  async send(route: string, body: unknown, options?: RequestOptions): Promise<unknown> {
+    const baseUrl = this.options.baseUrl.replace(/\\/$/, "");
+    const query = options?.query ? `?${options.query}` : "";
+    const json = body !== undefined && options?.contentType !== "multipart/form-data";
+    const payload = json ? JSON.stringify(body) : (body as FormData | undefined);
+    let response: Response;
+    try {
+      response = await (this.options.fetch ?? fetch)(
+        `${baseUrl}${options?.path ?? `/${route}`}${query}`,
+        {
+          method: options?.method ?? "POST",
+          headers: {
+            ...(json ? { "Content-Type": "application/json" } : {}),
+            ...this.options.headers,
+            ...options?.headers,
+            ...(await this.options.auth?.()),
+          },
+          body: payload,
+        },
+      );
+    } catch (error) {
+      throw new NetworkError(`Request to \\`${route}\\` failed`, { cause: error });
+    }
+    const text = await response.text();
+    const data: unknown = text === "" ? undefined : JSON.parse(text);
+    if (!response.ok) {
+      throw new HttpError(response.status, data);
+    }
+    return data;
+  }
+
+  createUser(body: User): Promise<User> {
+    return createUser(this.request, body);
+  }
+
+  /** Returns a user. */
+  getUser(params: { id: number }): Promise<User> {
+    return getUser(this.request, params);
+  }
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_fetch_client_services() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": { "user": { "id": { "type": "Number", "required": true } } },
+            "routes": {
+                "billing_get_user": { "accepts": "user", "returns": "user", "service": "billing" },
+                "identity_get_user": { "accepts": "user", "returns": "user", "service": "identity" }
+            }
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            service_urls: [(
+                "billing".to_string(),
+                "https://billing.example.com".to_string(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let output = render_fetch_client(&api_docs, &config);
+        k9::snapshot!(
+            &output[output.find("type Service").unwrap()..output.find("    const text").unwrap()],
+            r#"
+type Service = "billing" | "identity";
+
+export interface ApiClientOptions {
+  /** URL the paths of routes are relative to, e.g. `https://example.com/api`. */
+  baseUrl: string;
+  /** Base URLs of services, replacing `baseUrl` for their routes. */
+  services?: Partial<Record<Service, string>>;
+  /** Headers sent with every request, e.g. `Accept-Language`. */
+  headers?: Record<string, string>;
+  /** Returns headers authenticating every request, e.g. a fresh `Authorization`. */
+  auth?: () => Record<string, string> | Promise<Record<string, string>>;
+  /** Replaces the global `fetch`, e.g. to add retries. */
+  fetch?: typeof fetch;
+}
+
+export interface RequestOptions {
+  service?: Service;
+  method?: string;
+  contentType?: string;
+  path?: string;
+  query?: string;
+  headers?: Record<string, string>;
+}
+
+const serviceUrls: Partial<Record<Service, string>> = {
+  billing: "https://billing.example.com",
+};
+
+export class ApiClient {
+  constructor(private readonly options: ApiClientOptions) {}
+
+  /** Sends requests for the route functions, e.g. `getUser(client.request, ...)`. */
+  readonly request: ApiRequest = (route, body, options?: RequestOptions) =>
+    this.send(route, body, options);
+
+  /** Sends a request to `route`, by default a `POST` to `/route`. */
+  async send(route: string, body: unknown, options?: RequestOptions): Promise<unknown> {
+    const serviceUrl =
+      options?.service &&
+      (this.options.services?.[options.service] ?? serviceUrls[options.service]);
+    const baseUrl = (serviceUrl ?? this.options.baseUrl).replace(/\\/$/, "");
+    const query = options?.query ? `?${options.query}` : "";
+    const json = body !== undefined && options?.contentType !== "multipart/form-data";
+    const payload = json ? JSON.stringify(body) : (body as FormData | undefined);
+    let response: Response;
+    try {
+      response = await (this.options.fetch ?? fetch)(
+        `${baseUrl}${options?.path ?? `/${route}`}${query}`,
+        {
+          method: options?.method ?? "POST",
+          headers: {
+            ...(json ? { "Content-Type": "application/json" } : {}),
+            ...this.options.headers,
+            ...options?.headers,
+            ...(await this.options.auth?.()),
+          },
+          body: payload,
+        },
+      );
+    } catch (error) {
+      throw new NetworkError(`Request to \\`${route}\\` failed`, { cause: error });
+    }
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_fetch_client_analytics() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": { "user": { "id": { "type": "Number", "required": true } } },
+            "routes": { "get_user": { "accepts": "user", "returns": "user" } }
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            analytics: true,
+            ..Default::default()
+        };
+        let output = render_fetch_client(&api_docs, &config);
+        k9::snapshot!(
+            &output[output.find("interface ApiEvent").unwrap()..],
+            r#"
+interface ApiEvent {
+  route: string;
+  /** Milliseconds from sending the request to receiving the whole response. */
+  duration: number;
+  /** Unset if the request failed without a response. */
+  status?: number;
+  /** Bytes of the JSON body, unset without one or for multipart bodies. */
+  requestSize?: number;
+  /** Bytes of the response body. */
+  responseSize?: number;
+}
+
+export interface ApiClientOptions {
+  /** URL the paths of routes are relative to, e.g. `https://example.com/api`. */
+  baseUrl: string;
+  /** Headers sent with every request, e.g. `Accept-Language`. */
+  headers?: Record<string, string>;
+  /** Returns headers authenticating every request, e.g. a fresh `Authorization`. */
+  auth?: () => Record<string, string> | Promise<Record<string, string>>;
+  /** Replaces the global `fetch`, e.g. to add retries. */
+  fetch?: typeof fetch;
+  /** Receives an event for every call, e.g. to send to product analytics. */
+  analytics?: (event: ApiEvent) => void;
+}
+
+export interface RequestOptions {
+  method?: string;
+  contentType?: string;
+  path?: string;
+  query?: string;
+  headers?: Record<string, string>;
+}
+
+export class ApiClient {
+  constructor(private readonly options: ApiClientOptions) {}
+
+  /** Sends requests for the route functions, e.g. `getUser(client.request, ...)`. */
+  readonly request: ApiRequest = (route, body, options?: RequestOptions) =>
+    this.send(route, body, options);
+
+  /** Sends a request to `route`, by default a `POST` to `/route`. */
+  async send(route: string, body: unknown, options?: RequestOptions): Promise<unknown> {
+    const start = performance.now();
+    const baseUrl = this.options.baseUrl.replace(/\\/$/, "");
+    const query = options?.query ? `?${options.query}` : "";
+    const json = body !== undefined && options?.contentType !== "multipart/form-data";
+    const payload = json ? JSON.stringify(body) : (body as FormData | undefined);
+    let response: Response;
+    try {
+      response = await (this.options.fetch ?? fetch)(
+        `${baseUrl}${options?.path ?? `/${route}`}${query}`,
+        {
+          method: options?.method ?? "POST",
+          headers: {
+            ...(json ? { "Content-Type": "application/json" } : {}),
+            ...this.options.headers,
+            ...options?.headers,
+            ...(await this.options.auth?.()),
+          },
+          body: payload,
+        },
+      );
+    } catch (error) {
+      this.track(route, start, payload);
+      throw new NetworkError(`Request to \\`${route}\\` failed`, { cause: error });
+    }
+    const text = await response.text();
+    this.track(route, start, payload, { status: response.status, text });
+    const data: unknown = text === "" ? undefined : JSON.parse(text);
+    if (!response.ok) {
+      throw new HttpError(response.status, data);
+    }
+    return data;
+  }
+
+  private track(
+    route: string,
+    start: number,
+    payload: string | FormData | undefined,
+    response?: { status: number; text: string },
+  ) {
+    const size = (text: string) => new TextEncoder().encode(text).length;
+    this.options.analytics?.({
+      route,
+      duration: performance.now() - start,
+      status: response?.status,
+      requestSize: typeof payload === "string" ? size(payload) : undefined,
+      responseSize: response && size(response.text),
+    });
+  }
+
+  getUser(body: User): Promise<User> {
+    return getUser(this.request, body);
+  }
+}
+
+"#
+        );
+    }
+}
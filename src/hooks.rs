@@ -0,0 +1,112 @@
+//! Parts shared by the targets generating React hooks over the route
+//! functions of the TypeScript target, e.g. React Query and SWR.
+//!
+//! Hooks send requests with the transport given to `ApiRequestProvider`.
+
+use crate::config::{TypeScriptClient, TypeScriptConfig};
+use crate::{
+    render_doc, render_header, render_typescript, ApiDocs, ApiDocsRoute, TypeScriptRenderer,
+};
+
+/// A route along with the names and inputs its hook is rendered from.
+pub(crate) struct RouteHook<'a> {
+    pub name: &'a str,
+    pub route: &'a ApiDocsRoute,
+    /// The route function, e.g. `getUser`.
+    pub function: String,
+    /// The name of the hook without `use`, e.g. `GetUser`.
+    pub hook: String,
+    pub returns: String,
+    /// The parameters of the route function after `request`, e.g.
+    /// `params: { id: number }`.
+    pub parameters: Vec<String>,
+    /// The names of `parameters`, e.g. `params`.
+    pub inputs: Vec<String>,
+    pub doc: String,
+}
+
+impl RouteHook<'_> {
+    /// Renders a call to the route function with the inputs of the hook.
+    pub fn render_call(&self) -> String {
+        let arguments = std::iter::once("request")
+            .chain(self.inputs.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+        format!("{}({})", self.function, arguments.join(", "))
+    }
+}
+
+/// The config the TypeScript target is rendered with for hooks, which call
+/// the route functions.
+pub(crate) fn hooks_config(config: &TypeScriptConfig) -> TypeScriptConfig {
+    TypeScriptConfig {
+        client: TypeScriptClient::Functions,
+        ..config.clone()
+    }
+}
+
+pub(crate) fn route_hooks<'a>(
+    api_docs: &'a ApiDocs, config: &TypeScriptConfig,
+) -> Vec<RouteHook<'a>> {
+    let renderer = TypeScriptRenderer::without_docs(config);
+    api_docs
+        .routes
+        .iter()
+        .map(|(name, route)| {
+            let parameters = renderer.render_route_parameters(route)[1..].to_vec();
+            let inputs = parameters
+                .iter()
+                .map(|parameter| {
+                    parameter
+                        .split_once(':')
+                        .map_or(parameter.as_str(), |(name, _)| name)
+                        .to_string()
+                })
+                .collect();
+            RouteHook {
+                name,
+                route,
+                function: heck::AsLowerCamelCase(name).to_string(),
+                hook: heck::AsPascalCase(name).to_string(),
+                returns: heck::AsPascalCase(&route.returns).to_string(),
+                parameters,
+                inputs,
+                doc: render_doc(route.description.as_deref(), route.deprecated.as_ref(), ""),
+            }
+        })
+        .collect()
+}
+
+/// Renders the TypeScript target with `imports` at its top, followed by the
+/// transport context and `hooks`.
+pub(crate) fn render_hooks_file(
+    api_docs: &ApiDocs, config: &TypeScriptConfig, imports: &str, hooks: &str,
+) -> String {
+    let client = render_typescript(api_docs, config);
+    let header = render_header(config);
+    let client = client.strip_prefix(&header).unwrap_or(&client);
+    if api_docs.routes.is_empty() {
+        return format!("{header}{client}");
+    }
+
+    let indent = config.indent(1);
+    format!(
+        "{header}import {{ createContext, useContext }} from \"react\";\n\
+         {imports}\
+         \n\
+         {client}\n\
+         const ApiRequestContext = createContext<ApiRequest | undefined>(undefined);\n\
+         \n\
+         /** Provides the transport the hooks send requests with. */\n\
+         export const ApiRequestProvider = ApiRequestContext.Provider;\n\
+         \n\
+         function useApiRequest(): ApiRequest {{\n\
+         {indent}const request = useContext(ApiRequestContext);\n\
+         {indent}if (request === undefined) {{\n\
+         {indent}{indent}throw new Error(\"API hooks must be used inside of an `ApiRequestProvider`\");\n\
+         {indent}}}\n\
+         {indent}return request;\n\
+         }}\n\
+         \n\
+         {hooks}"
+    )
+}
@@ -0,0 +1,199 @@
+//! Inference of models from example payloads.
+
+use eyre::Result;
+
+use crate::{ApiDocsModel, ApiDocsModelFormat, ApiDocsModelObject, ApiDocsModelObjectType};
+
+/// Infers a model from sample values of the same shape.
+///
+/// A field is required if it is present in every sample object, and nullable
+/// if it is `null` in any. Array items of all samples are merged into a
+/// single item model. Without a non-null sample, e.g. for a field that is
+/// always `null`, the model is a string described as a placeholder.
+pub fn infer_model(
+    path: &str, samples: &[&serde_json::Value], required: bool,
+) -> Result<ApiDocsModel> {
+    let nullable = samples.iter().any(|sample| sample.is_null());
+    let samples = samples
+        .iter()
+        .copied()
+        .filter(|sample| !sample.is_null())
+        .collect::<Vec<_>>();
+
+    let Some(first) = samples.first() else {
+        return Ok(ApiDocsModel {
+            required,
+            nullable,
+            description: Some(
+                "Placeholder type, as no sample has a value other than `null`.".to_string(),
+            ),
+            ..Default::default()
+        });
+    };
+
+    let r#type = infer_type(first);
+    if let Some(other) = samples.iter().find(|sample| infer_type(sample) != r#type) {
+        eyre::bail!(
+            "Conflicting types for `{path}`: {:?} and {:?}",
+            r#type,
+            infer_type(other)
+        );
+    }
+
+    let mut model = ApiDocsModel {
+        r#type,
+        required,
+        ..Default::default()
+    };
+
+    match r#type {
+        ApiDocsModelObjectType::Number => {
+            let is_integer = |sample: &&serde_json::Value| sample.is_i64() || sample.is_u64();
+            model.format = Some(if samples.iter().all(is_integer) {
+                ApiDocsModelFormat::Integer
+            } else {
+                ApiDocsModelFormat::Float
+            });
+        },
+        ApiDocsModelObjectType::Object => {
+            let objects = samples
+                .iter()
+                .filter_map(|sample| sample.as_object())
+                .collect::<Vec<_>>();
+            model.fields = Some(infer_fields(path, &objects)?);
+        },
+        ApiDocsModelObjectType::Array => {
+            let items = samples
+                .iter()
+                .filter_map(|sample| sample.as_array())
+                .flatten()
+                .collect::<Vec<_>>();
+            model.model = Some(Box::new(infer_model(&format!("{path}[]"), &items, true)?));
+        },
+        _ => {},
+    }
+
+    Ok(model)
+}
+
+fn infer_fields(
+    path: &str, objects: &[&serde_json::Map<String, serde_json::Value>],
+) -> Result<ApiDocsModelObject> {
+    let mut names = objects
+        .iter()
+        .flat_map(|object| object.keys())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let values = objects
+                .iter()
+                .filter_map(|object| object.get(name))
+                .collect::<Vec<_>>();
+            let required = values.len() == objects.len();
+            let mut model = infer_model(&format!("{path}.{name}"), &values, required)?;
+            model.nullable = values.iter().any(|value| value.is_null());
+            Ok((name.clone(), model))
+        })
+        .collect()
+}
+
+fn infer_type(value: &serde_json::Value) -> ApiDocsModelObjectType {
+    match value {
+        serde_json::Value::String(_) | serde_json::Value::Null => ApiDocsModelObjectType::String,
+        serde_json::Value::Number(_) => ApiDocsModelObjectType::Number,
+        serde_json::Value::Bool(_) => ApiDocsModelObjectType::Boolean,
+        serde_json::Value::Array(_) => ApiDocsModelObjectType::Array,
+        serde_json::Value::Object(_) => ApiDocsModelObjectType::Object,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::render_interface;
+
+    #[test]
+    fn test_infer_model_optionality_across_samples() {
+        let samples = [
+            serde_json::json!({ "id": 1, "name": "a", "tags": ["x"] }),
+            serde_json::json!({ "id": 2, "name": null, "tags": [] }),
+            serde_json::json!({ "id": 3, "tags": [], "admin": true }),
+        ];
+        let model = infer_model("user", &samples.iter().collect::<Vec<_>>(), true).unwrap();
+        let rendered = render_interface("User", model.fields.as_ref().unwrap());
+        k9::snapshot!(
+            rendered,
+            "
+export interface User {
+  admin?: boolean | undefined;
+  id: number;
+  name?: string | null | undefined;
+  tags: Array<string>;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_infer_model_nested_array_of_objects() {
+        let samples = [serde_json::json!({
+            "items": [{ "sku": "a", "qty": 1 }, { "sku": "b" }]
+        })];
+        let model = infer_model("order", &samples.iter().collect::<Vec<_>>(), true).unwrap();
+        let rendered = render_interface("Order", model.fields.as_ref().unwrap());
+        k9::snapshot!(
+            rendered,
+            "
+export interface Order {
+  items: Array<{
+    qty?: number | undefined;
+    sku: string;
+  }>;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_infer_model_without_non_null_samples() {
+        let samples = [
+            serde_json::json!({ "id": 1, "deleted_at": null, "tags": [] }),
+            serde_json::json!({ "id": 2, "deleted_at": null, "tags": [] }),
+        ];
+        let model = infer_model("user", &samples.iter().collect::<Vec<_>>(), true).unwrap();
+        let rendered = render_interface("User", model.fields.as_ref().unwrap());
+        k9::snapshot!(
+            rendered,
+            "
+export interface User {
+  /** Placeholder type, as no sample has a value other than `null`. */
+  deleted_at: string | null;
+  id: number;
+  tags: Array<string>;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_infer_model_conflicting_types() {
+        let samples = [
+            serde_json::json!({ "id": 1 }),
+            serde_json::json!({ "id": "1" }),
+        ];
+        let error = infer_model("user", &samples.iter().collect::<Vec<_>>(), true)
+            .err()
+            .unwrap();
+        k9::snapshot!(
+            error.to_string(),
+            "Conflicting types for `user.id`: Number and String"
+        );
+    }
+}
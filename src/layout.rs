@@ -0,0 +1,396 @@
+//! Line breaking of rendered TypeScript. Code is described as groups, which
+//! stay on one line if they fit within the line width and are broken the way
+//! prettier breaks them otherwise, so that formatting the output leaves it
+//! unchanged.
+
+use crate::config::TypeScriptConfig;
+
+/// Code whose groups are broken across lines only if they don't fit.
+pub(crate) enum Doc {
+    Text(String),
+    Concat(Vec<Doc>),
+    /// Comma-separated items between delimiters, e.g. parameters, arguments
+    /// or type parameters, broken one per line.
+    List(List),
+    /// Members of an object type, broken one per line.
+    Members(Vec<Doc>),
+    /// Members of a union, broken one per line, each after a `|`.
+    Union(Vec<Doc>),
+    /// The body of an arrow function, broken onto its own line.
+    Body(Box<Doc>),
+}
+
+pub(crate) struct List {
+    open: &'static str,
+    close: &'static str,
+    items: Vec<Doc>,
+    /// Whether a broken list ends with a comma, which prettier leaves out
+    /// for type arguments.
+    trailing_comma: bool,
+    /// Whether the last item may be broken on its own while the others stay
+    /// on the line, as prettier does for the last argument of calls.
+    hug_last: bool,
+}
+
+impl Doc {
+    pub(crate) fn text(text: impl Into<String>) -> Self {
+        Doc::Text(text.into())
+    }
+
+    fn list(open: &'static str, close: &'static str, items: Vec<Doc>) -> Self {
+        Doc::List(List {
+            open,
+            close,
+            items,
+            trailing_comma: true,
+            hug_last: false,
+        })
+    }
+
+    pub(crate) fn parameters(items: Vec<Doc>) -> Self {
+        Doc::list("(", ")", items)
+    }
+
+    pub(crate) fn arguments(items: Vec<Doc>) -> Self {
+        match Doc::list("(", ")", items) {
+            Doc::List(list) => Doc::List(List {
+                hug_last: true,
+                ..list
+            }),
+            doc => doc,
+        }
+    }
+
+    pub(crate) fn type_parameters(items: Vec<Doc>) -> Self {
+        Doc::list("<", ">", items)
+    }
+
+    pub(crate) fn type_arguments(items: Vec<Doc>) -> Self {
+        match Doc::list("<", ">", items) {
+            Doc::List(list) => Doc::List(List {
+                trailing_comma: false,
+                ..list
+            }),
+            doc => doc,
+        }
+    }
+
+    /// An object literal.
+    pub(crate) fn object(items: Vec<Doc>) -> Self {
+        Doc::list("{", "}", items)
+    }
+
+    pub(crate) fn array(items: Vec<Doc>) -> Self {
+        Doc::list("[", "]", items)
+    }
+
+    /// A union, rendered after a space once it's on the line. A single
+    /// member isn't a union and is broken like the member itself.
+    pub(crate) fn union(mut items: Vec<Doc>) -> Self {
+        if items.len() == 1 {
+            return Doc::Concat(vec![Doc::text(" "), items.remove(0)]);
+        }
+        Doc::Union(items)
+    }
+
+    /// Renders the doc starting on a line nested `level` deep, after its
+    /// indentation.
+    pub(crate) fn render(&self, config: &TypeScriptConfig, level: usize) -> String {
+        let mut printer = Printer {
+            config,
+            out: String::new(),
+            column: width(config, &config.indent(level)),
+        };
+        printer.print(self, level, 0);
+        printer.out
+    }
+
+    /// Renders the doc on a single line.
+    pub(crate) fn flat(&self) -> String {
+        let join = |items: &[Doc], separator| {
+            items
+                .iter()
+                .map(Doc::flat)
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match self {
+            Doc::Text(text) => text.clone(),
+            Doc::Concat(docs) => docs.iter().map(Doc::flat).collect(),
+            Doc::List(list) if list.open == "{" && !list.items.is_empty() => {
+                format!("{{ {} }}", join(&list.items, ", "))
+            },
+            Doc::List(list) => format!("{}{}{}", list.open, join(&list.items, ", "), list.close),
+            Doc::Members(items) => format!("{{ {} }}", join(items, "; ")),
+            Doc::Union(items) => format!(" {}", join(items, " | ")),
+            Doc::Body(body) => format!(" {}", body.flat()),
+        }
+    }
+
+    /// Width of the doc up to where it can first be broken, and whether it
+    /// can be broken at all.
+    fn head(&self, config: &TypeScriptConfig) -> (usize, bool) {
+        match self {
+            Doc::Text(text) => match text.split_once('\n') {
+                Some((line, _)) => (width(config, line), true),
+                None => (width(config, text), false),
+            },
+            Doc::Concat(docs) => head(config, docs),
+            Doc::List(list) if list.items.is_empty() => (list.open.len() + list.close.len(), false),
+            Doc::List(list) => (list.open.len(), true),
+            Doc::Members(_) => (1, true),
+            Doc::Union(_) | Doc::Body(_) => (0, true),
+        }
+    }
+
+    fn ends_with_body(&self) -> bool {
+        match self {
+            Doc::Body(_) => true,
+            Doc::Concat(docs) => docs.last().is_some_and(Doc::ends_with_body),
+            _ => false,
+        }
+    }
+}
+
+fn head(config: &TypeScriptConfig, docs: &[Doc]) -> (usize, bool) {
+    let mut total = 0;
+    for doc in docs {
+        let (width, breaks) = doc.head(config);
+        total += width;
+        if breaks {
+            return (total, true);
+        }
+    }
+    (total, false)
+}
+
+/// Width of `text` in columns, counting tabs as `tab_width` like prettier.
+pub(crate) fn width(config: &TypeScriptConfig, text: &str) -> usize {
+    text.chars()
+        .map(|c| if c == '\t' { config.tab_width } else { 1 })
+        .sum()
+}
+
+/// Splits a rendered type into the members of its outermost union, e.g.
+/// `"a" | { b: "c" | "d" }` into `"a"` and `{ b: "c" | "d" }`.
+pub(crate) fn union_members(r#type: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut previous = ' ';
+    for (index, c) in r#type.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {},
+            }
+        } else {
+            match c {
+                '"' => in_string = true,
+                '(' | '[' | '{' | '<' => depth += 1,
+                // The arrow of function types isn't a bracket.
+                '>' if previous == '=' => {},
+                ')' | ']' | '}' | '>' => depth = depth.saturating_sub(1),
+                '|' if depth == 0 && r#type[index..].starts_with("| ") && index > start => {
+                    members.push(r#type[start..index].trim_end());
+                    start = index + 2;
+                },
+                _ => {},
+            }
+        }
+        previous = c;
+    }
+    members.push(&r#type[start..]);
+    members
+}
+
+struct Printer<'a> {
+    config: &'a TypeScriptConfig,
+    out: String,
+    column: usize,
+}
+
+impl Printer<'_> {
+    fn push(&mut self, text: &str) {
+        match text.rsplit_once('\n') {
+            Some((_, line)) => self.column = width(self.config, line),
+            None => self.column += width(self.config, text),
+        }
+        self.out.push_str(text);
+    }
+
+    fn newline(&mut self, level: usize) {
+        self.push(&format!("\n{}", self.config.indent(level)));
+    }
+
+    /// Whether `doc` fits on the line followed by `rest` more columns.
+    fn fits(&self, doc: &Doc, rest: usize) -> bool {
+        let flat = doc.flat();
+        !flat.contains('\n')
+            && self.column + width(self.config, &flat) + rest <= self.config.line_width()
+    }
+
+    /// Prints `doc` nested `level` deep, followed by `rest` columns that
+    /// can't be broken.
+    fn print(&mut self, doc: &Doc, level: usize, rest: usize) {
+        match doc {
+            Doc::Text(text) => self.push(text),
+            Doc::Concat(docs) => {
+                for (index, doc) in docs.iter().enumerate() {
+                    let (following, breaks) = head(self.config, &docs[index + 1..]);
+                    self.print(
+                        doc,
+                        level,
+                        if breaks { following } else { following + rest },
+                    );
+                }
+            },
+            _ if self.fits(doc, rest) => self.push(&doc.flat()),
+            Doc::List(list) if list.items.is_empty() => self.push(&doc.flat()),
+            Doc::List(list) => {
+                if list.hug_last && self.hug(list, level, rest) {
+                    return;
+                }
+                self.push(list.open);
+                for (index, item) in list.items.iter().enumerate() {
+                    let comma = list.trailing_comma || index + 1 < list.items.len();
+                    self.newline(level + 1);
+                    self.print(item, level + 1, usize::from(comma));
+                    if comma {
+                        self.push(",");
+                    }
+                }
+                self.newline(level);
+                self.push(list.close);
+            },
+            Doc::Members(items) => {
+                self.push("{");
+                for item in items {
+                    self.newline(level + 1);
+                    self.print(item, level + 1, 1);
+                    self.push(";");
+                }
+                self.newline(level);
+                self.push("}");
+            },
+            Doc::Union(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.newline(level + 1);
+                    self.push("| ");
+                    self.print(
+                        item,
+                        level + 1,
+                        if index + 1 < items.len() { 0 } else { rest },
+                    );
+                }
+            },
+            Doc::Body(body) => {
+                self.newline(level + 1);
+                self.print(body, level + 1, rest);
+            },
+        }
+    }
+
+    /// Prints `list` with its other items on the line and only its last one
+    /// broken, if that fits. A broken arrow function body is followed by a
+    /// comma and the closing delimiter on its own line.
+    fn hug(&mut self, list: &List, level: usize, rest: usize) -> bool {
+        let Some((last, init)) = list.items.split_last() else {
+            return false;
+        };
+        let (out, column) = (self.out.len(), self.column);
+        let mut prefix = list.open.to_string();
+        for item in init {
+            prefix.push_str(&item.flat());
+            prefix.push_str(", ");
+        }
+        self.push(&prefix);
+        self.print(last, level, list.close.len() + rest);
+        let printed = &self.out[out..];
+        let first_line = printed.split('\n').next().unwrap_or_default();
+        if !printed.contains('\n')
+            || init.iter().any(|item| item.flat().contains('\n'))
+            || column + width(self.config, first_line) > self.config.line_width()
+        {
+            self.out.truncate(out);
+            self.column = column;
+            return false;
+        }
+        if last.ends_with_body() {
+            self.push(",");
+            self.newline(level);
+        }
+        self.push(list.close);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_members() {
+        k9::snapshot!(
+            union_members(r#""a|b" | { c: "d" | "e" } | ((f: G) => H) | undefined"#),
+            r#"
+[
+    ""a|b"",
+    "{ c: "d" | "e" }",
+    "((f: G) => H)",
+    "undefined",
+]
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_breaks_groups_that_do_not_fit() {
+        let config = TypeScriptConfig {
+            max_line_width: Some(40),
+            ..Default::default()
+        };
+        let call = Doc::Concat(vec![
+            Doc::text("return send"),
+            Doc::arguments(vec![
+                Doc::text("route"),
+                Doc::object(vec![Doc::text("method: \"GET\""), Doc::text("path")]),
+            ]),
+            Doc::text(";"),
+        ]);
+        k9::snapshot!(
+            call.render(&config, 1),
+            r#"
+return send(route, {
+    method: "GET",
+    path,
+  });
+"#
+        );
+        let signature = Doc::Concat(vec![
+            Doc::text("function f"),
+            Doc::type_parameters(vec![Doc::text("T")]),
+            Doc::parameters(vec![
+                Doc::text("first: T"),
+                Doc::Concat(vec![
+                    Doc::text("second:"),
+                    Doc::union(vec![Doc::text("\"some\""), Doc::text("\"other\"")]),
+                ]),
+            ]),
+            Doc::text(": void {"),
+        ]);
+        k9::snapshot!(
+            signature.render(&config, 0),
+            r#"
+function f<T>(
+  first: T,
+  second: "some" | "other",
+): void {
+"#
+        );
+    }
+}
@@ -0,0 +1,5082 @@
+//! Generation of TypeScript interfaces and client functions from API docs
+//! specs, usable from build scripts and other tools without the CLI.
+
+#![allow(unused)]
+
+pub mod angular;
+pub mod capabilities;
+pub mod config;
+pub mod diagnostics;
+pub mod fetch_client;
+mod hooks;
+pub mod infer;
+mod layout;
+pub mod limits;
+pub mod lint;
+pub mod merge;
+pub mod msw;
+pub mod openapi;
+pub mod overrides;
+pub mod react_query;
+pub mod report;
+pub mod rust;
+pub mod sarif;
+pub mod smithy;
+pub mod smoke;
+pub mod snippets;
+pub mod swr;
+pub mod timings;
+pub mod validate;
+pub mod zod;
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use indoc::indoc;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use string_template::Template;
+
+use crate::config::{Config, OptionalWrapper, TypeScriptClient, TypeScriptConfig};
+use crate::diagnostics::Diagnostics;
+use crate::layout::Doc;
+use crate::timings::Timings;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+pub enum ApiDocsModelObjectType {
+    #[default]
+    String,
+    Number,
+    Boolean,
+    Object,
+    Array,
+    Enum,
+    /// Reference to another entry of `models` by name
+    Ref,
+    /// One of several alternative models
+    Union,
+    /// Object with arbitrary keys and values of the same model
+    Map,
+    /// Array with a fixed number of items, each with its own model
+    Tuple,
+    /// A single constant value
+    Literal,
+    /// Binary content, e.g. an uploaded image, only sent in multipart bodies
+    File,
+}
+
+/// Refinement of a model's type.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiDocsModelFormat {
+    /// Whole `number`
+    Integer,
+    /// Floating-point `number`
+    Float,
+    /// `string` holding an ISO 8601 date, e.g. `2024-01-31`
+    Date,
+    /// `string` holding an ISO 8601 date and time with an offset, e.g.
+    /// `2024-01-31T12:00:00Z`
+    DateTime,
+    /// `string` holding an ISO 8601 time, e.g. `12:00:00`
+    Time,
+    /// `string` holding a UUID
+    Uuid,
+    /// `string` holding an email address
+    Email,
+    /// `string` holding an absolute URI
+    Uri,
+}
+
+pub type ApiDocsModelObject = BTreeMap<String, ApiDocsModel>;
+pub type ApiDocsModelsObject = BTreeMap<String, ApiDocsModel>;
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct ApiDocsModel {
+    pub r#type: ApiDocsModelObjectType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ApiDocsModelFormat>,
+    /// Model if `type` is `object`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<ApiDocsModelObject>,
+    /// Model if `type` is `array`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<Box<ApiDocsModel>>,
+    /// Model of the values if `type` is `map`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values: Option<Box<ApiDocsModel>>,
+    /// Models of the items, in order, if `type` is `tuple`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<ApiDocsModel>>,
+    /// Model if `type` is `enum`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<serde_json::Value>>,
+    /// The only allowed value if `type` is `literal`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    /// Name of the referenced model if `type` is `ref`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#ref: Option<String>,
+    /// Alternatives if `type` is `union`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub models: Option<Vec<ApiDocsModel>>,
+    /// Name of the field holding the tag of each variant if `type` is a
+    /// discriminated `union`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<String>,
+    /// Alternatives keyed by their tag if `type` is a discriminated `union`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variants: Option<BTreeMap<String, ApiDocsModel>>,
+    pub required: bool,
+    /// Whether `null` is allowed. Independent of `required`, which is about
+    /// the field being absent.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub nullable: bool,
+    /// Minimum length of strings and arrays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    /// Maximum length of strings and arrays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// Inclusive lower bound of numbers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    /// Inclusive upper bound of numbers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    /// Regular expression strings must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Value of the field in the `makeDefault` factory of its model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    /// Example value of the field, returned by the mocks of the MSW target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    /// Whether the field is only sent by the server, like a generated id.
+    /// Only allowed for fields of models, which then get request and
+    /// response variants.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// Whether the field is only sent by the client, like a password.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub write_only: bool,
+    /// Feature flag the field is behind, e.g. `new_checkout`. Only allowed
+    /// for fields of models, which then get an experimental variant with
+    /// them, e.g. `UserExperimental extends User`, so that consumers opt
+    /// into unstable fields explicitly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<Deprecation>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Gzip,
+    /// Brotli
+    Br,
+}
+
+/// How a route splits its results into pages, requested through a field of
+/// its `query`.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(tag = "style", rename_all = "snake_case")]
+pub enum Pagination {
+    /// Every page holds the cursor of the next one, unset on the last page.
+    Cursor {
+        /// Field of `query` taking the cursor of the page to return.
+        cursor: String,
+        /// Field of `returns` holding the cursor of the next page.
+        next_cursor: String,
+    },
+    /// Pages start at an offset into the results, and the last page is
+    /// empty.
+    Offset {
+        /// Field of `query` taking the number of results to skip.
+        offset: String,
+        /// Field of `returns` holding the results of the page.
+        items: String,
+    },
+}
+
+/// Content type of the body of a route.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum ContentType {
+    #[serde(rename = "application/json")]
+    Json,
+    /// Sent as `FormData`, which can hold files.
+    #[serde(rename = "multipart/form-data")]
+    Multipart,
+}
+
+/// HTTP method of a route.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Options,
+    Trace,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Options => "OPTIONS",
+            Method::Trace => "TRACE",
+        }
+    }
+
+    /// Whether requests with this method can have a body.
+    pub fn has_body(self) -> bool {
+        !matches!(self, Method::Get | Method::Head | Method::Trace)
+    }
+}
+
+/// Marks a model, field or route as deprecated, either with `true` or with
+/// the reason, e.g. what to use instead.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Deprecation {
+    Flag(bool),
+    Reason(String),
+}
+
+/// A named entry of `models`.
+///
+/// Specs may give a bare map of fields instead of the full form when the
+/// model has no other properties.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(remote = "Self", deny_unknown_fields)]
+pub struct ApiDocsModelDefinition {
+    /// Models whose fields are inherited, e.g. shared id and audit fields.
+    /// TypeScript extends their interfaces; other targets copy their fields.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extends: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<Deprecation>,
+    /// Comment emitted verbatim before the generated type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_before: Option<String>,
+    /// Comment emitted verbatim after the generated type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_after: Option<String>,
+    pub fields: ApiDocsModelObject,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct ApiDocsRoute {
+    pub accepts: String,
+    pub returns: String,
+    /// HTTP method of the route, passed on to the transport. Client functions
+    /// of routes whose method can't have a body don't take one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<Method>,
+    /// URL path of the route with parameters in braces, e.g. `/users/{id}`,
+    /// passed on to the transport with the parameters interpolated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Parameters of `path`, taken by the client function as `params`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: ApiDocsModelObject,
+    /// Model of the query parameters, taken by the client function as
+    /// `query` and passed on to the transport as a query string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Model of the headers set on the request, keyed by header name, e.g.
+    /// `X-Tenant-Id`. Taken by the client function as `headers`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<String>,
+    /// Models of the error responses of the route, keyed by status code, e.g.
+    /// `404`. Typed as `HttpError`s carrying them, which the transport throws.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, String>,
+    /// Content type of the body, passed on to the transport. Multipart bodies
+    /// are built from the fields of `accepts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<ContentType>,
+    /// Tags grouping routes by what they're about, e.g. `users`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Scopes the caller needs all of to call the route, e.g. `users:write`.
+    /// The TypeScript target gets a `RoutePermissions` map and `canCall`, so
+    /// that UI code can hide actions the user can't perform.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+    /// Service the route comes from in specs merged from several services,
+    /// e.g. `billing`, passed on to the transport so that it can send the
+    /// request to the service. Routes are namespaced by service instead of
+    /// tags.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    /// Encoding to compress bodies with, passed on to the transport, which
+    /// sets `Accept-Encoding` and `Content-Encoding`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<Compression>,
+    /// Pagination of the route. The functions client gets a helper iterating
+    /// over all pages, e.g. `listUsersPages`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<Deprecation>,
+    /// Comment emitted verbatim before the generated function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_before: Option<String>,
+    /// Comment emitted verbatim after the generated function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_after: Option<String>,
+}
+
+/// Languages code can be generated for.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Target {
+    TypeScript,
+    /// The TypeScript target along with a client sending requests with
+    /// `fetch`.
+    FetchClient,
+    /// The TypeScript target along with React Query hooks.
+    ReactQuery,
+    /// The TypeScript target along with SWR hooks.
+    Swr,
+    /// The models of the TypeScript target along with Angular services
+    /// sending requests with `HttpClient`.
+    Angular,
+    /// MSW request handlers returning mock payloads.
+    Msw,
+    Zod,
+    Rust,
+    /// Script calling `GET` routes of a deployed API and checking their
+    /// responses with the schemas of the Zod target.
+    Smoke,
+}
+
+/// A WebSocket endpoint, over which both sides send messages tagged with
+/// their type, e.g. `{ "type": "joined", "data": { ... } }`.
+#[derive(Default, Deserialize, Serialize)]
+pub struct ApiDocsSocket {
+    /// URL path of the socket, e.g. `/chat`.
+    pub path: String,
+    /// Models of the messages the client sends, keyed by message type.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sends: BTreeMap<String, String>,
+    /// Models of the messages the server sends, keyed by message type.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub receives: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ApiDocs {
+    pub models: BTreeMap<String, ApiDocsModelDefinition>,
+    pub routes: BTreeMap<String, ApiDocsRoute>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sockets: BTreeMap<String, ApiDocsSocket>,
+}
+
+impl ApiDocsRoute {
+    /// Whether requests to the route have a body, the `accepts` model.
+    pub fn has_body(&self) -> bool {
+        self.method.is_none_or(Method::has_body)
+    }
+}
+
+impl ApiDocsModel {
+    /// The alternatives of a union, along with the path segment addressing
+    /// them: their index, or their tag in discriminated unions.
+    pub(crate) fn alternatives(&self) -> impl Iterator<Item = (String, &ApiDocsModel)> {
+        let listed = self.models.iter().flatten().enumerate();
+        let tagged = self.variants.iter().flatten();
+        listed
+            .map(|(index, model)| (index.to_string(), model))
+            .chain(tagged.map(|(tag, model)| (tag.clone(), model)))
+    }
+
+    pub(crate) fn alternatives_mut(&mut self) -> impl Iterator<Item = (String, &mut ApiDocsModel)> {
+        let listed = self.models.iter_mut().flatten().enumerate();
+        let tagged = self.variants.iter_mut().flatten();
+        listed
+            .map(|(index, model)| (index.to_string(), model))
+            .chain(tagged.map(|(tag, model)| (tag.clone(), model)))
+    }
+}
+
+impl ApiDocsModelDefinition {
+    /// Whether the definition needs the full form to be serialized.
+    fn has_properties(&self) -> bool {
+        !self.extends.is_empty()
+            || self.description.is_some()
+            || self.deprecated.is_some()
+            || self.comment_before.is_some()
+            || self.comment_after.is_some()
+    }
+}
+
+impl ApiDocsModelFormat {
+    /// Name of the branded type and TypeScript expression checking that the
+    /// string `value` has the format, for formats that can be branded.
+    fn brand(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            ApiDocsModelFormat::Uuid => Some((
+                "Uuid",
+                "/^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/i.test(value)",
+            )),
+            ApiDocsModelFormat::Email => Some(("Email", "/^[^\\s@]+@[^\\s@]+$/.test(value)")),
+            ApiDocsModelFormat::Uri => Some(("Uri", "URL.canParse(value)")),
+            _ => None,
+        }
+    }
+}
+
+impl Deprecation {
+    /// Whether `deprecation` marks something as deprecated, as
+    /// `"deprecated": false` doesn't.
+    pub fn is_deprecated(deprecation: Option<&Deprecation>) -> bool {
+        !matches!(deprecation, None | Some(Deprecation::Flag(false)))
+    }
+}
+
+impl From<ApiDocsModelObject> for ApiDocsModelDefinition {
+    fn from(fields: ApiDocsModelObject) -> Self {
+        Self {
+            fields,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiDocsModelDefinition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        // In the bare form, a field called `fields` is a model and so has a
+        // string `type`, unlike the fields map of the full form.
+        let is_full_form = value
+            .get("fields")
+            .is_some_and(|fields| !fields.get("type").is_some_and(|t| t.is_string()));
+
+        if is_full_form {
+            ApiDocsModelDefinition::deserialize(value).map_err(serde::de::Error::custom)
+        } else {
+            serde_json::from_value(value)
+                .map(ApiDocsModelObject::into)
+                .map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+impl Serialize for ApiDocsModelDefinition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.has_properties() {
+            ApiDocsModelDefinition::serialize(self, serializer)
+        } else {
+            self.fields.serialize(serializer)
+        }
+    }
+}
+
+fn interface_field_template(name: &str, r#type: &str) -> String {
+    Template::new(indoc! {"
+        {{name}}: {{type}},
+    "})
+    .render(&[("name", name), ("type", r#type)].into())
+}
+
+fn interface_field_object_template(content: &str) -> String {
+    Template::new(indoc! {"
+        {
+            {{content}}
+        },
+    "})
+    .render(&[("content", content)].into())
+}
+
+fn interface_template(name: &str, content: &str) -> String {
+    Template::new(indoc! {"
+        interface {{name}} {
+            {{content}}
+        }
+    "})
+    .render(&[("name", name), ("content", content)].into())
+}
+
+/// Renders TypeScript the way prettier formats it, breaking signatures,
+/// calls and types that don't fit within the line width.
+pub(crate) struct TypeScriptRenderer<'a> {
+    config: &'a TypeScriptConfig,
+    /// Names of rendered types that have a module in `config.imports`.
+    imported: RefCell<BTreeSet<&'a str>>,
+    /// Formats of rendered strings that have a branded type.
+    branded: RefCell<BTreeSet<ApiDocsModelFormat>>,
+    /// Whether descriptions and deprecations of fields are rendered.
+    docs: bool,
+}
+
+impl<'a> TypeScriptRenderer<'a> {
+    pub(crate) fn new(config: &'a TypeScriptConfig) -> Self {
+        Self {
+            config,
+            imported: RefCell::default(),
+            branded: RefCell::default(),
+            docs: true,
+        }
+    }
+
+    /// A renderer leaving out JSDoc comments, for comparing types.
+    pub(crate) fn without_docs(config: &'a TypeScriptConfig) -> Self {
+        Self {
+            docs: false,
+            ..Self::new(config)
+        }
+    }
+
+    /// Returns `name`, recording it as in use if it has to be imported. For
+    /// qualified names like `Temporal.Instant`, the namespace is imported.
+    fn external_type(&self, name: &'a str) -> &'a str {
+        let root = name.split('.').next().unwrap_or(name);
+        if self.config.imports.contains_key(root) {
+            self.imported.borrow_mut().insert(root);
+        }
+        name
+    }
+
+    /// Renders import statements for the types used so far, grouped by
+    /// module.
+    fn render_imports(&self) -> String {
+        let mut modules = BTreeMap::<&str, Vec<&str>>::new();
+        for name in self.imported.borrow().iter() {
+            modules
+                .entry(&self.config.imports[*name])
+                .or_default()
+                .push(name);
+        }
+        modules
+            .into_iter()
+            .map(|(module, names)| {
+                let import = Doc::Concat(vec![
+                    Doc::text("import type "),
+                    Doc::object(names.into_iter().map(Doc::text).collect()),
+                    Doc::text(format!(" from \"{module}\";")),
+                ]);
+                format!("{}\n", import.render(self.config, 0))
+            })
+            .collect()
+    }
+
+    /// Renders the type of `obj` for a position nested `level` deep.
+    pub(crate) fn render_field_type(&self, obj: &ApiDocsModel, level: usize) -> String {
+        self.render_type(obj, level, false)
+    }
+
+    /// Renders the type of `obj`, with objects on a single line if `flat`.
+    fn render_type(&self, obj: &ApiDocsModel, level: usize, flat: bool) -> String {
+        let inner_type = match &obj.r#type {
+            ApiDocsModelObjectType::String => match obj
+                .format
+                .and_then(|format| self.config.formats.get(&format))
+            {
+                Some(r#type) => self.external_type(r#type).to_string(),
+                None => match obj
+                    .format
+                    .filter(|_| self.config.branded_formats)
+                    .and_then(|format| Some((format, format.brand()?)))
+                {
+                    Some((format, (name, _))) => {
+                        self.branded.borrow_mut().insert(format);
+                        name.to_string()
+                    },
+                    None => "string".to_string(),
+                },
+            },
+            ApiDocsModelObjectType::Number => match (&obj.format, &self.config.integer_type) {
+                (Some(ApiDocsModelFormat::Integer), Some(integer_type)) => {
+                    self.external_type(integer_type).to_string()
+                },
+                _ => "number".to_string(),
+            },
+            ApiDocsModelObjectType::Boolean => "boolean".to_string(),
+            ApiDocsModelObjectType::Array => {
+                format!(
+                    "Array<{}>",
+                    self.render_type(
+                        obj.model
+                            .as_ref()
+                            .expect("validated: `model` is set for arrays"),
+                        level,
+                        flat
+                    )
+                )
+            },
+            ApiDocsModelObjectType::Map => format!(
+                "Record<string, {}>",
+                self.render_type(
+                    obj.values
+                        .as_ref()
+                        .expect("validated: `values` is set for maps"),
+                    level,
+                    flat
+                )
+            ),
+            ApiDocsModelObjectType::Tuple => {
+                let items = obj
+                    .items
+                    .as_ref()
+                    .expect("validated: `items` is set for tuples")
+                    .iter()
+                    .map(|item| self.render_type(item, level, flat))
+                    .collect::<Vec<_>>();
+                format!("[{}]", items.join(", "))
+            },
+            ApiDocsModelObjectType::Object => self.render_object(
+                obj.fields
+                    .as_ref()
+                    .expect("validated: `fields` is set for objects"),
+                level,
+                flat,
+            ),
+            ApiDocsModelObjectType::Enum => render_enum_members(
+                obj.members
+                    .as_ref()
+                    .expect("validated: `members` is set for enums"),
+            ),
+            ApiDocsModelObjectType::Literal => obj
+                .value
+                .as_ref()
+                .expect("validated: `value` is set for literals")
+                .to_string(),
+            ApiDocsModelObjectType::File => "Blob".to_string(),
+            ApiDocsModelObjectType::Ref => heck::AsPascalCase(
+                obj.r#ref
+                    .as_ref()
+                    .expect("validated: `ref` is set for refs"),
+            )
+            .to_string(),
+            ApiDocsModelObjectType::Union => match &obj.discriminator {
+                Some(discriminator) => obj
+                    .variants
+                    .as_ref()
+                    .expect("validated: `variants` is set for discriminated unions")
+                    .iter()
+                    .map(|(tag, variant)| {
+                        self.render_variant(discriminator, tag, variant, level, flat)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+                None => obj
+                    .models
+                    .as_ref()
+                    .expect("validated: `models` is set for unions")
+                    .iter()
+                    .map(|model| self.render_type(model, level, flat))
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            },
+        };
+
+        let inner_type = if obj.nullable {
+            format!("{inner_type} | null")
+        } else {
+            inner_type
+        };
+        if obj.required {
+            return inner_type;
+        }
+        match &self.config.optional {
+            OptionalWrapper::Undefined => format!("{inner_type} | undefined"),
+            OptionalWrapper::Null if obj.nullable => inner_type,
+            OptionalWrapper::Null => format!("{inner_type} | null"),
+            OptionalWrapper::Generic(name) => {
+                format!("{}<{inner_type}>", self.external_type(name))
+            },
+        }
+    }
+
+    fn render_object(&self, fields: &ApiDocsModelObject, level: usize, flat: bool) -> String {
+        let fields = fields
+            .iter()
+            .map(|(name, model)| (name.as_str(), model))
+            .collect::<Vec<_>>();
+        self.render_object_fields(&fields, level, flat)
+    }
+
+    fn render_object_fields(
+        &self, fields: &[(&str, &ApiDocsModel)], level: usize, flat: bool,
+    ) -> String {
+        if fields.is_empty() {
+            return self.render_empty_object().to_string();
+        }
+        if flat {
+            let fields = fields
+                .iter()
+                .map(|(name, model)| {
+                    format!(
+                        "{}{}: {}",
+                        render_property_name(name),
+                        optional_marker(model),
+                        self.render_type(model, level, true)
+                    )
+                })
+                .collect::<Vec<_>>();
+            return format!("{{ {} }}", fields.join("; "));
+        }
+        format!(
+            "{{\n{}{}}}",
+            self.render_fields(fields, level + 1),
+            self.config.indent(level)
+        )
+    }
+
+    /// Renders a field on its own line. With `max_line_width` set, nested
+    /// objects are kept on that line as long as it fits. Unions that don't
+    /// fit are broken one member per line.
+    pub(crate) fn render_field(&self, name: &str, model: &ApiDocsModel, level: usize) -> String {
+        let render = |flat| {
+            format!(
+                "{indent}{name}{opt}: {type};\n",
+                indent = self.config.indent(level),
+                name = render_property_name(name),
+                opt = optional_marker(model),
+                r#type = self.render_type(model, level, flat)
+            )
+        };
+
+        // Single-line objects have no room for the descriptions of their
+        // fields.
+        if let Some(max_line_width) = self
+            .config
+            .max_line_width
+            .filter(|_| !(self.docs && documents_fields(model)))
+        {
+            let line = render(true);
+            if layout::width(self.config, line.trim_end()) <= max_line_width {
+                return line;
+            }
+        }
+        let line = render(false);
+        let r#type = self.render_type(model, level, false);
+        let members = layout::union_members(&r#type);
+        if r#type.contains('\n')
+            || members.len() < 2
+            || layout::width(self.config, line.trim_end()) <= self.config.line_width()
+        {
+            return line;
+        }
+        let field = Doc::Concat(vec![
+            Doc::text(format!(
+                "{}{}:",
+                render_property_name(name),
+                optional_marker(model)
+            )),
+            Doc::union(members.into_iter().map(Doc::text).collect()),
+            Doc::text(";"),
+        ]);
+        format!(
+            "{}{}\n",
+            self.config.indent(level),
+            field.render(self.config, level)
+        )
+    }
+
+    fn render_fields(&self, fields: &[(&str, &ApiDocsModel)], level: usize) -> String {
+        fields
+            .iter()
+            .map(|(name, model)| {
+                let doc = if self.docs {
+                    render_doc(
+                        model.description.as_deref(),
+                        model.deprecated.as_ref(),
+                        &self.config.indent(level),
+                    )
+                } else {
+                    String::new()
+                };
+                format!("{doc}{}", self.render_field(name, model, level))
+            })
+            .collect::<String>()
+    }
+
+    /// Renders a variant of a discriminated union, which carries its tag in
+    /// the `discriminator` field.
+    fn render_variant(
+        &self, discriminator: &str, tag: &str, variant: &ApiDocsModel, level: usize, flat: bool,
+    ) -> String {
+        let tag = ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Enum,
+            members: Some(vec![tag.into()]),
+            required: true,
+            ..Default::default()
+        };
+        match &variant.fields {
+            Some(fields) => {
+                let fields = std::iter::once((discriminator, &tag))
+                    .chain(fields.iter().map(|(name, model)| (name.as_str(), model)))
+                    .collect::<Vec<_>>();
+                self.render_object_fields(&fields, level, flat)
+            },
+            None => format!(
+                "{} & {}",
+                self.render_object_fields(&[(discriminator, &tag)], level, true),
+                self.render_type(variant, level, flat)
+            ),
+        }
+    }
+
+    /// Renders the type of objects without fields. `{}` is any non-nullish
+    /// value, which linters reject.
+    fn render_empty_object(&self) -> &'static str {
+        if self.config.lint_clean {
+            "Record<string, never>"
+        } else {
+            "{}"
+        }
+    }
+
+    /// Renders the interface `name` extending `extends`. Without fields, it's
+    /// a type alias instead with `lint_clean`, as linters reject empty
+    /// interfaces.
+    fn render_declaration(
+        &self, name: &str, extends: &[String], fields: &[(&str, &ApiDocsModel)],
+    ) -> String {
+        if self.config.lint_clean && fields.is_empty() {
+            let r#type = if extends.is_empty() {
+                self.render_empty_object().to_string()
+            } else {
+                extends.join(" & ")
+            };
+            return format!("export type {name} = {type};\n");
+        }
+        let extends = if extends.is_empty() {
+            String::new()
+        } else {
+            format!("extends {} ", extends.join(", "))
+        };
+        format!(
+            "export interface {name} {extends}{}\n",
+            self.render_object_fields(fields, 0, false)
+        )
+    }
+
+    pub(crate) fn render_interface(&self, name: &str, obj: &ApiDocsModelObject) -> String {
+        let fields = obj
+            .iter()
+            .map(|(name, model)| (name.as_str(), model))
+            .collect::<Vec<_>>();
+        self.render_declaration(name, &[], &fields)
+    }
+
+    /// Renders the interface of a model, extending the interfaces of the
+    /// models it inherits from.
+    pub(crate) fn render_model_interface(
+        &self, name: &str, model: &ApiDocsModelDefinition,
+    ) -> String {
+        let extends = model
+            .extends
+            .iter()
+            .map(|parent| heck::AsPascalCase(parent).to_string())
+            .collect::<Vec<_>>();
+        self.render_declaration(name, &extends, &stable_fields(&model.fields))
+    }
+
+    /// Renders `{name}Experimental`, extending the interface of the model
+    /// with the fields behind feature flags, if there are any.
+    fn render_experimental_interface(&self, name: &str, fields: &ApiDocsModelObject) -> String {
+        let flagged = fields
+            .iter()
+            .filter_map(|(name, field)| {
+                let feature = field.feature.as_deref()?;
+                let note = format!("Behind the feature flag `{feature}`.");
+                let description = match field.description.as_deref() {
+                    Some(description) => format!("{}\n\n{note}", description.trim()),
+                    None => note,
+                };
+                let field = ApiDocsModel {
+                    description: Some(description),
+                    ..field.clone()
+                };
+                Some((name.as_str(), field))
+            })
+            .collect::<Vec<_>>();
+        if flagged.is_empty() {
+            return String::new();
+        }
+        let flagged = flagged
+            .iter()
+            .map(|(name, field)| (*name, field))
+            .collect::<Vec<_>>();
+        format!(
+            "\n{}",
+            self.render_declaration(
+                &format!("{name}Experimental"),
+                &[name.to_string()],
+                &flagged
+            )
+        )
+    }
+
+    pub(crate) fn render_interfaces(
+        &self, models: &BTreeMap<String, ApiDocsModelDefinition>,
+    ) -> String {
+        // Inherited fields may be read-only or write-only too.
+        let flattened = flatten_extends(models);
+        models
+            .iter()
+            .map(|(model_name, model)| {
+                let name = heck::AsPascalCase(model_name).to_string();
+                format!(
+                    "{}{}{}{}{}{}",
+                    render_comment(model.comment_before.as_deref()),
+                    render_doc(model.description.as_deref(), model.deprecated.as_ref(), ""),
+                    self.render_model_interface(&name, model),
+                    self.render_experimental_interface(&name, &model.fields),
+                    self.render_io_interfaces(&name, &flattened[model_name].fields),
+                    render_comment(model.comment_after.as_deref()),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders `{name}CreateRequest` without the read-only fields and
+    /// `{name}Response` without the write-only fields, if there are any.
+    fn render_io_interfaces(&self, name: &str, fields: &ApiDocsModelObject) -> String {
+        if !fields
+            .values()
+            .any(|field| field.read_only || field.write_only)
+        {
+            return String::new();
+        }
+        let without = |hidden: fn(&ApiDocsModel) -> bool| {
+            stable_fields(fields)
+                .into_iter()
+                .filter(|(_, field)| !hidden(field))
+                .collect::<Vec<_>>()
+        };
+        format!(
+            "\n{}\n{}",
+            self.render_declaration(
+                &format!("{name}CreateRequest"),
+                &[],
+                &without(|field| field.read_only)
+            ),
+            self.render_declaration(
+                &format!("{name}Response"),
+                &[],
+                &without(|field| field.write_only)
+            ),
+        )
+    }
+
+    /// Renders a `makeDefault` factory for every model with field defaults.
+    /// Required fields without a default are taken as arguments. Fields
+    /// behind feature flags aren't part of the interface and are left out.
+    pub(crate) fn render_factories(
+        &self, models: &BTreeMap<String, ApiDocsModelDefinition>,
+    ) -> String {
+        models
+            .iter()
+            .map(|(model_name, model)| (model_name, stable_fields(&model.fields)))
+            .filter(|(_, fields)| fields.iter().any(|(_, field)| field.default.is_some()))
+            .map(|(model_name, fields)| {
+                let name = heck::AsPascalCase(model_name);
+                let missing = fields
+                    .iter()
+                    .filter(|(_, field)| field.required && field.default.is_none())
+                    .map(|(name, _)| format!("\"{name}\""))
+                    .collect::<Vec<_>>();
+                let (params, spread) = if missing.is_empty() {
+                    (Vec::new(), String::new())
+                } else {
+                    (
+                        vec![Doc::Concat(vec![
+                            Doc::text("fields: Pick"),
+                            Doc::type_arguments(vec![
+                                Doc::text(name.to_string()),
+                                Doc::text(missing.join(" | ")),
+                            ]),
+                        ])],
+                        format!("{}...fields,\n", self.config.indent(2)),
+                    )
+                };
+                let signature = Doc::Concat(vec![
+                    Doc::text(format!("export function makeDefault{name}")),
+                    Doc::parameters(params),
+                    Doc::text(format!(": {name} {{")),
+                ]);
+                let defaults = fields
+                    .iter()
+                    .filter_map(|(name, field)| {
+                        let default = field.default.as_ref()?;
+                        Some(format!(
+                            "{}{}: {default},\n",
+                            self.config.indent(2),
+                            render_property_name(name)
+                        ))
+                    })
+                    .collect::<String>();
+                format!(
+                    "{}\n{indent}return {{\n{defaults}{spread}{indent}}};\n}}\n",
+                    signature.render(self.config, 0),
+                    indent = self.config.indent(1),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders the branded types used so far, each with a guard and a
+    /// constructor that throws on invalid values.
+    fn render_brands(&self) -> String {
+        let indent = self.config.indent(1);
+        self.branded
+            .borrow()
+            .iter()
+            .filter_map(|format| {
+                let (name, check) = format.brand()?;
+                Some(format!(
+                    "export type {name} = string & {{ __brand: \"{name}\" }};\n\n\
+                     export function is{name}(value: string): value is {name} {{\n\
+                     {indent}return {check};\n}}\n\n\
+                     export function to{name}(value: string): {name} {{\n\
+                     {indent}if (!is{name}(value)) {{\n\
+                     {indent}{indent}throw new TypeError(`Invalid {name}: ${{value}}`);\n\
+                     {indent}}}\n\
+                     {indent}return value;\n}}\n"
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders the error classes, `ApiError` and its subclasses `HttpError`,
+    /// `NetworkError` and `ValidationError`, if enabled or needed by the error
+    /// types of routes.
+    fn render_error_classes(&self, routes: &BTreeMap<String, ApiDocsRoute>) -> String {
+        if !self.config.error_classes && routes.values().all(|route| route.errors.is_empty()) {
+            return String::new();
+        }
+        format!(
+            "export class ApiError extends Error {{\n\
+             {indent}constructor(message: string, options?: ErrorOptions) {{\n\
+             {indent}{indent}super(message, options);\n\
+             {indent}{indent}this.name = new.target.name;\n\
+             {indent}}}\n\
+             }}\n\n\
+             {http_error}\n\
+             {indent}constructor(\n\
+             {indent}{indent}readonly status: Status,\n\
+             {indent}{indent}readonly body: Body,\n\
+             {indent}) {{\n\
+             {indent}{indent}super(`Request failed with status ${{status}}`);\n\
+             {indent}}}\n\
+             }}\n\n\
+             export class NetworkError extends ApiError {{}}\n\n\
+             export class ValidationError extends ApiError {{\n\
+             {indent}constructor(\n\
+             {indent}{indent}message: string,\n\
+             {indent}{indent}readonly issues: unknown,\n\
+             {indent}) {{\n\
+             {indent}{indent}super(message);\n\
+             {indent}}}\n\
+             }}\n",
+            http_error = Doc::Concat(vec![
+                Doc::text("export class HttpError"),
+                Doc::type_parameters(vec![
+                    Doc::text("Status extends number = number"),
+                    Doc::text("Body = unknown"),
+                ]),
+                Doc::text(" extends ApiError {"),
+            ])
+            .render(self.config, 0),
+            indent = self.config.indent(1),
+        )
+    }
+
+    /// Renders the signature of the client function for a route on a single
+    /// line.
+    pub(crate) fn render_route_signature(&self, name: &str, route: &ApiDocsRoute) -> String {
+        format!(
+            "function {}({}): Promise<{}>",
+            heck::AsLowerCamelCase(name),
+            self.render_route_parameters(route).join(", "),
+            heck::AsPascalCase(&route.returns)
+        )
+    }
+
+    /// Renders the parameters of the client function of `route`.
+    fn render_route_parameters(&self, route: &ApiDocsRoute) -> Vec<String> {
+        self.route_parameters(route).iter().map(Doc::flat).collect()
+    }
+
+    /// The parameters of the client function of `route`, broken one per
+    /// line if they don't fit.
+    fn route_parameters(&self, route: &ApiDocsRoute) -> Vec<Doc> {
+        let mut parameters = vec![Doc::text("request: ApiRequest")];
+        if !route.params.is_empty() {
+            parameters.push(Doc::Concat(vec![
+                Doc::text("params: "),
+                self.render_object_doc(&route.params),
+            ]));
+        }
+        if let Some(query) = &route.query {
+            parameters.push(Doc::text(format!("query: {}", heck::AsPascalCase(query))));
+        }
+        if let Some(headers) = &route.headers {
+            parameters.push(Doc::text(format!(
+                "headers: {}",
+                heck::AsPascalCase(headers)
+            )));
+        }
+        if route.has_body() {
+            parameters.push(Doc::text(format!(
+                "body: {}",
+                heck::AsPascalCase(&route.accepts)
+            )));
+        }
+        parameters
+    }
+
+    /// Renders an object type whose fields are broken one per line if it
+    /// doesn't fit.
+    fn render_object_doc(&self, fields: &ApiDocsModelObject) -> Doc {
+        if fields.is_empty() {
+            return Doc::text(self.render_empty_object());
+        }
+        Doc::Members(
+            fields
+                .iter()
+                .map(|(name, model)| {
+                    Doc::text(format!(
+                        "{}{}: {}",
+                        render_property_name(name),
+                        optional_marker(model),
+                        self.render_type(model, 0, true)
+                    ))
+                })
+                .collect(),
+        )
+    }
+
+    /// Renders a generator over all pages of a paginated route, requesting
+    /// the next page once the previous one has been consumed.
+    fn render_route_pages(&self, name: &str, route: &ApiDocsRoute) -> String {
+        let Some(pagination) = &route.pagination else {
+            return String::new();
+        };
+        let field = match pagination {
+            Pagination::Cursor { cursor, .. } => cursor,
+            Pagination::Offset { offset, .. } => offset,
+        };
+        let variable = heck::AsLowerCamelCase(field).to_string();
+        let cursor = if render_property_name(field) == variable {
+            variable.clone()
+        } else {
+            format!("{}: {variable}", render_property_name(field))
+        };
+        let parameters = self.route_parameters(route);
+        let arguments = parameters
+            .iter()
+            .map(|parameter| match parameter.flat().split_once(':') {
+                Some(("query", _)) => {
+                    Doc::object(vec![Doc::text("...query"), Doc::text(cursor.clone())])
+                },
+                Some((parameter, _)) => Doc::text(parameter),
+                None => Doc::text(parameter.flat()),
+            })
+            .collect::<Vec<_>>();
+        let call = Doc::Concat(vec![
+            Doc::text(format!(
+                "const response = await {}",
+                heck::AsLowerCamelCase(name)
+            )),
+            Doc::arguments(arguments),
+            Doc::text(";"),
+        ])
+        .render(self.config, 2);
+        let signature = Doc::Concat(vec![
+            Doc::text(format!(
+                "export async function* {}Pages",
+                heck::AsLowerCamelCase(name)
+            )),
+            Doc::parameters(parameters),
+            Doc::text(format!(
+                ": AsyncGenerator<{}, void> {{",
+                heck::AsPascalCase(&route.returns)
+            )),
+        ]);
+
+        let indent = self.config.indent(1);
+        let indent2 = self.config.indent(2);
+        let (start, r#loop, next, end) = match pagination {
+            Pagination::Cursor { next_cursor, .. } => (
+                format!("query{}", render_property_access(field)),
+                format!("do {{\n{indent2}{call}"),
+                format!(
+                    "response{} ?? undefined",
+                    render_property_access(next_cursor)
+                ),
+                format!("{indent}}} while ({variable} !== undefined);\n"),
+            ),
+            Pagination::Offset { items, .. } => {
+                let items = format!("response{}", render_property_access(items));
+                (
+                    format!("query{} ?? 0", render_property_access(field)),
+                    format!(
+                        "for (;;) {{\n{indent2}{call}\n\
+                         {indent2}if ({items}.length === 0) {{\n{indent2}{indent}return;\n\
+                         {indent2}}}"
+                    ),
+                    format!("{variable} + {items}.length"),
+                    format!("{indent}}}\n"),
+                )
+            },
+        };
+        format!(
+            "{}\n\
+             {indent}let {variable} = {start};\n\
+             {indent}{loop}\n\
+             {indent2}yield response;\n\
+             {indent2}{variable} = {next};\n\
+             {end}\
+             }}\n",
+            signature.render(self.config, 0),
+            loop = r#loop,
+        )
+    }
+
+    pub(crate) fn render_route(&self, name: &str, route: &ApiDocsRoute) -> String {
+        let returns = heck::AsPascalCase(&route.returns);
+        let mut options = Vec::new();
+        if let Some(service) = &route.service {
+            options.push(format!(
+                "service: {}",
+                serde_json::Value::from(service.as_str())
+            ));
+        }
+        if let Some(method) = route.method {
+            options.push(format!("method: \"{}\"", method.as_str()));
+        }
+        if let Some(content_type) = route.content_type {
+            options.push(format!(
+                "contentType: {}",
+                serde_json::to_value(content_type).unwrap_or_default()
+            ));
+        }
+        if let Some(compression) = route.compression {
+            options.push(format!(
+                "compression: {}",
+                serde_json::to_value(compression).unwrap_or_default()
+            ));
+        }
+        if let Some(path) = &route.path {
+            options.push(format!("path: {}", render_path(path)));
+        }
+        if route.query.is_some() {
+            options.push("query: serializeQuery(query)".to_string());
+        }
+        if route.headers.is_some() {
+            options.push("headers: serializeHeaders(headers)".to_string());
+        }
+        let body = match route.content_type {
+            _ if !route.has_body() => "undefined",
+            Some(ContentType::Multipart) => "toFormData(body)",
+            _ => "body",
+        };
+        let mut arguments = vec![Doc::text(format!("\"{name}\"")), Doc::text(body)];
+        if !options.is_empty() {
+            arguments.push(Doc::object(options.into_iter().map(Doc::text).collect()));
+        }
+        let sanitize = if self.config.null_prototype {
+            ".then(withoutPrototypes)"
+        } else {
+            ""
+        };
+        let signature = Doc::Concat(vec![
+            Doc::text(format!("export function {}", heck::AsLowerCamelCase(name))),
+            Doc::parameters(self.route_parameters(route)),
+            Doc::text(format!(": Promise<{returns}> {{")),
+        ]);
+        let call = Doc::Concat(vec![
+            Doc::text("return request"),
+            Doc::arguments(arguments),
+            Doc::text(format!("{sanitize} as Promise<{returns}>;")),
+        ]);
+        format!(
+            "{}\n{}{}\n}}\n",
+            signature.render(self.config, 0),
+            self.config.indent(1),
+            call.render(self.config, 1)
+        )
+    }
+
+    pub(crate) fn render_routes(&self, routes: &BTreeMap<String, ApiDocsRoute>) -> String {
+        if routes.is_empty() {
+            return String::new();
+        }
+
+        // Options are only declared when used, so transports written for
+        // specs without them keep type checking.
+        let mut options = Vec::new();
+        let union = |name: &str, members: &str| {
+            Doc::Concat(vec![
+                Doc::text(format!("{name}?:")),
+                Doc::union(
+                    layout::union_members(members)
+                        .into_iter()
+                        .map(Doc::text)
+                        .collect(),
+                ),
+            ])
+        };
+        let services = render_services(routes);
+        if !services.is_empty() {
+            options.push(union("service", &services));
+        }
+        if routes.values().any(|route| route.method.is_some()) {
+            options.push(union(
+                "method",
+                "\"GET\" | \"HEAD\" | \"POST\" | \"PUT\" | \"PATCH\" | \"DELETE\" | \"OPTIONS\" | \
+                 \"TRACE\"",
+            ));
+        }
+        if routes.values().any(|route| route.content_type.is_some()) {
+            options.push(union(
+                "contentType",
+                "\"application/json\" | \"multipart/form-data\"",
+            ));
+        }
+        if routes.values().any(|route| route.compression.is_some()) {
+            options.push(union("compression", "\"gzip\" | \"br\""));
+        }
+        if routes.values().any(|route| route.path.is_some()) {
+            options.push(Doc::text("path?: string"));
+        }
+        let has_query = routes.values().any(|route| route.query.is_some());
+        if has_query {
+            options.push(Doc::text("query?: string"));
+        }
+        let has_headers = routes.values().any(|route| route.headers.is_some());
+        if has_headers {
+            options.push(Doc::text("headers?: Record<string, string>"));
+        }
+        let with_options = !options.is_empty();
+        let mut parameters = vec![Doc::text("route: string"), Doc::text("body: unknown")];
+        if with_options {
+            parameters.push(Doc::Concat(vec![
+                Doc::text("options?: "),
+                Doc::Members(options),
+            ]));
+        }
+        let request_type = Doc::Concat(vec![
+            Doc::text("export type ApiRequest = "),
+            Doc::parameters(parameters),
+            Doc::text(" => Promise<unknown>;"),
+        ]);
+        let request_type = format!("{}\n", request_type.render(self.config, 0));
+        let mut helpers = String::new();
+        if self.config.null_prototype {
+            helpers.push_str(&format!("\n{}", self.render_without_prototypes()));
+        }
+        if has_query {
+            helpers.push_str(&format!("\n{}", self.render_serialize_query()));
+        }
+        if has_headers {
+            helpers.push_str(&format!("\n{}", self.render_serialize_headers()));
+        }
+        if routes.values().any(|route| !route.errors.is_empty()) {
+            helpers.push_str(&format!("\n{}", self.render_settle()));
+        }
+        if routes
+            .values()
+            .any(|route| route.content_type == Some(ContentType::Multipart))
+        {
+            helpers.push_str(&format!("\n{}", self.render_to_form_data()));
+        }
+        let functions = if self.config.client == TypeScriptClient::Minimal {
+            self.render_minimal_client(routes, with_options)
+        } else {
+            self.render_route_functions(routes)
+        };
+        // Grouping functions in an object keeps all of them in bundles.
+        // Merged specs are always namespaced, by service.
+        let namespaced =
+            self.config.namespaces || routes.values().any(|route| route.service.is_some());
+        let namespaces = if namespaced && !self.config.optimize_size {
+            self.render_namespaces(routes)
+        } else {
+            String::new()
+        };
+        let permissions = self.render_permissions(routes);
+        format!("{request_type}{helpers}\n{functions}{namespaces}{permissions}")
+    }
+
+    /// Renders the scopes needed to call each route, typed with the literal
+    /// scopes, and `canCall`, telling whether a user with some scopes can call
+    /// a route. Nothing is rendered if no route needs scopes.
+    fn render_permissions(&self, routes: &BTreeMap<String, ApiDocsRoute>) -> String {
+        let scopes = routes
+            .values()
+            .flat_map(|route| &route.scopes)
+            .collect::<BTreeSet<_>>();
+        if scopes.is_empty() {
+            return String::new();
+        }
+
+        let indent = self.config.indent(1);
+        let literal =
+            |scope: &String| Doc::text(serde_json::Value::from(scope.as_str()).to_string());
+        let scopes = Doc::Concat(vec![
+            Doc::text("export type Scope ="),
+            Doc::union(scopes.into_iter().map(literal).collect()),
+            Doc::text(";"),
+        ]);
+        let permissions = routes
+            .iter()
+            .map(|(name, route)| {
+                let permission = Doc::Concat(vec![
+                    Doc::text(format!("{}: ", render_property_name(name))),
+                    Doc::array(route.scopes.iter().map(literal).collect()),
+                    Doc::text(","),
+                ]);
+                format!("{indent}{}\n", permission.render(self.config, 1))
+            })
+            .collect::<String>();
+        let can_call = Doc::Concat(vec![
+            Doc::text("export function canCall"),
+            Doc::parameters(vec![
+                Doc::text("route: keyof RoutePermissions"),
+                Doc::text("userScopes: readonly string[]"),
+            ]),
+            Doc::text(": boolean {"),
+        ]);
+        format!(
+            "\n{}\n\
+             \n\
+             const routePermissions = {{\n\
+             {permissions}\
+             }} as const;\n\
+             \n\
+             /** Scopes needed to call each route. */\n\
+             export type RoutePermissions = typeof routePermissions;\n\
+             \n\
+             /** Whether a user with `userScopes` can call `route`. */\n\
+             {}\n\
+             {indent}const scopes: readonly Scope[] = routePermissions[route];\n\
+             {indent}return scopes.every((scope) => userScopes.includes(scope));\n\
+             }}\n",
+            scopes.render(self.config, 0),
+            can_call.render(self.config, 0),
+        )
+    }
+
+    fn render_route_functions(&self, routes: &BTreeMap<String, ApiDocsRoute>) -> String {
+        routes
+            .iter()
+            .map(|(name, route)| {
+                let pages = self.render_route_pages(name, route);
+                format!(
+                    "{}{}{}{}{}{}",
+                    self.render_route_errors(name, route),
+                    render_comment(route.comment_before.as_deref()),
+                    render_doc(route.description.as_deref(), route.deprecated.as_ref(), ""),
+                    self.render_route(name, route),
+                    render_comment(route.comment_after.as_deref()),
+                    if pages.is_empty() {
+                        pages
+                    } else {
+                        format!("\n{pages}")
+                    },
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders a constant per route, typed with what the route takes and
+    /// returns, and the generic `request` function sending any of them.
+    /// `with_options` tells whether the transport takes options.
+    fn render_minimal_client(
+        &self, routes: &BTreeMap<String, ApiDocsRoute>, with_options: bool,
+    ) -> String {
+        let indent = self.config.indent(1);
+        let has_path = routes.values().any(|route| route.path.is_some());
+        let has_static_options = routes.values().any(|route| {
+            route.service.is_some()
+                || route.method.is_some()
+                || route.content_type.is_some()
+                || route.compression.is_some()
+        });
+
+        let mut fields = vec!["name: string;".to_string()];
+        if has_path {
+            fields.push("path?: string;".to_string());
+        }
+        if has_static_options {
+            fields.push("options?: Parameters<ApiRequest>[2];".to_string());
+        }
+        fields.push("/** Never set, only carries the types of the route. */".to_string());
+        fields.push("types?: (input: Input) => Returns;".to_string());
+        let route_input = Doc::Concat(vec![
+            Doc::text("export type RouteInput = "),
+            Doc::Members(vec![
+                Doc::text("params?: Record<string, unknown>"),
+                Doc::text("query?: object"),
+                Doc::text("headers?: object"),
+                Doc::text("body?: unknown"),
+            ]),
+            Doc::text(";"),
+        ]);
+        let route_type = format!(
+            "export interface Route<Input, Returns> {{\n{}}}\n\n{}\n",
+            fields
+                .iter()
+                .map(|field| format!("{indent}{field}\n"))
+                .collect::<String>(),
+            route_input.render(self.config, 0)
+        );
+
+        let mut options = Vec::new();
+        if has_static_options {
+            options.push("...route.options".to_string());
+        }
+        if has_path {
+            let path = Doc::Concat(vec![
+                Doc::text("path: route.path?.replace"),
+                Doc::arguments(vec![
+                    Doc::text("/\\{([^{}]*)\\}/g"),
+                    Doc::Concat(vec![
+                        Doc::text("(_, name: string) =>"),
+                        Doc::Body(Box::new(Doc::text(
+                            "encodeURIComponent(String(input.params?.[name]))",
+                        ))),
+                    ]),
+                ]),
+            ]);
+            options.push(path.render(self.config, 2));
+        }
+        if routes.values().any(|route| route.query.is_some()) {
+            options.push("query: input.query && serializeQuery(input.query)".to_string());
+        }
+        if routes.values().any(|route| route.headers.is_some()) {
+            options.push("headers: input.headers && serializeHeaders(input.headers)".to_string());
+        }
+        // Like prettier, a conditional that doesn't fit is moved after the
+        // `=` and broken before its branches.
+        let (body, form_data) = if routes
+            .values()
+            .any(|route| route.content_type == Some(ContentType::Multipart))
+        {
+            let (test, consequent, alternate) = (
+                "route.options?.contentType === \"multipart/form-data\"",
+                "toFormData(input.body as object)",
+                "input.body",
+            );
+            let line = format!("{indent}const body = {test} ? {consequent} : {alternate};");
+            let form_data = if layout::width(self.config, &line) <= self.config.line_width() {
+                format!("{line}\n")
+            } else {
+                let indent2 = self.config.indent(2);
+                let indent3 = self.config.indent(3);
+                format!(
+                    "{indent}const body =\n{indent2}{test}\n{indent3}? {consequent}\n\
+                     {indent3}: {alternate};\n"
+                )
+            };
+            ("body", form_data)
+        } else {
+            ("input.body", String::new())
+        };
+        let options = if with_options && !options.is_empty() {
+            format!(
+                ", {{\n{}{indent}}}",
+                options
+                    .iter()
+                    .map(|option| format!("{indent}{indent}{option},\n"))
+                    .collect::<String>()
+            )
+        } else {
+            String::new()
+        };
+        let sanitize = if self.config.null_prototype {
+            ".then(withoutPrototypes)"
+        } else {
+            ""
+        };
+        let signature = Doc::Concat(vec![
+            Doc::text("export function request"),
+            Doc::type_parameters(vec![
+                Doc::text("Input extends RouteInput"),
+                Doc::text("Returns"),
+            ]),
+            Doc::parameters(vec![
+                Doc::text("send: ApiRequest"),
+                Doc::text("route: Route<Input, Returns>"),
+                Doc::text("input: Input"),
+            ]),
+            Doc::text(": Promise<Returns> {"),
+        ]);
+        let request = format!(
+            "{}\n{form_data}\
+             {indent}return send(route.name, {body}{options}){sanitize} as Promise<Returns>;\n\
+             }}\n",
+            signature.render(self.config, 0)
+        );
+
+        let constants = routes
+            .iter()
+            .map(|(name, route)| {
+                // The request of the transport isn't part of the input.
+                let mut input = self.route_parameters(route);
+                input.remove(0);
+                let input = if input.is_empty() {
+                    Doc::text(self.render_empty_object())
+                } else {
+                    Doc::Members(input)
+                };
+                let declaration = Doc::Concat(vec![
+                    Doc::text(format!(
+                        "export const {}: Route",
+                        heck::AsLowerCamelCase(name)
+                    )),
+                    Doc::type_arguments(vec![
+                        input,
+                        Doc::text(heck::AsPascalCase(&route.returns).to_string()),
+                    ]),
+                    Doc::text(" = {"),
+                ]);
+
+                let mut fields = vec![Doc::text(format!("name: \"{name}\""))];
+                if let Some(path) = &route.path {
+                    fields.push(Doc::text(format!(
+                        "path: {}",
+                        serde_json::Value::from(path.as_str())
+                    )));
+                }
+                let mut options = Vec::new();
+                if let Some(service) = &route.service {
+                    options.push(format!(
+                        "service: {}",
+                        serde_json::Value::from(service.as_str())
+                    ));
+                }
+                if let Some(method) = route.method {
+                    options.push(format!("method: \"{}\"", method.as_str()));
+                }
+                if let Some(content_type) = route.content_type {
+                    options.push(format!(
+                        "contentType: {}",
+                        serde_json::to_value(content_type).unwrap_or_default()
+                    ));
+                }
+                if let Some(compression) = route.compression {
+                    options.push(format!(
+                        "compression: {}",
+                        serde_json::to_value(compression).unwrap_or_default()
+                    ));
+                }
+                if !options.is_empty() {
+                    fields.push(Doc::Concat(vec![
+                        Doc::text("options: "),
+                        Doc::object(options.into_iter().map(Doc::text).collect()),
+                    ]));
+                }
+                format!(
+                    "{}{}{}{}\n{}}};\n{}",
+                    self.render_route_errors(name, route),
+                    render_comment(route.comment_before.as_deref()),
+                    render_doc(route.description.as_deref(), route.deprecated.as_ref(), ""),
+                    declaration.render(self.config, 0),
+                    fields
+                        .into_iter()
+                        .map(|field| {
+                            let field = Doc::Concat(vec![field, Doc::text(",")]);
+                            format!("{indent}{}\n", field.render(self.config, 1))
+                        })
+                        .collect::<String>(),
+                    render_comment(route.comment_after.as_deref()),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("{route_type}\n{request}\n{constants}")
+    }
+
+    /// Renders a function per socket connecting to it, through `connect`
+    /// like routes are requested through `request`, and returning a
+    /// `TypedSocket` whose messages are narrowed by their type.
+    pub(crate) fn render_sockets(&self, sockets: &BTreeMap<String, ApiDocsSocket>) -> String {
+        if sockets.is_empty() {
+            return String::new();
+        }
+        let indent = self.config.indent(1);
+        let messages = |name: String, models: &BTreeMap<String, String>| {
+            let messages = if models.is_empty() {
+                vec![Doc::text("never")]
+            } else {
+                models
+                    .iter()
+                    .map(|(r#type, model)| {
+                        Doc::Members(vec![
+                            Doc::text(format!(
+                                "type: {}",
+                                serde_json::Value::from(r#type.as_str())
+                            )),
+                            Doc::text(format!("data: {}", heck::AsPascalCase(model))),
+                        ])
+                    })
+                    .collect()
+            };
+            Doc::Concat(vec![
+                Doc::text(format!("export type {name} =")),
+                Doc::union(messages),
+                Doc::text(";"),
+            ])
+            .render(self.config, 0)
+        };
+        let functions = sockets
+            .iter()
+            .map(|(name, socket)| {
+                let type_name = heck::AsPascalCase(name);
+                let signature = Doc::Concat(vec![
+                    Doc::text(format!("export function {}", heck::AsLowerCamelCase(name))),
+                    Doc::parameters(vec![Doc::text("connect: ApiConnect")]),
+                    Doc::text(format!(
+                        ": TypedSocket<{type_name}Sends, {type_name}Receives> {{"
+                    )),
+                ]);
+                format!(
+                    "{}\n\n{}\n\n{}{}\n\
+                     {indent}return new TypedSocket(connect({}));\n\
+                     }}\n",
+                    messages(format!("{type_name}Sends"), &socket.sends),
+                    messages(format!("{type_name}Receives"), &socket.receives),
+                    render_doc(socket.description.as_deref(), None, ""),
+                    signature.render(self.config, 0),
+                    serde_json::Value::from(socket.path.as_str()),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let class = Doc::Concat(vec![
+            Doc::text("export class TypedSocket"),
+            Doc::type_parameters(vec![
+                Doc::text("Sends extends SocketMessage"),
+                Doc::text("Receives extends SocketMessage"),
+            ]),
+            Doc::text(" {"),
+        ]);
+        let send = Doc::Concat(vec![
+            Doc::text("send<Type extends Sends[\"type\"]>"),
+            Doc::parameters(vec![
+                Doc::text("type: Type"),
+                Doc::text("data: Extract<Sends, { type: Type }>[\"data\"]"),
+            ]),
+            Doc::text(": void {"),
+        ]);
+        let on_message = Doc::Concat(vec![
+            Doc::text("onMessage<Type extends Receives[\"type\"]>"),
+            Doc::parameters(vec![
+                Doc::text("type: Type"),
+                Doc::Concat(vec![
+                    Doc::text("listener: "),
+                    Doc::parameters(vec![Doc::text(
+                        "data: Extract<Receives, { type: Type }>[\"data\"]",
+                    )]),
+                    Doc::text(" => void"),
+                ]),
+            ]),
+            Doc::text(": () => void {"),
+        ]);
+        let listener = Doc::Concat(vec![
+            Doc::text("listener"),
+            Doc::arguments(vec![Doc::text(
+                "message.data as Extract<Receives, { type: Type }>[\"data\"]",
+            )]),
+            Doc::text(";"),
+        ]);
+        format!(
+            "export type ApiConnect = (path: string) => WebSocket;\n\n\
+             export type SocketMessage = {{ type: string; data: unknown }};\n\n\
+             {class}\n\
+             {indent}constructor(readonly socket: WebSocket) {{}}\n\n\
+             {indent}{send}\n\
+             {indent}{indent}this.socket.send(JSON.stringify({{ type, data }}));\n\
+             {indent}}}\n\n\
+             {indent}{on_message}\n\
+             {indent}{indent}const handle = (event: MessageEvent) => {{\n\
+             {indent}{indent}{indent}const message = JSON.parse(event.data) as Receives;\n\
+             {indent}{indent}{indent}if (message.type === type) {{\n\
+             {indent}{indent}{indent}{indent}{listener}\n\
+             {indent}{indent}{indent}}}\n\
+             {indent}{indent}}};\n\
+             {indent}{indent}this.socket.addEventListener(\"message\", handle);\n\
+             {indent}{indent}return () => this.socket.removeEventListener(\"message\", handle);\n\
+             {indent}}}\n\
+             }}\n\n\
+             {functions}",
+            class = class.render(self.config, 0),
+            send = send.render(self.config, 1),
+            on_message = on_message.render(self.config, 1),
+            listener = listener.render(self.config, 4),
+        )
+    }
+
+    /// Renders the `api` object grouping the client functions by tag.
+    fn render_namespaces(&self, routes: &BTreeMap<String, ApiDocsRoute>) -> String {
+        let namespaces = route_namespaces(routes);
+        if namespaces.is_empty() {
+            return String::new();
+        }
+
+        let indent = self.config.indent(1);
+        let mut rendered = "\nexport const api = {\n".to_string();
+        for (namespace, entries) in &namespaces {
+            rendered.push_str(&format!("{indent}{namespace}: {{\n"));
+            for (name, member) in entries {
+                let function = heck::AsLowerCamelCase(name);
+                rendered.push_str(&format!("{indent}{indent}{member}: {function},\n"));
+            }
+            rendered.push_str(&format!("{indent}}},\n"));
+        }
+        rendered.push_str("};\n");
+        rendered
+    }
+
+    /// Renders the type of the errors of a route, a union of `HttpError`s
+    /// discriminated by their status, and of the result of settling it.
+    fn render_route_errors(&self, name: &str, route: &ApiDocsRoute) -> String {
+        if route.errors.is_empty() {
+            return String::new();
+        }
+        let name = heck::AsPascalCase(name);
+        let errors = Doc::Concat(vec![
+            Doc::text(format!("export type {name}Error =")),
+            Doc::union(
+                route
+                    .errors
+                    .iter()
+                    .map(|(status, model)| {
+                        Doc::text(format!(
+                            "HttpError<{status}, {}>",
+                            heck::AsPascalCase(model)
+                        ))
+                    })
+                    .collect(),
+            ),
+            Doc::text(";"),
+        ]);
+        let result = Doc::Concat(vec![
+            Doc::text(format!("export type {name}Result = ApiResult")),
+            Doc::type_arguments(vec![
+                Doc::text(heck::AsPascalCase(&route.returns).to_string()),
+                Doc::text(format!("{name}Error")),
+            ]),
+            Doc::text(";"),
+        ]);
+        format!(
+            "{}\n\n{}\n\n",
+            errors.render(self.config, 0),
+            result.render(self.config, 0)
+        )
+    }
+
+    /// Renders `settle`, which turns the promise of a client function into
+    /// the promise of its result, so that errors can be handled by status
+    /// without catching them. The errors of the route are trusted to be the
+    /// only `HttpError`s the transport throws.
+    fn render_settle(&self) -> String {
+        let result = Doc::Concat(vec![
+            Doc::text("export type ApiResult<T, E> ="),
+            Doc::union(vec![
+                Doc::Members(vec![Doc::text("ok: true"), Doc::text("body: T")]),
+                Doc::Members(vec![Doc::text("ok: false"), Doc::text("error: E")]),
+            ]),
+            Doc::text(";"),
+        ]);
+        let signature = Doc::Concat(vec![
+            Doc::text("export function settle"),
+            Doc::type_parameters(vec![Doc::text("T"), Doc::text("E extends HttpError")]),
+            Doc::parameters(vec![Doc::text("promise: Promise<T>")]),
+            Doc::text(": Promise<ApiResult<T, E>> {"),
+        ]);
+        format!(
+            "{}\n\n\
+             {}\n\
+             {indent}return promise.then(\n\
+             {indent}{indent}(body) => ({{ ok: true, body }}),\n\
+             {indent}{indent}(error) => {{\n\
+             {indent}{indent}{indent}if (error instanceof HttpError) {{\n\
+             {indent}{indent}{indent}{indent}return {{ ok: false, error: error as E }};\n\
+             {indent}{indent}{indent}}}\n\
+             {indent}{indent}{indent}throw error;\n\
+             {indent}{indent}}},\n\
+             {indent});\n\
+             }}\n",
+            result.render(self.config, 0),
+            signature.render(self.config, 0),
+            indent = self.config.indent(1),
+        )
+    }
+
+    /// Renders a check that `value` isn't `undefined` nor `null`.
+    fn render_is_present(&self, value: &str) -> String {
+        if self.config.optimize_size {
+            format!("{value} != null")
+        } else {
+            format!("{value} !== undefined && {value} !== null")
+        }
+    }
+
+    /// Renders the items of `value` if it's an array, or `value` alone.
+    fn render_items(&self, value: &str) -> String {
+        if self.config.optimize_size {
+            format!("[{value}].flat()")
+        } else {
+            format!("Array.isArray({value}) ? {value} : [{value}]")
+        }
+    }
+
+    /// Renders `toFormData`, which turns a body into `FormData`, appending
+    /// files as they are, objects as JSON and other values as strings,
+    /// repeating the field for every item of arrays and leaving out missing
+    /// ones.
+    fn render_to_form_data(&self) -> String {
+        format!(
+            "function toFormData(body: object): FormData {{\n\
+             {indent}const form = new FormData();\n\
+             {indent}for (const [key, value] of Object.entries(body)) {{\n\
+             {indent}{indent}for (const item of {items}) {{\n\
+             {indent}{indent}{indent}if (item instanceof Blob) {{\n\
+             {indent}{indent}{indent}{indent}form.append(key, item);\n\
+             {indent}{indent}{indent}}} else if (typeof item === \"object\" && item !== null) {{\n\
+             {indent}{indent}{indent}{indent}form.append(key, JSON.stringify(item));\n\
+             {indent}{indent}{indent}}} else if ({present}) {{\n\
+             {indent}{indent}{indent}{indent}form.append(key, String(item));\n\
+             {indent}{indent}{indent}}}\n\
+             {indent}{indent}}}\n\
+             {indent}}}\n\
+             {indent}return form;\n\
+             }}\n",
+            indent = self.config.indent(1),
+            items = self.render_items("value"),
+            present = self.render_is_present("item"),
+        )
+    }
+
+    /// Renders `serializeHeaders`, which turns headers into strings, leaving
+    /// out missing ones.
+    fn render_serialize_headers(&self) -> String {
+        format!(
+            "function serializeHeaders(headers: object): Record<string, string> {{\n\
+             {indent}const serialized: Record<string, string> = {{}};\n\
+             {indent}for (const [name, value] of Object.entries(headers)) {{\n\
+             {indent}{indent}if ({present}) {{\n\
+             {indent}{indent}{indent}serialized[name] = String(value);\n\
+             {indent}{indent}}}\n\
+             {indent}}}\n\
+             {indent}return serialized;\n\
+             }}\n",
+            indent = self.config.indent(1),
+            present = self.render_is_present("value"),
+        )
+    }
+
+    /// Renders `serializeQuery`, which turns query parameters into a query
+    /// string, repeating the parameter for every item of arrays and leaving
+    /// out missing ones.
+    fn render_serialize_query(&self) -> String {
+        format!(
+            "function serializeQuery(query: object): string {{\n\
+             {indent}const search = new URLSearchParams();\n\
+             {indent}for (const [key, value] of Object.entries(query)) {{\n\
+             {indent}{indent}for (const item of {items}) {{\n\
+             {indent}{indent}{indent}if ({present}) {{\n\
+             {indent}{indent}{indent}{indent}search.append(key, String(item));\n\
+             {indent}{indent}{indent}}}\n\
+             {indent}{indent}}}\n\
+             {indent}}}\n\
+             {indent}const serialized = search.toString();\n\
+             {indent}return serialized === \"\" ? \"\" : `?${{serialized}}`;\n\
+             }}\n",
+            indent = self.config.indent(1),
+            items = self.render_items("value"),
+            present = self.render_is_present("item"),
+        )
+    }
+
+    /// Renders `withoutPrototypes`, which deeply copies a response into
+    /// null-prototype objects without the keys used for prototype pollution.
+    fn render_without_prototypes(&self) -> String {
+        format!(
+            "function withoutPrototypes(value: unknown): unknown {{\n\
+             {indent}if (Array.isArray(value)) {{\n\
+             {indent}{indent}return value.map(withoutPrototypes);\n\
+             {indent}}}\n\
+             {indent}if (typeof value !== \"object\" || value === null) {{\n\
+             {indent}{indent}return value;\n\
+             {indent}}}\n\
+             {indent}const copy = Object.create(null);\n\
+             {indent}for (const [key, item] of Object.entries(value)) {{\n\
+             {indent}{indent}if (key !== \"__proto__\" && key !== \"constructor\" && key !== \"prototype\") {{\n\
+             {indent}{indent}{indent}copy[key] = withoutPrototypes(item);\n\
+             {indent}{indent}}}\n\
+             {indent}}}\n\
+             {indent}return copy;\n\
+             }}\n",
+            indent = self.config.indent(1),
+        )
+    }
+}
+
+/// Renders the union of the services of merged `routes`, e.g.
+/// `"billing" | "identity"`, or nothing if they aren't merged.
+pub(crate) fn render_services(routes: &BTreeMap<String, ApiDocsRoute>) -> String {
+    routes
+        .values()
+        .filter_map(|route| route.service.as_deref())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|service| serde_json::Value::from(service).to_string())
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Groups `routes` by service or, if they aren't merged, by tag, keyed by
+/// the camel-cased group. Members are the camel-cased names of the routes
+/// without the words of the group, or their full name if that leaves nothing
+/// or makes two of them clash.
+pub(crate) fn route_namespaces(
+    routes: &BTreeMap<String, ApiDocsRoute>,
+) -> BTreeMap<String, Vec<(&str, String)>> {
+    let mut namespaces = BTreeMap::<String, Vec<(&str, String)>>::new();
+    for (name, route) in routes {
+        // Merged routes are prefixed with their service, which is all their
+        // member drops.
+        if let Some(service) = &route.service {
+            let member = name.strip_prefix(&format!("{service}_")).unwrap_or(name);
+            let namespace = heck::AsLowerCamelCase(service).to_string();
+            let member = heck::AsLowerCamelCase(member).to_string();
+            namespaces
+                .entry(namespace)
+                .or_default()
+                .push((name, member));
+            continue;
+        }
+        for tag in &route.tags {
+            let namespace = heck::AsLowerCamelCase(tag).to_string();
+            let member = heck::AsLowerCamelCase(without_words(name, &namespace)).to_string();
+            namespaces
+                .entry(namespace)
+                .or_default()
+                .push((name, member));
+        }
+    }
+    for entries in namespaces.values_mut() {
+        let members = entries
+            .iter()
+            .map(|(_, member)| member.clone())
+            .collect::<Vec<_>>();
+        for (name, member) in entries.iter_mut() {
+            let clashes = members.iter().filter(|other| *other == member).count() > 1;
+            if member.is_empty() || clashes {
+                *member = heck::AsLowerCamelCase(name).to_string();
+            }
+        }
+    }
+    namespaces
+}
+
+/// Removes the words of `tag`, in singular or plural, from the name `name`,
+/// e.g. `create_user` without `users` is `create`.
+fn without_words(name: &str, tag: &str) -> String {
+    let tag = heck::AsSnakeCase(tag).to_string();
+    let tag_words = tag.split('_').collect::<Vec<_>>();
+    heck::AsSnakeCase(name)
+        .to_string()
+        .split('_')
+        .filter(|word| {
+            !tag_words
+                .iter()
+                .any(|tag_word| tag_word == word || *tag_word == format!("{word}s"))
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Whether `name` is a valid identifier in TypeScript, ignoring non-ASCII
+/// ones.
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Renders `name` as a property name, quoted unless it's an identifier.
+pub(crate) fn render_property_name(name: &str) -> Cow<'_, str> {
+    if is_identifier(name) {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(serde_json::Value::from(name).to_string())
+    }
+}
+
+/// Renders an access to the property `name`, e.g. `.name` or `["a-b"]`.
+fn render_property_access(name: &str) -> String {
+    if is_identifier(name) {
+        format!(".{name}")
+    } else {
+        format!("[{}]", serde_json::Value::from(name))
+    }
+}
+
+/// Pattern of the parameters of route paths, capturing their name.
+pub(crate) const PATH_PARAM: &str = r"\{([^{}]*)\}";
+
+/// Renders `path` as a template literal interpolating the URI-encoded
+/// `params`.
+fn render_path(path: &str) -> String {
+    let escaped = path
+        .replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace('$', "\\$");
+    let path_param = regex::Regex::new(PATH_PARAM).expect("valid pattern");
+    let path = path_param.replace_all(&escaped, |captures: &regex::Captures| {
+        format!("${{encodeURIComponent(params.{})}}", &captures[1])
+    });
+    format!("`{path}`")
+}
+
+/// Maps `items` to diagnostics or other results on as many threads as
+/// there are cores, keeping the order of `items`.
+pub(crate) fn par_flat_map<T: Sync, R: Send>(
+    items: &[T], f: impl Fn(&T) -> Vec<R> + Sync,
+) -> Vec<R> {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    let chunk_size = items.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let chunks = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| chunk.iter().flat_map(&f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.join().expect("workers don't panic"))
+            .collect()
+    })
+}
+
+/// Copies `models` with the fields they inherit through `extends` added to
+/// their own, for targets without inheritance.
+pub(crate) fn flatten_extends(
+    models: &BTreeMap<String, ApiDocsModelDefinition>,
+) -> BTreeMap<String, ApiDocsModelDefinition> {
+    fn inherit<'a>(
+        models: &'a BTreeMap<String, ApiDocsModelDefinition>, name: &'a str,
+        fields: &mut ApiDocsModelObject, visited: &mut BTreeSet<&'a str>,
+    ) {
+        let Some(model) = models.get(name).filter(|_| visited.insert(name)) else {
+            return;
+        };
+        for parent in &model.extends {
+            inherit(models, parent, fields, visited);
+        }
+        fields.extend(model.fields.clone());
+    }
+
+    models
+        .iter()
+        .map(|(name, model)| {
+            let mut fields = ApiDocsModelObject::new();
+            inherit(models, name, &mut fields, &mut BTreeSet::new());
+            let model = ApiDocsModelDefinition {
+                extends: Vec::new(),
+                fields,
+                ..model.clone()
+            };
+            (name.clone(), model)
+        })
+        .collect()
+}
+
+/// Returns the models TypeScript code is generated for, with inline objects
+/// hoisted if `config` asks for it.
+pub(crate) fn typescript_models<'a>(
+    models: &'a BTreeMap<String, ApiDocsModelDefinition>, config: &TypeScriptConfig,
+) -> Cow<'a, BTreeMap<String, ApiDocsModelDefinition>> {
+    if config.hoist_inline_objects {
+        Cow::Owned(hoist_inline_objects(models))
+    } else {
+        Cow::Borrowed(models)
+    }
+}
+
+/// Copies `models` with the objects nested in their fields lifted into
+/// models named after their path, e.g. `user_address` for the `address`
+/// field of `user`, and referenced instead. Objects in unions stay inline,
+/// as do objects whose name is already taken.
+pub(crate) fn hoist_inline_objects(
+    models: &BTreeMap<String, ApiDocsModelDefinition>,
+) -> BTreeMap<String, ApiDocsModelDefinition> {
+    fn hoist(
+        name: &str, model: &mut ApiDocsModel, models: &BTreeMap<String, ApiDocsModelDefinition>,
+        hoisted: &mut BTreeMap<String, ApiDocsModelDefinition>,
+    ) {
+        if let Some(item) = &mut model.model {
+            hoist(&format!("{name}_item"), item, models, hoisted);
+        }
+        if let Some(value) = &mut model.values {
+            hoist(&format!("{name}_value"), value, models, hoisted);
+        }
+        for (index, item) in model.items.iter_mut().flatten().enumerate() {
+            hoist(&format!("{name}_{index}"), item, models, hoisted);
+        }
+        if model.r#type != ApiDocsModelObjectType::Object {
+            return;
+        }
+        let fields = model
+            .fields
+            .as_mut()
+            .expect("validated: `fields` is set for objects");
+        for (field_name, field) in fields.iter_mut() {
+            hoist(&format!("{name}_{field_name}"), field, models, hoisted);
+        }
+        if models.contains_key(name) || hoisted.contains_key(name) {
+            return;
+        }
+        hoisted.insert(name.to_string(), std::mem::take(fields).into());
+        model.r#type = ApiDocsModelObjectType::Ref;
+        model.fields = None;
+        model.r#ref = Some(name.to_string());
+    }
+
+    let mut hoisted = BTreeMap::new();
+    let mut result = models.clone();
+    for (name, model) in &mut result {
+        for (field_name, field) in &mut model.fields {
+            hoist(&format!("{name}_{field_name}"), field, models, &mut hoisted);
+        }
+    }
+    result.extend(hoisted);
+    result
+}
+
+/// Collects the names of the models referenced by `model`, at any depth.
+pub(crate) fn collect_refs<'a>(model: &'a ApiDocsModel, refs: &mut Vec<&'a str>) {
+    refs.extend(model.r#ref.as_deref());
+    if let Some(item) = &model.model {
+        collect_refs(item, refs);
+    }
+    if let Some(value) = &model.values {
+        collect_refs(value, refs);
+    }
+    for item in model.items.iter().flatten() {
+        collect_refs(item, refs);
+    }
+    for (_, alternative) in model.alternatives() {
+        collect_refs(alternative, refs);
+    }
+    for field in model.fields.iter().flat_map(|fields| fields.values()) {
+        collect_refs(field, refs);
+    }
+}
+
+/// Returns the models that reference themselves, directly or through other
+/// models.
+pub(crate) fn recursive_models(
+    models: &BTreeMap<String, ApiDocsModelDefinition>,
+) -> BTreeSet<&str> {
+    let refs = models
+        .iter()
+        .map(|(name, model)| {
+            let mut refs = Vec::new();
+            for field in model.fields.values() {
+                collect_refs(field, &mut refs);
+            }
+            (name.as_str(), refs)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    models
+        .keys()
+        .map(String::as_str)
+        .filter(|name| {
+            let mut pending = refs[name].clone();
+            let mut visited = BTreeSet::new();
+            while let Some(next) = pending.pop() {
+                if next == *name {
+                    return true;
+                }
+                if visited.insert(next) {
+                    pending.extend(refs.get(next).into_iter().flatten());
+                }
+            }
+            false
+        })
+        .collect()
+}
+
+/// Renders `comment` verbatim as line comments, which TypeScript and Rust
+/// share.
+pub(crate) fn render_comment(comment: Option<&str>) -> String {
+    comment
+        .into_iter()
+        .flat_map(lines)
+        .map(|line| format!("//{}{line}\n", if line.is_empty() { "" } else { " " }))
+        .collect()
+}
+
+/// Splits `text` into lines ending with `\n`, `\r\n` or a lone `\r`, so that
+/// output has the same line endings whatever the platform the spec was
+/// written on.
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().flat_map(|line| line.split('\r'))
+}
+
+/// Renders `description` and a `@deprecated` tag as a JSDoc comment indented
+/// by `indent`, on a single line if it fits on one.
+fn render_doc(
+    description: Option<&str>, deprecation: Option<&Deprecation>, indent: &str,
+) -> String {
+    let mut doc = description.unwrap_or_default().trim().to_string();
+    if Deprecation::is_deprecated(deprecation) {
+        if !doc.is_empty() {
+            doc.push('\n');
+        }
+        doc.push_str("@deprecated");
+        if let Some(Deprecation::Reason(reason)) = deprecation {
+            doc.push_str(&format!(" {}", reason.trim()));
+        }
+    }
+    if doc.is_empty() {
+        return String::new();
+    }
+    // `*/` would end the comment early.
+    let doc = doc.replace("*/", "*\\/");
+    match lines(&doc).collect::<Vec<_>>().as_slice() {
+        [line] => format!("{indent}/** {line} */\n"),
+        lines => {
+            let lines = lines
+                .iter()
+                .map(|line| {
+                    format!(
+                        "{indent} *{}{line}\n",
+                        if line.is_empty() { "" } else { " " }
+                    )
+                })
+                .collect::<String>();
+            format!("{indent}/**\n{lines}{indent} */\n")
+        },
+    }
+}
+
+/// The fields of a model that aren't behind a feature flag.
+fn stable_fields(fields: &ApiDocsModelObject) -> Vec<(&str, &ApiDocsModel)> {
+    fields
+        .iter()
+        .filter(|(_, field)| field.feature.is_none())
+        .map(|(name, field)| (name.as_str(), field))
+        .collect()
+}
+
+/// Whether any field nested in `model` has a description.
+fn documents_fields(model: &ApiDocsModel) -> bool {
+    model
+        .fields
+        .iter()
+        .flat_map(|fields| fields.values())
+        .any(|field| field.description.is_some() || documents_fields(field))
+        || model
+            .model
+            .iter()
+            .chain(&model.values)
+            .any(|model| documents_fields(model))
+        || model.items.iter().flatten().any(documents_fields)
+        || model
+            .alternatives()
+            .any(|(_, alternative)| documents_fields(alternative))
+}
+
+fn optional_marker(model: &ApiDocsModel) -> &'static str {
+    if model.required {
+        ""
+    } else {
+        "?"
+    }
+}
+
+pub(crate) fn render_enum_members(members: &[serde_json::Value]) -> String {
+    members
+        .iter()
+        .map(|member| member.to_string())
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+pub(crate) fn render_header(config: &TypeScriptConfig) -> String {
+    config
+        .suppressions
+        .iter()
+        .map(|suppression| format!("{}\n", suppression.comment()))
+        .collect()
+}
+
+/// Validates the spec, then renders the interfaces for all models followed
+/// by the client functions for all routes.
+pub fn generate_typescript(
+    api_docs: &ApiDocs, config: &TypeScriptConfig,
+) -> Result<String, Diagnostics> {
+    let errors = validate::validate(api_docs);
+    if !errors.is_empty() {
+        return Err(Diagnostics(errors));
+    }
+    Ok(render_typescript(api_docs, config))
+}
+
+/// Validates the spec once, then renders all `targets` concurrently. Each
+/// output is passed to `write` with the index of its target as soon as it's
+/// rendered. Validation and rendering are recorded in `timings`.
+pub fn generate_targets(
+    api_docs: &ApiDocs, targets: &[Target], config: &Config, timings: &Timings,
+    mut write: impl FnMut(usize, String) -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    let mut errors = timings.time("validate", || validate::validate(api_docs));
+    if targets.contains(&Target::Smoke) {
+        errors.extend(smoke::check(api_docs, &config.smoke));
+    }
+    if !errors.is_empty() {
+        return Err(Diagnostics(errors).into());
+    }
+
+    std::thread::scope(|scope| {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for (index, target) in targets.iter().enumerate() {
+            let sender = sender.clone();
+            scope.spawn(move || {
+                // The receiver is only gone if writing an earlier output failed.
+                let output = timings.time(format!("render {target}"), || {
+                    target.render(api_docs, config)
+                });
+                let _ = sender.send((index, output));
+            });
+        }
+        drop(sender);
+        receiver
+            .iter()
+            .try_for_each(|(index, output)| write(index, output))
+    })
+}
+
+impl Target {
+    /// Renders a validated spec.
+    fn render(self, api_docs: &ApiDocs, config: &Config) -> String {
+        match self {
+            Target::TypeScript => render_typescript(api_docs, &config.typescript),
+            Target::FetchClient => fetch_client::render_fetch_client(api_docs, &config.typescript),
+            Target::ReactQuery => react_query::render_react_query(api_docs, &config.typescript),
+            Target::Swr => swr::render_swr(api_docs, &config.typescript),
+            Target::Angular => angular::render_angular(api_docs, &config.typescript),
+            Target::Msw => msw::render_msw(api_docs, &config.typescript),
+            Target::Zod => zod::render_zod(api_docs, &config.typescript),
+            Target::Rust => rust::render_rust(api_docs, &config.rust),
+            Target::Smoke => smoke::render_smoke(api_docs, &config.smoke, &config.typescript),
+        }
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Target::TypeScript => "typescript",
+            Target::FetchClient => "ts-fetch-client",
+            Target::ReactQuery => "react-query",
+            Target::Swr => "swr",
+            Target::Angular => "angular",
+            Target::Msw => "msw",
+            Target::Zod => "zod",
+            Target::Rust => "rust",
+            Target::Smoke => "smoke",
+        })
+    }
+}
+
+impl std::str::FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "typescript" => Ok(Self::TypeScript),
+            "ts-fetch-client" => Ok(Self::FetchClient),
+            "react-query" => Ok(Self::ReactQuery),
+            "swr" => Ok(Self::Swr),
+            "angular" => Ok(Self::Angular),
+            "msw" => Ok(Self::Msw),
+            "zod" => Ok(Self::Zod),
+            "rust" => Ok(Self::Rust),
+            "smoke" => Ok(Self::Smoke),
+            _ => Err(format!(
+                "unknown target `{s}`, expected `typescript`, `ts-fetch-client`, `react-query`, \
+                 `swr`, `angular`, `msw`, `zod`, `rust` or `smoke`"
+            )),
+        }
+    }
+}
+
+fn render_typescript(api_docs: &ApiDocs, config: &TypeScriptConfig) -> String {
+    let models = typescript_models(&api_docs.models, config);
+    let renderer = TypeScriptRenderer::new(config);
+    let mut sections = vec![
+        renderer.render_interfaces(&models),
+        renderer.render_factories(&flatten_extends(&models)),
+        renderer.render_error_classes(&api_docs.routes),
+        renderer.render_routes(&api_docs.routes),
+        renderer.render_sockets(&api_docs.sockets),
+    ];
+    // Brands are only known once everything using them is rendered.
+    sections.insert(0, renderer.render_brands());
+    let body = sections
+        .into_iter()
+        .filter(|section| !section.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut imports = renderer.render_imports();
+    if !imports.is_empty() {
+        imports.push('\n');
+    }
+    format!("{}{imports}{body}", render_header(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) fn render_field_type(model: &ApiDocsModel) -> String {
+        TypeScriptRenderer::new(&TypeScriptConfig::default()).render_field_type(model, 0)
+    }
+
+    pub(crate) fn render_field(name: &str, model: &ApiDocsModel) -> String {
+        TypeScriptRenderer::new(&TypeScriptConfig::default()).render_field(name, model, 0)
+    }
+
+    pub(crate) fn render_interface(name: &str, obj: &ApiDocsModelObject) -> String {
+        TypeScriptRenderer::new(&TypeScriptConfig::default()).render_interface(name, obj)
+    }
+
+    pub(crate) fn render_interfaces(models: &BTreeMap<String, ApiDocsModelDefinition>) -> String {
+        TypeScriptRenderer::new(&TypeScriptConfig::default()).render_interfaces(models)
+    }
+
+    fn render_route(name: &str, route: &ApiDocsRoute) -> String {
+        TypeScriptRenderer::new(&TypeScriptConfig::default()).render_route(name, route)
+    }
+
+    fn render_routes(routes: &BTreeMap<String, ApiDocsRoute>) -> String {
+        TypeScriptRenderer::new(&TypeScriptConfig::default()).render_routes(routes)
+    }
+
+    #[test]
+    fn test_render_field_type_string() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::String,
+            required: true,
+            ..Default::default()
+        });
+        k9::snapshot!(rendered, "string");
+    }
+
+    #[test]
+    fn test_render_field_type_number() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Number,
+            required: false,
+            ..Default::default()
+        });
+        k9::snapshot!(rendered, "number | undefined");
+    }
+
+    #[test]
+    fn test_render_field_type_boolean() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Boolean,
+            required: false,
+            ..Default::default()
+        });
+        k9::snapshot!(rendered, "boolean | undefined");
+    }
+
+    #[test]
+    fn test_render_field_type_array_of_scalar() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Array,
+            model: Some(Box::new(ApiDocsModel {
+                r#type: ApiDocsModelObjectType::Boolean,
+                required: true,
+                ..Default::default()
+            })),
+            required: true,
+            ..Default::default()
+        });
+        k9::snapshot!(rendered, "Array<boolean>");
+    }
+
+    #[test]
+    fn test_render_field_type_array_of_object() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Array,
+            model: Some(Box::new(ApiDocsModel {
+                r#type: ApiDocsModelObjectType::Object,
+                fields: Some(
+                    [
+                        (
+                            "foo".to_string(),
+                            ApiDocsModel {
+                                r#type: ApiDocsModelObjectType::String,
+                                required: true,
+                                ..Default::default()
+                            },
+                        ),
+                        (
+                            "bar".to_string(),
+                            ApiDocsModel {
+                                r#type: ApiDocsModelObjectType::Boolean,
+                                required: true,
+                                ..Default::default()
+                            },
+                        ),
+                    ]
+                    .into(),
+                ),
+                required: true,
+                ..Default::default()
+            })),
+            required: false,
+            ..Default::default()
+        });
+        k9::snapshot!(
+            rendered,
+            "
+Array<{
+  bar: boolean;
+  foo: string;
+}> | undefined
+"
+        );
+    }
+
+    #[test]
+    fn test_render_field_type_array_of_array() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Array,
+            model: Some(Box::new(ApiDocsModel {
+                r#type: ApiDocsModelObjectType::Array,
+                model: Some(Box::new(ApiDocsModel {
+                    r#type: ApiDocsModelObjectType::String,
+                    required: true,
+                    ..Default::default()
+                })),
+                required: true,
+                ..Default::default()
+            })),
+            required: false,
+            ..Default::default()
+        });
+        k9::snapshot!(rendered, "Array<Array<string>> | undefined");
+    }
+
+    #[test]
+    fn test_render_field_type_enum_of_mixed_members() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Enum,
+            members: Some(vec![
+                serde_json::json!("a"),
+                serde_json::json!("b"),
+                serde_json::json!(3),
+                serde_json::json!(true),
+            ]),
+            required: true,
+            ..Default::default()
+        });
+        k9::snapshot!(rendered, r#""a" | "b" | 3 | true"#);
+    }
+
+    #[test]
+    fn test_render_field_type_array_of_enum() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Array,
+            model: Some(Box::new(ApiDocsModel {
+                r#type: ApiDocsModelObjectType::Enum,
+                members: Some(vec![serde_json::json!("on"), serde_json::json!(0)]),
+                required: true,
+                ..Default::default()
+            })),
+            required: true,
+            ..Default::default()
+        });
+        k9::snapshot!(rendered, r#"Array<"on" | 0>"#);
+    }
+
+    #[test]
+    fn test_render_field_type_object_with_enum() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Object,
+            fields: Some(
+                [(
+                    "status".to_string(),
+                    ApiDocsModel {
+                        r#type: ApiDocsModelObjectType::Enum,
+                        members: Some(vec![
+                            serde_json::json!("active"),
+                            serde_json::json!("disabled"),
+                        ]),
+                        required: false,
+                        ..Default::default()
+                    },
+                )]
+                .into(),
+            ),
+            required: true,
+            ..Default::default()
+        });
+        k9::snapshot!(
+            rendered,
+            r#"
+{
+  status?: "active" | "disabled" | undefined;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_required_field() {
+        let rendered = render_field(
+            "foo",
+            &ApiDocsModel {
+                r#type: ApiDocsModelObjectType::Boolean,
+                required: true,
+                ..Default::default()
+            },
+        );
+        k9::snapshot!(
+            rendered,
+            "
+foo: boolean;
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_non_required_field() {
+        let rendered = render_field(
+            "foo",
+            &ApiDocsModel {
+                r#type: ApiDocsModelObjectType::Boolean,
+                required: false,
+                ..Default::default()
+            },
+        );
+        k9::snapshot!(
+            rendered,
+            "
+foo?: boolean | undefined;
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_interface_simple() {
+        let rendered = render_interface(
+            "Foo",
+            &[
+                (
+                    "foo".to_string(),
+                    ApiDocsModel {
+                        r#type: ApiDocsModelObjectType::String,
+                        required: true,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "bar".to_string(),
+                    ApiDocsModel {
+                        r#type: ApiDocsModelObjectType::Boolean,
+                        required: true,
+                        ..Default::default()
+                    },
+                ),
+            ]
+            .into(),
+        );
+        k9::snapshot!(
+            rendered,
+            "
+export interface Foo {
+  bar: boolean;
+  foo: string;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_interface_with_nested_objects() {
+        let rendered = render_interface(
+            "Foo",
+            &[
+                (
+                    "foo".to_string(),
+                    ApiDocsModel {
+                        r#type: ApiDocsModelObjectType::String,
+                        required: true,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "bar".to_string(),
+                    ApiDocsModel {
+                        r#type: ApiDocsModelObjectType::Object,
+                        fields: Some(
+                            [
+                                (
+                                    "foo".to_string(),
+                                    ApiDocsModel {
+                                        r#type: ApiDocsModelObjectType::String,
+                                        required: true,
+                                        ..Default::default()
+                                    },
+                                ),
+                                (
+                                    "bar".to_string(),
+                                    ApiDocsModel {
+                                        r#type: ApiDocsModelObjectType::Boolean,
+                                        required: true,
+                                        ..Default::default()
+                                    },
+                                ),
+                            ]
+                            .into(),
+                        ),
+                        required: true,
+                        ..Default::default()
+                    },
+                ),
+            ]
+            .into(),
+        );
+        k9::snapshot!(
+            rendered,
+            "
+export interface Foo {
+  bar: {
+    bar: boolean;
+    foo: string;
+  };
+  foo: string;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_models_simple() {
+        let rendered = render_interfaces(
+            &[(
+                "Foo".to_string(),
+                ApiDocsModelObject::from([(
+                    "baz".to_string(),
+                    ApiDocsModel {
+                        r#type: ApiDocsModelObjectType::Boolean,
+                        required: true,
+                        ..Default::default()
+                    },
+                )])
+                .into(),
+            )]
+            .into(),
+        );
+        k9::snapshot!(
+            rendered,
+            "
+export interface Foo {
+  baz: boolean;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_route() {
+        let rendered = render_route(
+            "create_user",
+            &ApiDocsRoute {
+                accepts: "new_user".to_string(),
+                returns: "user".to_string(),
+                ..Default::default()
+            },
+        );
+        k9::snapshot!(
+            rendered,
+            r#"
+export function createUser(request: ApiRequest, body: NewUser): Promise<User> {
+  return request("create_user", body) as Promise<User>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_routes_empty() {
+        let rendered = render_routes(&BTreeMap::new());
+        k9::snapshot!(rendered, "");
+    }
+
+    #[test]
+    fn test_render_routes_declares_request_type() {
+        let rendered = render_routes(
+            &[(
+                "get_user".to_string(),
+                ApiDocsRoute {
+                    accepts: "user_id".to_string(),
+                    returns: "user".to_string(),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+        );
+        k9::snapshot!(
+            rendered,
+            r#"
+export type ApiRequest = (route: string, body: unknown) => Promise<unknown>;
+
+export function getUser(request: ApiRequest, body: UserId): Promise<User> {
+  return request("get_user", body) as Promise<User>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_model_definition_forms() {
+        let models: BTreeMap<String, ApiDocsModelDefinition> =
+            serde_json::from_value(serde_json::json!({
+                "bare": {
+                    "fields": { "type": "String", "required": true }
+                },
+                "full": {
+                    "description": "Has a description.",
+                    "fields": {
+                        "type": { "type": "String", "required": true }
+                    }
+                }
+            }))
+            .unwrap();
+        k9::snapshot!(
+            render_interfaces(&models),
+            "
+export interface Bare {
+  fields: string;
+}
+
+/** Has a description. */
+export interface Full {
+  type: string;
+}
+
+"
+        );
+        k9::snapshot!(
+            serde_json::to_string(&models).unwrap(),
+            r#"{"bare":{"fields":{"type":"String","required":true}},"full":{"description":"Has a description.","fields":{"type":{"type":"String","required":true}}}}"#
+        );
+    }
+
+    #[test]
+    fn test_render_field_type_ref() {
+        let rendered = render_field_type(&ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Array,
+            model: Some(Box::new(ApiDocsModel {
+                r#type: ApiDocsModelObjectType::Ref,
+                r#ref: Some("user_profile".to_string()),
+                required: true,
+                ..Default::default()
+            })),
+            required: false,
+            ..Default::default()
+        });
+        k9::snapshot!(rendered, "Array<UserProfile> | undefined");
+    }
+
+    #[test]
+    fn test_generate_typescript_with_suppressions() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": { "foo": { "bar": { "type": "String", "required": true } } },
+            "routes": {}
+        }))
+        .unwrap();
+        let config: TypeScriptConfig = serde_json::from_value(serde_json::json!({
+            "suppressions": ["eslint", "ts-nocheck"]
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &config).unwrap(),
+            "
+/* eslint-disable */
+// @ts-nocheck
+export interface Foo {
+  bar: string;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_lint_clean() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "empty": {},
+                "entity": { "id": { "type": "Number", "required": true, "read_only": true } },
+                "user": { "extends": ["entity"], "fields": {} },
+                "settings": {
+                    "extra": { "type": "Object", "required": false, "fields": {} }
+                }
+            },
+            "routes": {
+                "get_user": {
+                    "accepts": "empty",
+                    "returns": "user",
+                    "method": "GET",
+                    "path": "/user"
+                }
+            }
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            lint_clean: true,
+            ..Default::default()
+        };
+        let output = generate_typescript(&api_docs, &config).unwrap();
+        // typescript-eslint's `no-empty-object-type` and `no-explicit-any`.
+        let empty =
+            regex::Regex::new(r"\binterface \w+ (extends [\w, ]+ )?\{\}|[:<|&] \{\}").unwrap();
+        assert!(!empty.is_match(&output), "{output}");
+        assert!(!output.contains("any"), "{output}");
+        k9::snapshot!(
+            output,
+            r#"
+export type Empty = Record<string, never>;
+
+export interface Entity {
+  id: number;
+}
+
+export type EntityCreateRequest = Record<string, never>;
+
+export interface EntityResponse {
+  id: number;
+}
+
+export interface Settings {
+  extra?: Record<string, never> | undefined;
+}
+
+export type User = Entity;
+
+export type UserCreateRequest = Record<string, never>;
+
+export interface UserResponse {
+  id: number;
+}
+
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: {
+    method?:
+      | "GET"
+      | "HEAD"
+      | "POST"
+      | "PUT"
+      | "PATCH"
+      | "DELETE"
+      | "OPTIONS"
+      | "TRACE";
+    path?: string;
+  },
+) => Promise<unknown>;
+
+export function getUser(request: ApiRequest): Promise<User> {
+  return request("get_user", undefined, {
+    method: "GET",
+    path: `/user`,
+  }) as Promise<User>;
+}
+
+"#
+        );
+
+        let minimal = TypeScriptConfig {
+            client: TypeScriptClient::Minimal,
+            ..config
+        };
+        let output = generate_typescript(&api_docs, &minimal).unwrap();
+        assert!(!empty.is_match(&output), "{output}");
+        assert!(
+            output.contains("Route<Record<string, never>, User>"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_render_interface_with_tabs() {
+        let config = TypeScriptConfig {
+            use_tabs: true,
+            ..Default::default()
+        };
+        let rendered = TypeScriptRenderer::new(&config).render_interface(
+            "Foo",
+            &[(
+                "bar".to_string(),
+                ApiDocsModel {
+                    r#type: ApiDocsModelObjectType::Object,
+                    fields: Some(
+                        [(
+                            "baz".to_string(),
+                            ApiDocsModel {
+                                r#type: ApiDocsModelObjectType::Number,
+                                required: true,
+                                ..Default::default()
+                            },
+                        )]
+                        .into(),
+                    ),
+                    required: true,
+                    ..Default::default()
+                },
+            )]
+            .into(),
+        );
+        assert_eq!(
+            rendered,
+            "export interface Foo {\n\tbar: {\n\t\tbaz: number;\n\t};\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_render_field_type_optional_wrappers() {
+        let model = ApiDocsModel {
+            r#type: ApiDocsModelObjectType::Array,
+            model: Some(Box::new(ApiDocsModel {
+                r#type: ApiDocsModelObjectType::String,
+                required: false,
+                ..Default::default()
+            })),
+            required: false,
+            ..Default::default()
+        };
+        let rendered = [
+            OptionalWrapper::Undefined,
+            OptionalWrapper::Null,
+            OptionalWrapper::Generic("Optional".to_string()),
+        ]
+        .map(|optional| {
+            let config = TypeScriptConfig {
+                optional,
+                ..Default::default()
+            };
+            TypeScriptRenderer::new(&config).render_field_type(&model, 0)
+        });
+        k9::snapshot!(
+            rendered.join("\n"),
+            "
+Array<string | undefined> | undefined
+Array<string | null> | null
+Optional<Array<Optional<string>>>
+"
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_imports_used_types() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": { "foo": { "bar": { "type": "String", "required": false } } },
+            "routes": {}
+        }))
+        .unwrap();
+        let config: TypeScriptConfig = serde_json::from_value(serde_json::json!({
+            "optional": { "generic": "Optional" },
+            "imports": { "Optional": "./types", "Unused": "./types" }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &config).unwrap(),
+            r#"
+import type { Optional } from "./types";
+
+export interface Foo {
+  bar?: Optional<string>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_interface_with_max_line_width() {
+        let config = TypeScriptConfig {
+            max_line_width: Some(40),
+            ..Default::default()
+        };
+        let fields = serde_json::from_value(serde_json::json!({
+            "point": {
+                "type": "Object",
+                "required": true,
+                "fields": {
+                    "x": { "type": "Number", "required": true },
+                    "y": { "type": "Number", "required": true }
+                }
+            },
+            "shape": {
+                "type": "Object",
+                "required": true,
+                "fields": {
+                    "center": {
+                        "type": "Object",
+                        "required": true,
+                        "fields": {
+                            "x": { "type": "Number", "required": true },
+                            "y": { "type": "Number", "required": true }
+                        }
+                    },
+                    "radius": { "type": "Number", "required": false }
+                }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            TypeScriptRenderer::new(&config).render_interface("Foo", &fields),
+            "
+export interface Foo {
+  point: { x: number; y: number };
+  shape: {
+    center: { x: number; y: number };
+    radius?: number | undefined;
+  };
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_with_comments() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "comment_before": "Copyright ACME.\n\nSee https://example.com/users",
+                    "comment_after": "End of user.",
+                    "fields": {}
+                }
+            },
+            "routes": {
+                "get_user": { "accepts": "user", "returns": "user", "comment_before": "Beta." }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &TypeScriptConfig::default()).unwrap(),
+            r#"
+// Copyright ACME.
+//
+// See https://example.com/users
+export interface User {}
+// End of user.
+
+export type ApiRequest = (route: string, body: unknown) => Promise<unknown>;
+
+// Beta.
+export function getUser(request: ApiRequest, body: User): Promise<User> {
+  return request("get_user", body) as Promise<User>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_field_type_union() {
+        let rendered = render_field_type(
+            &serde_json::from_value(serde_json::json!({
+                "type": "Union",
+                "required": false,
+                "models": [
+                    { "type": "Ref", "required": true, "ref": "user" },
+                    { "type": "Enum", "required": true, "members": ["none"] }
+                ]
+            }))
+            .unwrap(),
+        );
+        k9::snapshot!(rendered, r#"User | "none" | undefined"#);
+    }
+
+    #[test]
+    fn test_render_field_type_discriminated_union() {
+        let rendered = render_field_type(
+            &serde_json::from_value(serde_json::json!({
+                "type": "Union",
+                "required": true,
+                "discriminator": "kind",
+                "variants": {
+                    "circle": {
+                        "type": "Object",
+                        "required": true,
+                        "fields": { "radius": { "type": "Number", "required": true } }
+                    },
+                    "square": { "type": "Ref", "required": true, "ref": "square" }
+                }
+            }))
+            .unwrap(),
+        );
+        k9::snapshot!(
+            rendered,
+            r#"
+{
+  kind: "circle";
+  radius: number;
+} | { kind: "square" } & Square
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_field_type_map() {
+        let rendered = render_field_type(
+            &serde_json::from_value(serde_json::json!({
+                "type": "Map",
+                "required": true,
+                "values": { "type": "Ref", "required": true, "ref": "user_profile" }
+            }))
+            .unwrap(),
+        );
+        k9::snapshot!(rendered, "Record<string, UserProfile>");
+    }
+
+    #[test]
+    fn test_render_field_type_tuple() {
+        let rendered = render_field_type(
+            &serde_json::from_value(serde_json::json!({
+                "type": "Tuple",
+                "required": true,
+                "items": [
+                    { "type": "Number", "required": true },
+                    { "type": "String", "required": false }
+                ]
+            }))
+            .unwrap(),
+        );
+        k9::snapshot!(rendered, "[number, string | undefined]");
+    }
+
+    #[test]
+    fn test_render_field_nullable() {
+        let fields: ApiDocsModelObject = serde_json::from_value(serde_json::json!({
+            "absent": { "type": "String", "required": false },
+            "null": { "type": "String", "required": true, "nullable": true },
+            "both": { "type": "String", "required": false, "nullable": true }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_interface("Foo", &fields),
+            "
+export interface Foo {
+  absent?: string | undefined;
+  both?: string | null | undefined;
+  null: string | null;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_generate_targets() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": { "foo": { "bar": { "type": "String", "required": true } } },
+            "routes": {}
+        }))
+        .unwrap();
+        let config = Config::default();
+        let mut outputs = BTreeMap::new();
+        generate_targets(
+            &api_docs,
+            &[Target::Zod, Target::TypeScript],
+            &config,
+            &Timings::default(),
+            |index, output| {
+                outputs.insert(index, output);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            outputs[&0],
+            zod::generate_zod(&api_docs, &config.typescript).unwrap()
+        );
+        assert_eq!(
+            outputs[&1],
+            generate_typescript(&api_docs, &config.typescript).unwrap()
+        );
+    }
+
+    /// A spec with line endings of every platform in its descriptions and
+    /// comments, names that sort differently across locales, floats without
+    /// an exact representation and enough models and routes to be rendered
+    /// in parallel.
+    fn determinism_api_docs() -> ApiDocs {
+        let mut models = serde_json::json!({
+            "empty": {},
+            "shape": {
+                "Zeta": { "type": "Number", "required": true, "minimum": 0.1, "maximum": 1e21 },
+                "alpha": { "type": "Number", "required": true, "minimum": -0.5 },
+                "ärger": { "type": "String", "required": false, "description": "a\r\nb\rc" },
+                "Émile": { "type": "Boolean", "required": true }
+            }
+        });
+        let mut routes = serde_json::Map::new();
+        for index in 0..32 {
+            models[format!("model_{index}")] = serde_json::json!({
+                "shape": { "type": "Ref", "required": true, "ref": "shape" }
+            });
+            routes.insert(
+                format!("get_model_{index}"),
+                serde_json::json!({
+                    "accepts": "empty",
+                    "returns": format!("model_{index}"),
+                    "method": "GET",
+                    "path": format!("/models/{index}"),
+                    "tags": ["models"],
+                    "description": "Returns\r\na model.",
+                    "comment_before": "one\r\ntwo"
+                }),
+            );
+        }
+        serde_json::from_value(serde_json::json!({ "models": models, "routes": routes })).unwrap()
+    }
+
+    #[test]
+    fn test_generate_targets_deterministic() {
+        let api_docs = determinism_api_docs();
+        let targets = [
+            Target::TypeScript,
+            Target::FetchClient,
+            Target::ReactQuery,
+            Target::Swr,
+            Target::Angular,
+            Target::Msw,
+            Target::Zod,
+            Target::Rust,
+            Target::Smoke,
+        ];
+        let generate = |config: &Config| {
+            let mut outputs = BTreeMap::new();
+            generate_targets(
+                &api_docs,
+                &targets,
+                config,
+                &Timings::default(),
+                |index, output| {
+                    outputs.insert(index, output);
+                    Ok(())
+                },
+            )
+            .unwrap();
+            outputs
+        };
+        let config = Config::default();
+        let expected = generate(&config);
+        for (index, output) in &expected {
+            assert!(!output.contains('\r'), "`{}` has a `\\r`", targets[*index]);
+        }
+        for _ in 0..3 {
+            assert_eq!(generate(&config), expected);
+        }
+    }
+
+    #[test]
+    fn test_generate_ordering_and_floats() {
+        let api_docs = determinism_api_docs();
+        let shape = ApiDocs {
+            models: api_docs
+                .models
+                .into_iter()
+                .filter(|(name, _)| name == "shape")
+                .collect(),
+            routes: BTreeMap::new(),
+            sockets: BTreeMap::new(),
+        };
+        // Names sort by code point, not by the collation of a locale, and
+        // floats render as the shortest decimal reading back as them.
+        k9::snapshot!(
+            generate_typescript(&shape, &TypeScriptConfig::default()).unwrap(),
+            r#"
+export interface Shape {
+  Zeta: number;
+  alpha: number;
+  "Émile": boolean;
+  /**
+   * a
+   * b
+   * c
+   */
+  "ärger"?: string | undefined;
+}
+
+"#
+        );
+        k9::snapshot!(
+            zod::generate_zod(&shape, &TypeScriptConfig::default()).unwrap(),
+            r#"
+import { z } from "zod";
+
+export const ShapeSchema = z.object({
+  Zeta: z.number().min(0.1).max(1000000000000000000000),
+  alpha: z.number().min(-0.5),
+  "Émile": z.boolean(),
+  "ärger": z.string().optional(),
+});
+export type Shape = z.infer<typeof ShapeSchema>;
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_field_type_integer_type() {
+        let config = TypeScriptConfig {
+            integer_type: Some("Integer".to_string()),
+            ..Default::default()
+        };
+        let renderer = TypeScriptRenderer::new(&config);
+        let rendered = ["integer", "float"].map(|format| {
+            let model = serde_json::from_value(serde_json::json!({
+                "type": "Number",
+                "format": format,
+                "required": true
+            }))
+            .unwrap();
+            renderer.render_field_type(&model, 0)
+        });
+        k9::snapshot!(rendered.join(", "), "Integer, number");
+    }
+
+    #[test]
+    fn test_generate_typescript_string_formats() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "event": {
+                    "day": { "type": "String", "format": "date", "required": true },
+                    "at": { "type": "String", "format": "date-time", "required": true },
+                    "time": { "type": "String", "format": "time", "required": false }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            formats: [
+                (ApiDocsModelFormat::Date, "Date".to_string()),
+                (ApiDocsModelFormat::DateTime, "Temporal.Instant".to_string()),
+            ]
+            .into(),
+            imports: [("Temporal".to_string(), "@js-temporal/polyfill".to_string())].into(),
+            ..Default::default()
+        };
+        k9::snapshot!(
+            generate_typescript(&api_docs, &config).unwrap(),
+            r#"
+import type { Temporal } from "@js-temporal/polyfill";
+
+export interface Event {
+  at: Temporal.Instant;
+  day: Date;
+  time?: string | undefined;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_literals() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "response": {
+                    "status": { "type": "Literal", "required": true, "value": "ok" },
+                    "version": { "type": "Literal", "required": true, "value": 42 },
+                    "cached": { "type": "Literal", "required": false, "value": true }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &Default::default()).unwrap(),
+            r#"
+export interface Response {
+  cached?: true | undefined;
+  status: "ok";
+  version: 42;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_descriptions() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "description": "A registered user.\n\nNever deleted, only deactivated.",
+                    "fields": {
+                        "id": { "type": "Number", "required": true, "description": "Unique */ id." },
+                        "address": {
+                            "type": "Object",
+                            "required": true,
+                            "fields": {
+                                "city": { "type": "String", "required": true, "description": "City name." }
+                            }
+                        }
+                    }
+                }
+            },
+            "routes": {
+                "get_user": { "accepts": "user", "returns": "user", "description": "Fetches a user." }
+            }
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            max_line_width: Some(80),
+            ..Default::default()
+        };
+        k9::snapshot!(
+            generate_typescript(&api_docs, &config).unwrap(),
+            r#"
+/**
+ * A registered user.
+ *
+ * Never deleted, only deactivated.
+ */
+export interface User {
+  address: {
+    /** City name. */
+    city: string;
+  };
+  /** Unique *\\/ id. */
+  id: number;
+}
+
+export type ApiRequest = (route: string, body: unknown) => Promise<unknown>;
+
+/** Fetches a user. */
+export function getUser(request: ApiRequest, body: User): Promise<User> {
+  return request("get_user", body) as Promise<User>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_deprecations() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "deprecated": true,
+                    "fields": {
+                        "name": {
+                            "type": "String",
+                            "required": true,
+                            "description": "Full name.",
+                            "deprecated": "Use `first_name` instead."
+                        },
+                        "first_name": { "type": "String", "required": true, "deprecated": false }
+                    }
+                }
+            },
+            "routes": {
+                "get_user": { "accepts": "user", "returns": "user", "deprecated": true }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &Default::default()).unwrap(),
+            r#"
+/** @deprecated */
+export interface User {
+  first_name: string;
+  /**
+   * Full name.
+   * @deprecated Use `first_name` instead.
+   */
+  name: string;
+}
+
+export type ApiRequest = (route: string, body: unknown) => Promise<unknown>;
+
+/** @deprecated */
+export function getUser(request: ApiRequest, body: User): Promise<User> {
+  return request("get_user", body) as Promise<User>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_routes_compression() {
+        let routes = serde_json::from_value(serde_json::json!({
+            "upload": { "accepts": "document", "returns": "empty", "compression": "gzip" },
+            "ping": { "accepts": "empty", "returns": "empty" }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_routes(&routes),
+            r#"
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: { compression?: "gzip" | "br" },
+) => Promise<unknown>;
+
+export function ping(request: ApiRequest, body: Empty): Promise<Empty> {
+  return request("ping", body) as Promise<Empty>;
+}
+
+export function upload(request: ApiRequest, body: Document): Promise<Empty> {
+  return request("upload", body, { compression: "gzip" }) as Promise<Empty>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_interfaces_read_only_write_only() {
+        let models = serde_json::from_value(serde_json::json!({
+            "user": {
+                "id": { "type": "Number", "required": true, "read_only": true },
+                "name": { "type": "String", "required": true },
+                "password": { "type": "String", "required": true, "write_only": true }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_interfaces(&models),
+            "
+export interface User {
+  id: number;
+  name: string;
+  password: string;
+}
+
+export interface UserCreateRequest {
+  name: string;
+  password: string;
+}
+
+export interface UserResponse {
+  id: number;
+  name: string;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_interfaces_feature_flags() {
+        let models = serde_json::from_value(serde_json::json!({
+            "user": {
+                "id": { "type": "Number", "required": true },
+                "nickname": {
+                    "type": "String",
+                    "required": false,
+                    "feature": "nicknames",
+                    "description": "Shown instead of the name."
+                },
+                "referrer": { "type": "Number", "required": false, "feature": "referrals" }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_interfaces(&models),
+            "
+export interface User {
+  id: number;
+}
+
+export interface UserExperimental extends User {
+  /**
+   * Shown instead of the name.
+   *
+   * Behind the feature flag `nicknames`.
+   */
+  nickname?: string | undefined;
+  /** Behind the feature flag `referrals`. */
+  referrer?: number | undefined;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_factories() {
+        let models = serde_json::from_value(serde_json::json!({
+            "settings": {
+                "theme": { "type": "String", "required": true, "default": "light" },
+                "tags": {
+                    "type": "Array",
+                    "required": true,
+                    "default": [],
+                    "model": { "type": "String", "required": true }
+                }
+            },
+            "user": {
+                "id": { "type": "Number", "required": true },
+                "name": { "type": "String", "required": true },
+                "nickname": { "type": "String", "required": false },
+                "role": { "type": "Enum", "required": true, "members": ["admin", "member"], "default": "member" }
+            },
+            "post": { "title": { "type": "String", "required": true } }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            TypeScriptRenderer::new(&TypeScriptConfig::default()).render_factories(&models),
+            r#"
+export function makeDefaultSettings(): Settings {
+  return {
+    tags: [],
+    theme: "light",
+  };
+}
+
+export function makeDefaultUser(fields: Pick<User, "id" | "name">): User {
+  return {
+    role: "member",
+    ...fields,
+  };
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_factories_feature_flags() {
+        let models = serde_json::from_value(serde_json::json!({
+            "user": {
+                "id": { "type": "Number", "required": true },
+                "role": { "type": "String", "required": true, "default": "member" },
+                "beta": { "type": "Boolean", "required": true, "feature": "beta" },
+                "theme": {
+                    "type": "String",
+                    "required": false,
+                    "default": "dark",
+                    "feature": "themes"
+                }
+            },
+            "flags": {
+                "preview": {
+                    "type": "Boolean",
+                    "required": false,
+                    "default": false,
+                    "feature": "preview"
+                }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            TypeScriptRenderer::new(&TypeScriptConfig::default()).render_factories(&models),
+            r#"
+export function makeDefaultUser(fields: Pick<User, "id">): User {
+  return {
+    role: "member",
+    ...fields,
+  };
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_branded_formats() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "id": { "type": "String", "format": "uuid", "required": true },
+                    "manager_id": { "type": "String", "format": "uuid", "required": false },
+                    "email": { "type": "String", "format": "email", "required": true },
+                    "born": { "type": "String", "format": "date", "required": true }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            branded_formats: true,
+            ..Default::default()
+        };
+        k9::snapshot!(
+            generate_typescript(&api_docs, &config).unwrap(),
+            r#"
+export type Uuid = string & { __brand: "Uuid" };
+
+export function isUuid(value: string): value is Uuid {
+  return /^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/i.test(value);
+}
+
+export function toUuid(value: string): Uuid {
+  if (!isUuid(value)) {
+    throw new TypeError(`Invalid Uuid: ${value}`);
+  }
+  return value;
+}
+
+export type Email = string & { __brand: "Email" };
+
+export function isEmail(value: string): value is Email {
+  return /^[^\\s@]+@[^\\s@]+$/.test(value);
+}
+
+export function toEmail(value: string): Email {
+  if (!isEmail(value)) {
+    throw new TypeError(`Invalid Email: ${value}`);
+  }
+  return value;
+}
+
+export interface User {
+  born: string;
+  email: Email;
+  id: Uuid;
+  manager_id?: Uuid | undefined;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_interfaces_extends() {
+        let models = serde_json::from_value(serde_json::json!({
+            "base_entity": {
+                "id": { "type": "Number", "required": true, "read_only": true }
+            },
+            "user": {
+                "extends": ["base_entity"],
+                "fields": { "name": { "type": "String", "required": true } }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_interfaces(&models),
+            "
+export interface BaseEntity {
+  id: number;
+}
+
+export interface BaseEntityCreateRequest {}
+
+export interface BaseEntityResponse {
+  id: number;
+}
+
+export interface User extends BaseEntity {
+  name: string;
+}
+
+export interface UserCreateRequest {
+  name: string;
+}
+
+export interface UserResponse {
+  id: number;
+  name: string;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_hoist_inline_objects() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "address": {
+                        "type": "Object",
+                        "required": true,
+                        "fields": {
+                            "city": { "type": "String", "required": true },
+                            "geo": {
+                                "type": "Object",
+                                "required": false,
+                                "fields": { "lat": { "type": "Number", "required": true } }
+                            }
+                        }
+                    },
+                    "phones": {
+                        "type": "Array",
+                        "required": true,
+                        "model": {
+                            "type": "Object",
+                            "required": true,
+                            "fields": { "number": { "type": "String", "required": true } }
+                        }
+                    }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            hoist_inline_objects: true,
+            ..Default::default()
+        };
+        k9::snapshot!(
+            generate_typescript(&api_docs, &config).unwrap(),
+            "
+export interface User {
+  address: UserAddress;
+  phones: Array<UserPhonesItem>;
+}
+
+export interface UserAddress {
+  city: string;
+  geo?: UserAddressGeo | undefined;
+}
+
+export interface UserAddressGeo {
+  lat: number;
+}
+
+export interface UserPhonesItem {
+  number: string;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_error_classes() {
+        let config = TypeScriptConfig {
+            error_classes: true,
+            ..Default::default()
+        };
+        k9::snapshot!(
+            TypeScriptRenderer::new(&config).render_error_classes(&BTreeMap::new()),
+            "
+export class ApiError extends Error {
+  constructor(message: string, options?: ErrorOptions) {
+    super(message, options);
+    this.name = new.target.name;
+  }
+}
+
+export class HttpError<
+  Status extends number = number,
+  Body = unknown,
+> extends ApiError {
+  constructor(
+    readonly status: Status,
+    readonly body: Body,
+  ) {
+    super(`Request failed with status ${status}`);
+  }
+}
+
+export class NetworkError extends ApiError {}
+
+export class ValidationError extends ApiError {
+  constructor(
+    message: string,
+    readonly issues: unknown,
+  ) {
+    super(message);
+  }
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_render_routes_null_prototype() {
+        let config = TypeScriptConfig {
+            null_prototype: true,
+            ..Default::default()
+        };
+        let routes = [(
+            "get_user".to_string(),
+            ApiDocsRoute {
+                accepts: "user_id".to_string(),
+                returns: "user".to_string(),
+                ..Default::default()
+            },
+        )]
+        .into();
+        k9::snapshot!(
+            TypeScriptRenderer::new(&config).render_routes(&routes),
+            r#"
+export type ApiRequest = (route: string, body: unknown) => Promise<unknown>;
+
+function withoutPrototypes(value: unknown): unknown {
+  if (Array.isArray(value)) {
+    return value.map(withoutPrototypes);
+  }
+  if (typeof value !== "object" || value === null) {
+    return value;
+  }
+  const copy = Object.create(null);
+  for (const [key, item] of Object.entries(value)) {
+    if (key !== "__proto__" && key !== "constructor" && key !== "prototype") {
+      copy[key] = withoutPrototypes(item);
+    }
+  }
+  return copy;
+}
+
+export function getUser(request: ApiRequest, body: UserId): Promise<User> {
+  return request("get_user", body).then(withoutPrototypes) as Promise<User>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_route_path_params() {
+        let route = serde_json::from_value(serde_json::json!({
+            "accepts": "empty",
+            "returns": "comment",
+            "path": "/posts/{post_id}/comments/{id}",
+            "params": {
+                "post_id": { "type": "Number", "required": true },
+                "id": { "type": "String", "required": true }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_routes(&[("get_comment".to_string(), route)].into()),
+            r#"
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: { path?: string },
+) => Promise<unknown>;
+
+export function getComment(
+  request: ApiRequest,
+  params: { id: string; post_id: number },
+  body: Empty,
+): Promise<Comment> {
+  return request("get_comment", body, {
+    path: `/posts/${encodeURIComponent(params.post_id)}/comments/${encodeURIComponent(params.id)}`,
+  }) as Promise<Comment>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_route_query() {
+        let route = serde_json::from_value(serde_json::json!({
+            "accepts": "empty",
+            "returns": "users",
+            "query": "user_filter"
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_routes(&[("list_users".to_string(), route)].into()),
+            r#"
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: { query?: string },
+) => Promise<unknown>;
+
+function serializeQuery(query: object): string {
+  const search = new URLSearchParams();
+  for (const [key, value] of Object.entries(query)) {
+    for (const item of Array.isArray(value) ? value : [value]) {
+      if (item !== undefined && item !== null) {
+        search.append(key, String(item));
+      }
+    }
+  }
+  const serialized = search.toString();
+  return serialized === "" ? "" : `?${serialized}`;
+}
+
+export function listUsers(
+  request: ApiRequest,
+  query: UserFilter,
+  body: Empty,
+): Promise<Users> {
+  return request("list_users", body, {
+    query: serializeQuery(query),
+  }) as Promise<Users>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_route_headers() {
+        let models = serde_json::from_value(serde_json::json!({
+            "tenant_headers": { "X-Tenant-Id": { "type": "String", "required": true } }
+        }))
+        .unwrap();
+        let route = serde_json::from_value(serde_json::json!({
+            "accepts": "empty",
+            "returns": "users",
+            "headers": "tenant_headers"
+        }))
+        .unwrap();
+        k9::snapshot!(
+            format!(
+                "{}\n{}",
+                render_interfaces(&models),
+                render_routes(&[("list_users".to_string(), route)].into())
+            ),
+            r#"
+export interface TenantHeaders {
+  "X-Tenant-Id": string;
+}
+
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: { headers?: Record<string, string> },
+) => Promise<unknown>;
+
+function serializeHeaders(headers: object): Record<string, string> {
+  const serialized: Record<string, string> = {};
+  for (const [name, value] of Object.entries(headers)) {
+    if (value !== undefined && value !== null) {
+      serialized[name] = String(value);
+    }
+  }
+  return serialized;
+}
+
+export function listUsers(
+  request: ApiRequest,
+  headers: TenantHeaders,
+  body: Empty,
+): Promise<Users> {
+  return request("list_users", body, {
+    headers: serializeHeaders(headers),
+  }) as Promise<Users>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_route_method() {
+        let routes = serde_json::from_value(serde_json::json!({
+            "get_user": { "accepts": "empty", "returns": "user", "method": "GET" },
+            "create_user": { "accepts": "new_user", "returns": "user", "method": "POST" }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_routes(&routes),
+            r#"
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: {
+    method?:
+      | "GET"
+      | "HEAD"
+      | "POST"
+      | "PUT"
+      | "PATCH"
+      | "DELETE"
+      | "OPTIONS"
+      | "TRACE";
+  },
+) => Promise<unknown>;
+
+export function createUser(request: ApiRequest, body: NewUser): Promise<User> {
+  return request("create_user", body, { method: "POST" }) as Promise<User>;
+}
+
+export function getUser(request: ApiRequest): Promise<User> {
+  return request("get_user", undefined, { method: "GET" }) as Promise<User>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_route_errors() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user_id": { "id": { "type": "Number", "required": true } },
+                "user": { "name": { "type": "String", "required": true } },
+                "not_found": { "message": { "type": "String", "required": true } }
+            },
+            "routes": {
+                "get_user": {
+                    "accepts": "user_id",
+                    "returns": "user",
+                    "errors": { "404": "not_found", "422": "not_found" }
+                }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &TypeScriptConfig::default()).unwrap(),
+            r#"
+export interface NotFound {
+  message: string;
+}
+
+export interface User {
+  name: string;
+}
+
+export interface UserId {
+  id: number;
+}
+
+export class ApiError extends Error {
+  constructor(message: string, options?: ErrorOptions) {
+    super(message, options);
+    this.name = new.target.name;
+  }
+}
+
+export class HttpError<
+  Status extends number = number,
+  Body = unknown,
+> extends ApiError {
+  constructor(
+    readonly status: Status,
+    readonly body: Body,
+  ) {
+    super(`Request failed with status ${status}`);
+  }
+}
+
+export class NetworkError extends ApiError {}
+
+export class ValidationError extends ApiError {
+  constructor(
+    message: string,
+    readonly issues: unknown,
+  ) {
+    super(message);
+  }
+}
+
+export type ApiRequest = (route: string, body: unknown) => Promise<unknown>;
+
+export type ApiResult<T, E> = { ok: true; body: T } | { ok: false; error: E };
+
+export function settle<T, E extends HttpError>(
+  promise: Promise<T>,
+): Promise<ApiResult<T, E>> {
+  return promise.then(
+    (body) => ({ ok: true, body }),
+    (error) => {
+      if (error instanceof HttpError) {
+        return { ok: false, error: error as E };
+      }
+      throw error;
+    },
+  );
+}
+
+export type GetUserError = HttpError<404, NotFound> | HttpError<422, NotFound>;
+
+export type GetUserResult = ApiResult<User, GetUserError>;
+
+export function getUser(request: ApiRequest, body: UserId): Promise<User> {
+  return request("get_user", body) as Promise<User>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_routes_namespaces() {
+        let routes = serde_json::from_value(serde_json::json!({
+            "create_user": { "accepts": "new_user", "returns": "user", "tags": ["users"] },
+            "get_user": { "accepts": "user_id", "returns": "user", "tags": ["users", "admin"] },
+            "get_users": { "accepts": "empty", "returns": "users", "tags": ["users"] },
+            "ban_user": { "accepts": "user_id", "returns": "empty", "tags": ["admin"] },
+            "health": { "accepts": "empty", "returns": "empty" }
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            namespaces: true,
+            ..Default::default()
+        };
+        k9::snapshot!(
+            TypeScriptRenderer::new(&config).render_routes(&routes),
+            r#"
+export type ApiRequest = (route: string, body: unknown) => Promise<unknown>;
+
+export function banUser(request: ApiRequest, body: UserId): Promise<Empty> {
+  return request("ban_user", body) as Promise<Empty>;
+}
+
+export function createUser(request: ApiRequest, body: NewUser): Promise<User> {
+  return request("create_user", body) as Promise<User>;
+}
+
+export function getUser(request: ApiRequest, body: UserId): Promise<User> {
+  return request("get_user", body) as Promise<User>;
+}
+
+export function getUsers(request: ApiRequest, body: Empty): Promise<Users> {
+  return request("get_users", body) as Promise<Users>;
+}
+
+export function health(request: ApiRequest, body: Empty): Promise<Empty> {
+  return request("health", body) as Promise<Empty>;
+}
+
+export const api = {
+  admin: {
+    banUser: banUser,
+    getUser: getUser,
+  },
+  users: {
+    create: createUser,
+    getUser: getUser,
+    getUsers: getUsers,
+  },
+};
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_multipart() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "new_post": {
+                    "title": { "type": "String", "required": true },
+                    "attachments": {
+                        "type": "Array",
+                        "required": true,
+                        "model": { "type": "File", "required": true }
+                    }
+                },
+                "post": { "id": { "type": "Number", "required": true } }
+            },
+            "routes": {
+                "create_post": {
+                    "accepts": "new_post",
+                    "returns": "post",
+                    "content_type": "multipart/form-data"
+                }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &TypeScriptConfig::default()).unwrap(),
+            r#"
+export interface NewPost {
+  attachments: Array<Blob>;
+  title: string;
+}
+
+export interface Post {
+  id: number;
+}
+
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: { contentType?: "application/json" | "multipart/form-data" },
+) => Promise<unknown>;
+
+function toFormData(body: object): FormData {
+  const form = new FormData();
+  for (const [key, value] of Object.entries(body)) {
+    for (const item of Array.isArray(value) ? value : [value]) {
+      if (item instanceof Blob) {
+        form.append(key, item);
+      } else if (typeof item === "object" && item !== null) {
+        form.append(key, JSON.stringify(item));
+      } else if (item !== undefined && item !== null) {
+        form.append(key, String(item));
+      }
+    }
+  }
+  return form;
+}
+
+export function createPost(request: ApiRequest, body: NewPost): Promise<Post> {
+  return request("create_post", toFormData(body), {
+    contentType: "multipart/form-data",
+  }) as Promise<Post>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_routes_optimize_size() {
+        let models = serde_json::from_value(serde_json::json!({
+            "user_query": {
+                "ids": {
+                    "type": "Array",
+                    "required": true,
+                    "model": { "type": "Number", "required": true }
+                }
+            },
+            "tenant_headers": { "X-Tenant-Id": { "type": "String", "required": true } }
+        }))
+        .unwrap();
+        let routes = serde_json::from_value(serde_json::json!({
+            "list_users": {
+                "accepts": "empty",
+                "returns": "users",
+                "query": "user_query",
+                "headers": "tenant_headers",
+                "tags": ["users"]
+            }
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            namespaces: true,
+            optimize_size: true,
+            ..Default::default()
+        };
+        let renderer = TypeScriptRenderer::new(&config);
+        k9::snapshot!(
+            format!(
+                "{}\n{}",
+                renderer.render_interfaces(&models),
+                renderer.render_routes(&routes)
+            ),
+            r#"
+export interface TenantHeaders {
+  "X-Tenant-Id": string;
+}
+
+export interface UserQuery {
+  ids: Array<number>;
+}
+
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: { query?: string; headers?: Record<string, string> },
+) => Promise<unknown>;
+
+function serializeQuery(query: object): string {
+  const search = new URLSearchParams();
+  for (const [key, value] of Object.entries(query)) {
+    for (const item of [value].flat()) {
+      if (item != null) {
+        search.append(key, String(item));
+      }
+    }
+  }
+  const serialized = search.toString();
+  return serialized === "" ? "" : `?${serialized}`;
+}
+
+function serializeHeaders(headers: object): Record<string, string> {
+  const serialized: Record<string, string> = {};
+  for (const [name, value] of Object.entries(headers)) {
+    if (value != null) {
+      serialized[name] = String(value);
+    }
+  }
+  return serialized;
+}
+
+export function listUsers(
+  request: ApiRequest,
+  query: UserQuery,
+  headers: TenantHeaders,
+  body: Empty,
+): Promise<Users> {
+  return request("list_users", body, {
+    query: serializeQuery(query),
+    headers: serializeHeaders(headers),
+  }) as Promise<Users>;
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_sockets() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "chat_line": { "text": { "type": "String", "required": true } },
+                "user_left": { "name": { "type": "String", "required": true } }
+            },
+            "routes": {},
+            "sockets": {
+                "chat": {
+                    "path": "/chat",
+                    "description": "Messages of a chat room.",
+                    "sends": { "say": "chat_line" },
+                    "receives": { "said": "chat_line", "left": "user_left" }
+                }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &TypeScriptConfig::default()).unwrap(),
+            r#"
+export interface ChatLine {
+  text: string;
+}
+
+export interface UserLeft {
+  name: string;
+}
+
+export type ApiConnect = (path: string) => WebSocket;
+
+export type SocketMessage = { type: string; data: unknown };
+
+export class TypedSocket<
+  Sends extends SocketMessage,
+  Receives extends SocketMessage,
+> {
+  constructor(readonly socket: WebSocket) {}
+
+  send<Type extends Sends["type"]>(
+    type: Type,
+    data: Extract<Sends, { type: Type }>["data"],
+  ): void {
+    this.socket.send(JSON.stringify({ type, data }));
+  }
+
+  onMessage<Type extends Receives["type"]>(
+    type: Type,
+    listener: (data: Extract<Receives, { type: Type }>["data"]) => void,
+  ): () => void {
+    const handle = (event: MessageEvent) => {
+      const message = JSON.parse(event.data) as Receives;
+      if (message.type === type) {
+        listener(message.data as Extract<Receives, { type: Type }>["data"]);
+      }
+    };
+    this.socket.addEventListener("message", handle);
+    return () => this.socket.removeEventListener("message", handle);
+  }
+}
+
+export type ChatSends = { type: "say"; data: ChatLine };
+
+export type ChatReceives =
+  | { type: "left"; data: UserLeft }
+  | { type: "said"; data: ChatLine };
+
+/** Messages of a chat room. */
+export function chat(
+  connect: ApiConnect,
+): TypedSocket<ChatSends, ChatReceives> {
+  return new TypedSocket(connect("/chat"));
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_routes_minimal_client() {
+        let routes = serde_json::from_value(serde_json::json!({
+            "get_user": {
+                "accepts": "empty",
+                "returns": "user",
+                "method": "GET",
+                "path": "/users/{id}",
+                "params": { "id": { "type": "Number", "required": true } },
+                "query": "user_query"
+            },
+            "create_user": { "accepts": "new_user", "returns": "user", "method": "POST" }
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            client: TypeScriptClient::Minimal,
+            ..Default::default()
+        };
+        k9::snapshot!(
+            TypeScriptRenderer::new(&config).render_routes(&routes),
+            r#"
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: {
+    method?:
+      | "GET"
+      | "HEAD"
+      | "POST"
+      | "PUT"
+      | "PATCH"
+      | "DELETE"
+      | "OPTIONS"
+      | "TRACE";
+    path?: string;
+    query?: string;
+  },
+) => Promise<unknown>;
+
+function serializeQuery(query: object): string {
+  const search = new URLSearchParams();
+  for (const [key, value] of Object.entries(query)) {
+    for (const item of Array.isArray(value) ? value : [value]) {
+      if (item !== undefined && item !== null) {
+        search.append(key, String(item));
+      }
+    }
+  }
+  const serialized = search.toString();
+  return serialized === "" ? "" : `?${serialized}`;
+}
+
+export interface Route<Input, Returns> {
+  name: string;
+  path?: string;
+  options?: Parameters<ApiRequest>[2];
+  /** Never set, only carries the types of the route. */
+  types?: (input: Input) => Returns;
+}
+
+export type RouteInput = {
+  params?: Record<string, unknown>;
+  query?: object;
+  headers?: object;
+  body?: unknown;
+};
+
+export function request<Input extends RouteInput, Returns>(
+  send: ApiRequest,
+  route: Route<Input, Returns>,
+  input: Input,
+): Promise<Returns> {
+  return send(route.name, input.body, {
+    ...route.options,
+    path: route.path?.replace(/\\{([^{}]*)\\}/g, (_, name: string) =>
+      encodeURIComponent(String(input.params?.[name])),
+    ),
+    query: input.query && serializeQuery(input.query),
+  }) as Promise<Returns>;
+}
+
+export const createUser: Route<{ body: NewUser }, User> = {
+  name: "create_user",
+  options: { method: "POST" },
+};
+
+export const getUser: Route<
+  { params: { id: number }; query: UserQuery },
+  User
+> = {
+  name: "get_user",
+  path: "/users/{id}",
+  options: { method: "GET" },
+};
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_pagination() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "page_query": {
+                    "next-page": { "type": "String", "required": false },
+                    "offset": { "type": "Number", "required": false },
+                    "tag": { "type": "String", "required": false }
+                },
+                "user": { "id": { "type": "Number", "required": true } },
+                "user_page": {
+                    "users": {
+                        "type": "Array",
+                        "required": true,
+                        "model": { "type": "Ref", "required": true, "ref": "user" }
+                    },
+                    "next_cursor": { "type": "String", "required": false, "nullable": true }
+                },
+                "empty": {}
+            },
+            "routes": {
+                "list_users": {
+                    "accepts": "empty",
+                    "returns": "user_page",
+                    "method": "GET",
+                    "query": "page_query",
+                    "pagination": {
+                        "style": "cursor",
+                        "cursor": "next-page",
+                        "next_cursor": "next_cursor"
+                    }
+                },
+                "search_users": {
+                    "accepts": "empty",
+                    "returns": "user_page",
+                    "method": "GET",
+                    "params": { "team": { "type": "String", "required": true } },
+                    "path": "/teams/{team}/users",
+                    "query": "page_query",
+                    "pagination": { "style": "offset", "offset": "offset", "items": "users" }
+                }
+            }
+        }))
+        .unwrap();
+        let output = generate_typescript(&api_docs, &TypeScriptConfig::default()).unwrap();
+        k9::snapshot!(
+            output
+                .split("\n\n")
+                .filter(|section| section.contains("Pages("))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            r#"
+export async function* listUsersPages(
+  request: ApiRequest,
+  query: PageQuery,
+): AsyncGenerator<UserPage, void> {
+  let nextPage = query["next-page"];
+  do {
+    const response = await listUsers(request, {
+      ...query,
+      "next-page": nextPage,
+    });
+    yield response;
+    nextPage = response.next_cursor ?? undefined;
+  } while (nextPage !== undefined);
+}
+
+export async function* searchUsersPages(
+  request: ApiRequest,
+  params: { team: string },
+  query: PageQuery,
+): AsyncGenerator<UserPage, void> {
+  let offset = query.offset ?? 0;
+  for (;;) {
+    const response = await searchUsers(request, params, { ...query, offset });
+    if (response.users.length === 0) {
+      return;
+    }
+    yield response;
+    offset = offset + response.users.length;
+  }
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_typescript_line_width() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "organization_membership": {
+                    "id": { "type": "Number", "required": true },
+                    "display_name": { "type": "String", "required": true },
+                    "organization_identifier": { "type": "String", "required": true },
+                    "role": {
+                        "type": "Enum",
+                        "required": true,
+                        "members": [
+                            "administrator",
+                            "billing_manager",
+                            "member",
+                            "read_only_guest"
+                        ],
+                        "default": "member"
+                    },
+                    "notifications": {
+                        "type": "Enum",
+                        "required": false,
+                        "members": ["email", "push_notification", "text_message", "none"]
+                    }
+                },
+                "membership_filter": {
+                    "cursor": { "type": "String", "required": false },
+                    "organization_identifier": { "type": "String", "required": false }
+                },
+                "membership_page": { "next_cursor": { "type": "String", "required": false } },
+                "membership_not_found": { "message": { "type": "String", "required": true } },
+                "membership_conflict": { "message": { "type": "String", "required": true } },
+                "tenant_headers": { "x-tenant-identifier": { "type": "String", "required": true } },
+                "avatar_upload": { "file": { "type": "String", "required": true } },
+                "empty": {}
+            },
+            "routes": {
+                "list_organization_memberships": {
+                    "accepts": "empty",
+                    "returns": "membership_page",
+                    "method": "GET",
+                    "path": "/{org}/teams",
+                    "params": {
+                        "org": { "type": "String", "required": true }
+                    },
+                    "query": "membership_filter",
+                    "headers": "tenant_headers",
+                    "errors": { "404": "membership_not_found", "409": "membership_conflict" },
+                    "pagination": {
+                        "style": "cursor",
+                        "cursor": "cursor",
+                        "next_cursor": "next_cursor"
+                    },
+                    "scopes": ["organizations:memberships:read", "organizations:memberships:admin"]
+                },
+                "upload_membership_avatar": {
+                    "accepts": "avatar_upload",
+                    "returns": "organization_membership",
+                    "method": "POST",
+                    "content_type": "multipart/form-data",
+                    "compression": "gzip",
+                    "scopes": ["organizations:memberships:write"]
+                }
+            },
+            "sockets": {
+                "organization_activity": {
+                    "path": "/activity",
+                    "sends": { "subscribe": "membership_filter" },
+                    "receives": {
+                        "membership_joined": "organization_membership",
+                        "membership_left": "organization_membership"
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        for (client, max_line_width, use_tabs) in [
+            (TypeScriptClient::Functions, None, false),
+            (TypeScriptClient::Minimal, None, false),
+            (TypeScriptClient::Functions, Some(100), true),
+        ] {
+            let config = TypeScriptConfig {
+                client,
+                max_line_width,
+                use_tabs,
+                error_classes: true,
+                ..Default::default()
+            };
+            let output = generate_typescript(&api_docs, &config).unwrap();
+            for line in output.lines() {
+                assert!(
+                    layout::width(&config, line) <= config.line_width(),
+                    "longer than {} columns: {line}",
+                    config.line_width()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_typescript_permissions() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": { "id": { "type": "Number", "required": true } }
+            },
+            "routes": {
+                "get_user": { "accepts": "user", "returns": "user", "scopes": ["users:read"] },
+                "delete_user": {
+                    "accepts": "user",
+                    "returns": "user",
+                    "scopes": ["users:read", "users:delete"]
+                },
+                "ping": { "accepts": "user", "returns": "user" }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_typescript(&api_docs, &TypeScriptConfig::default()).unwrap(),
+            r#"
+export interface User {
+  id: number;
+}
+
+export type ApiRequest = (route: string, body: unknown) => Promise<unknown>;
+
+export function deleteUser(request: ApiRequest, body: User): Promise<User> {
+  return request("delete_user", body) as Promise<User>;
+}
+
+export function getUser(request: ApiRequest, body: User): Promise<User> {
+  return request("get_user", body) as Promise<User>;
+}
+
+export function ping(request: ApiRequest, body: User): Promise<User> {
+  return request("ping", body) as Promise<User>;
+}
+
+export type Scope = "users:delete" | "users:read";
+
+const routePermissions = {
+  delete_user: ["users:read", "users:delete"],
+  get_user: ["users:read"],
+  ping: [],
+} as const;
+
+/** Scopes needed to call each route. */
+export type RoutePermissions = typeof routePermissions;
+
+/** Whether a user with `userScopes` can call `route`. */
+export function canCall(
+  route: keyof RoutePermissions,
+  userScopes: readonly string[],
+): boolean {
+  const scopes: readonly Scope[] = routePermissions[route];
+  return scopes.every((scope) => userScopes.includes(scope));
+}
+
+"#
+        );
+    }
+}
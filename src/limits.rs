@@ -0,0 +1,165 @@
+//! Limits on the size of specs, so that a huge spec committed by accident
+//! fails fast with a clear error instead of slowing down or exhausting CI.
+
+use crate::config::LimitsConfig;
+use crate::diagnostics::Diagnostic;
+use crate::{ApiDocs, ApiDocsModel, ApiDocsModelObject};
+
+/// Checks the size of a spec file before it's read.
+pub fn check_file_size(path: &str, size: u64, config: &LimitsConfig) -> eyre::Result<()> {
+    match config.max_file_size {
+        Some(max) if size > max => eyre::bail!(
+            "Spec `{path}` is {size} bytes, over the limit of {max} bytes (`limits.max_file_size`)"
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Checks the number of models, of fields of every object and of members of
+/// every enum.
+pub fn check(api_docs: &ApiDocs, config: &LimitsConfig) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        config,
+        diagnostics: Vec::new(),
+    };
+    checker.check_count(
+        "models",
+        api_docs.models.len(),
+        config.max_models,
+        "max_models",
+        "models",
+    );
+    for (name, model) in &api_docs.models {
+        checker.check_fields(name, &model.fields);
+    }
+    for (name, route) in &api_docs.routes {
+        checker.check_fields(&format!("routes.{name}.params"), &route.params);
+    }
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    config: &'a LimitsConfig,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn check_count(
+        &mut self, path: &str, count: usize, max: Option<usize>, option: &str, what: &str,
+    ) {
+        if let Some(max) = max.filter(|max| count > *max) {
+            self.diagnostics.push(Diagnostic::error(
+                path,
+                format!("has {count} {what}, over the limit of {max} (`limits.{option}`)"),
+            ));
+        }
+    }
+
+    fn check_fields(&mut self, path: &str, fields: &ApiDocsModelObject) {
+        self.check_count(
+            path,
+            fields.len(),
+            self.config.max_fields,
+            "max_fields",
+            "fields",
+        );
+        for (name, model) in fields {
+            self.check_model(&format!("{path}.{name}"), model);
+        }
+    }
+
+    fn check_model(&mut self, path: &str, model: &ApiDocsModel) {
+        if let Some(members) = &model.members {
+            self.check_count(
+                path,
+                members.len(),
+                self.config.max_enum_members,
+                "max_enum_members",
+                "members",
+            );
+        }
+        if let Some(fields) = &model.fields {
+            self.check_fields(path, fields);
+        }
+        if let Some(model) = &model.model {
+            self.check_model(&format!("{path}[]"), model);
+        }
+        if let Some(model) = &model.values {
+            self.check_model(&format!("{path}{{}}"), model);
+        }
+        for (index, model) in model.items.iter().flatten().enumerate() {
+            self.check_model(&format!("{path}[{index}]"), model);
+        }
+        for (segment, model) in model.alternatives() {
+            self.check_model(&format!("{path}|{segment}"), model);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "id": { "type": "Number", "required": true },
+                    "name": { "type": "String", "required": true },
+                    "role": {
+                        "type": "Enum",
+                        "required": true,
+                        "members": ["admin", "member", "guest"]
+                    },
+                    "address": {
+                        "type": "Object",
+                        "required": true,
+                        "fields": {
+                            "city": { "type": "String", "required": true },
+                            "street": { "type": "String", "required": true },
+                            "zip": { "type": "String", "required": true },
+                            "country": { "type": "String", "required": true }
+                        }
+                    }
+                },
+                "post": { "title": { "type": "String", "required": true } }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        let config = LimitsConfig {
+            max_models: Some(1),
+            max_fields: Some(3),
+            max_enum_members: Some(2),
+            ..Default::default()
+        };
+        k9::snapshot!(
+            check(&api_docs, &config)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "
+error: models: has 2 models, over the limit of 1 (`limits.max_models`)
+error: user: has 4 fields, over the limit of 3 (`limits.max_fields`)
+error: user.address: has 4 fields, over the limit of 3 (`limits.max_fields`)
+error: user.role: has 3 members, over the limit of 2 (`limits.max_enum_members`)
+"
+        );
+
+        let error = check_file_size(
+            "spec.json",
+            2048,
+            &LimitsConfig {
+                max_file_size: Some(1024),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        k9::snapshot!(
+            error.to_string(),
+            "Spec `spec.json` is 2048 bytes, over the limit of 1024 bytes (`limits.max_file_size`)"
+        );
+    }
+}
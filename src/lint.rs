@@ -0,0 +1,997 @@
+//! Spec lints that don't affect generation but point at likely mistakes or
+//! maintenance problems. Lints are reported as warnings, except for the
+//! opt-in gates that fail the run: description coverage below
+//! `min_coverage` and, with `fail_on_deprecated_use`, uses of deprecated
+//! models.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::config::TypeScriptConfig;
+use crate::config::{Case, DescriptionsConfig, LintConfig, NamingConfig, NamingRule};
+use crate::diagnostics::Diagnostic;
+use crate::{
+    ApiDocs, ApiDocsModel, ApiDocsModelObject, ApiDocsModelObjectType, Deprecation, Pagination,
+    TypeScriptRenderer,
+};
+
+/// Minimum share of fields two objects must have in common to be reported as
+/// near-duplicates.
+const NEAR_DUPLICATE_RATIO: f64 = 0.8;
+
+/// Objects with fewer fields than this are only reported if identical, since
+/// small objects overlap by coincidence all the time.
+const NEAR_DUPLICATE_MIN_FIELDS: usize = 3;
+
+/// Runs the lint passes in parallel. Lints are still reported pass by pass,
+/// in a fixed order.
+pub fn lint(api_docs: &ApiDocs, config: &LintConfig) -> Vec<Diagnostic> {
+    std::thread::scope(|scope| {
+        let passes = [
+            scope.spawn(|| duplicate_structures(api_docs)),
+            scope.spawn(|| naming(api_docs, &config.naming)),
+            scope.spawn(|| description_coverage(api_docs, &config.descriptions)),
+            scope.spawn(|| {
+                if config.fail_on_deprecated_use {
+                    deprecated_uses(api_docs)
+                } else {
+                    Vec::new()
+                }
+            }),
+        ];
+        passes
+            .into_iter()
+            .flat_map(|pass| pass.join().expect("lints don't panic"))
+            .collect()
+    })
+}
+
+/// Reports uses of deprecated models outside of deprecated models, fields
+/// and routes, which would keep them from ever being removed.
+fn deprecated_uses(api_docs: &ApiDocs) -> Vec<Diagnostic> {
+    let deprecated = api_docs
+        .models
+        .iter()
+        .filter(|(_, model)| Deprecation::is_deprecated(model.deprecated.as_ref()))
+        .map(|(name, _)| name.as_str())
+        .collect::<BTreeSet<_>>();
+
+    let mut lints = Vec::new();
+    for (name, model) in &api_docs.models {
+        if !deprecated.contains(name.as_str()) {
+            check_deprecated_fields(name, &model.fields, &deprecated, &mut lints);
+        }
+    }
+    for (name, route) in &api_docs.routes {
+        if Deprecation::is_deprecated(route.deprecated.as_ref()) {
+            continue;
+        }
+        let models = [&route.accepts, &route.returns]
+            .into_iter()
+            .chain(&route.query)
+            .chain(&route.headers)
+            .chain(route.errors.values());
+        for model in models {
+            if deprecated.contains(model.as_str()) {
+                lints.push(Diagnostic::error(
+                    format!("routes.{name}"),
+                    format!("uses deprecated model `{model}`"),
+                ));
+            }
+        }
+    }
+    for (name, socket) in &api_docs.sockets {
+        for model in socket.sends.values().chain(socket.receives.values()) {
+            if deprecated.contains(model.as_str()) {
+                lints.push(Diagnostic::error(
+                    format!("sockets.{name}"),
+                    format!("uses deprecated model `{model}`"),
+                ));
+            }
+        }
+    }
+    lints
+}
+
+fn check_deprecated_fields(
+    path: &str, fields: &ApiDocsModelObject, deprecated: &BTreeSet<&str>,
+    lints: &mut Vec<Diagnostic>,
+) {
+    for (name, model) in fields {
+        if !Deprecation::is_deprecated(model.deprecated.as_ref()) {
+            check_deprecated_refs(&format!("{path}.{name}"), model, deprecated, lints);
+        }
+    }
+}
+
+fn check_deprecated_refs(
+    path: &str, model: &ApiDocsModel, deprecated: &BTreeSet<&str>, lints: &mut Vec<Diagnostic>,
+) {
+    if let Some(name) = model
+        .r#ref
+        .as_deref()
+        .filter(|name| deprecated.contains(name))
+    {
+        lints.push(Diagnostic::error(
+            path,
+            format!("uses deprecated model `{name}`"),
+        ));
+    }
+    if let Some(fields) = &model.fields {
+        check_deprecated_fields(path, fields, deprecated, lints);
+    }
+    if let Some(model) = &model.model {
+        check_deprecated_refs(&format!("{path}[]"), model, deprecated, lints);
+    }
+    if let Some(model) = &model.values {
+        check_deprecated_refs(&format!("{path}{{}}"), model, deprecated, lints);
+    }
+    for (index, model) in model.items.iter().flatten().enumerate() {
+        check_deprecated_refs(&format!("{path}[{index}]"), model, deprecated, lints);
+    }
+    for (segment, model) in model.alternatives() {
+        check_deprecated_refs(&format!("{path}|{segment}"), model, deprecated, lints);
+    }
+}
+
+/// Reports undocumented models, fields and routes, and fails if too few of
+/// them are documented.
+fn description_coverage(api_docs: &ApiDocs, config: &DescriptionsConfig) -> Vec<Diagnostic> {
+    let mut items = Vec::new();
+    for (name, model) in &api_docs.models {
+        items.push((name.clone(), model.description.is_some()));
+        collect_field_descriptions(name, &model.fields, &mut items);
+    }
+    for (name, route) in &api_docs.routes {
+        items.push((format!("routes.{name}"), route.description.is_some()));
+    }
+
+    let mut lints = Vec::new();
+    if config.required {
+        for (path, _) in items.iter().filter(|(_, documented)| !documented) {
+            lints.push(Diagnostic::warning(path, "missing description"));
+        }
+    }
+
+    if let Some(min_coverage) = config.min_coverage {
+        let documented = items.iter().filter(|(_, documented)| *documented).count();
+        let coverage = if items.is_empty() {
+            100.0
+        } else {
+            documented as f64 * 100.0 / items.len() as f64
+        };
+        if coverage < min_coverage {
+            lints.push(Diagnostic::error(
+                "spec",
+                format!(
+                    "description coverage is {coverage:.1}% ({documented} of {}), below the \
+                     required {min_coverage}%",
+                    items.len()
+                ),
+            ));
+        }
+    }
+
+    lints
+}
+
+fn collect_field_descriptions(
+    path: &str, fields: &ApiDocsModelObject, items: &mut Vec<(String, bool)>,
+) {
+    for (name, model) in fields {
+        let path = format!("{path}.{name}");
+        items.push((path.clone(), model.description.is_some()));
+        collect_nested_field_descriptions(&path, model, items);
+    }
+}
+
+fn collect_nested_field_descriptions(
+    path: &str, model: &ApiDocsModel, items: &mut Vec<(String, bool)>,
+) {
+    if let Some(fields) = &model.fields {
+        collect_field_descriptions(path, fields, items);
+    }
+    if let Some(model) = &model.model {
+        collect_nested_field_descriptions(&format!("{path}[]"), model, items);
+    }
+    if let Some(model) = &model.values {
+        collect_nested_field_descriptions(&format!("{path}{{}}"), model, items);
+    }
+    for (index, model) in model.items.iter().flatten().enumerate() {
+        collect_nested_field_descriptions(&format!("{path}[{index}]"), model, items);
+    }
+    for (segment, model) in model.alternatives() {
+        collect_nested_field_descriptions(&format!("{path}|{segment}"), model, items);
+    }
+}
+
+/// Reports model keys and field names breaking the configured conventions.
+fn naming(api_docs: &ApiDocs, config: &NamingConfig) -> Vec<Diagnostic> {
+    let mut lints = Vec::new();
+    for (name, model) in &api_docs.models {
+        check_name(name, name, "model", &config.models, config, &mut lints);
+        check_field_names(name, &model.fields, config, &mut lints);
+    }
+    lints
+}
+
+fn check_field_names(
+    path: &str, fields: &ApiDocsModelObject, config: &NamingConfig, lints: &mut Vec<Diagnostic>,
+) {
+    for (name, model) in fields {
+        let path = format!("{path}.{name}");
+        check_name(&path, name, "field", &config.fields, config, lints);
+        check_nested_field_names(&path, model, config, lints);
+    }
+}
+
+fn check_nested_field_names(
+    path: &str, model: &ApiDocsModel, config: &NamingConfig, lints: &mut Vec<Diagnostic>,
+) {
+    match model.r#type {
+        ApiDocsModelObjectType::Object => {
+            if let Some(fields) = &model.fields {
+                check_field_names(path, fields, config, lints);
+            }
+        },
+        ApiDocsModelObjectType::Array => {
+            if let Some(model) = &model.model {
+                check_nested_field_names(&format!("{path}[]"), model, config, lints);
+            }
+        },
+        ApiDocsModelObjectType::Map => {
+            if let Some(model) = &model.values {
+                check_nested_field_names(&format!("{path}{{}}"), model, config, lints);
+            }
+        },
+        ApiDocsModelObjectType::Tuple => {
+            for (index, model) in model.items.iter().flatten().enumerate() {
+                check_nested_field_names(&format!("{path}[{index}]"), model, config, lints);
+            }
+        },
+        ApiDocsModelObjectType::Union => {
+            for (segment, model) in model.alternatives() {
+                check_nested_field_names(&format!("{path}|{segment}"), model, config, lints);
+            }
+        },
+        _ => {},
+    }
+}
+
+fn check_name(
+    path: &str, name: &str, kind: &str, rule: &NamingRule, config: &NamingConfig,
+    lints: &mut Vec<Diagnostic>,
+) {
+    let mut report = |message: String| lints.push(Diagnostic::warning(path, message));
+
+    if let Some(case) = rule.case {
+        if case.convert(name) != name {
+            report(format!("{kind} name `{name}` is not {case}"));
+        }
+    }
+    if let Some(pattern) = &rule.pattern {
+        if !pattern.is_match(name) {
+            report(format!("{kind} name `{name}` doesn't match `{pattern}`"));
+        }
+    }
+    for prefix in config
+        .banned_prefixes
+        .iter()
+        .filter(|p| name.starts_with(p.as_str()))
+    {
+        report(format!("{kind} name `{name}` has banned prefix `{prefix}`"));
+    }
+    for suffix in config
+        .banned_suffixes
+        .iter()
+        .filter(|s| name.ends_with(s.as_str()))
+    {
+        report(format!("{kind} name `{name}` has banned suffix `{suffix}`"));
+    }
+}
+
+/// Renames models and fields to the configured cases, updating the routes
+/// and refs that refer to renamed models.
+///
+/// Only case rules are fixed since they can be applied mechanically. Names
+/// whose fixed form would collide with another name are left untouched and
+/// returned as lints.
+///
+/// Field names are keys of payloads on the wire, so fields are only renamed
+/// with `rename_fields` and are otherwise returned as lints. Fields of the
+/// `headers` and `query` models of routes, and of the models they inherit
+/// from, are never renamed, like path parameters.
+pub fn fix_naming(
+    api_docs: &mut ApiDocs, config: &NamingConfig, rename_fields: bool,
+) -> Vec<Diagnostic> {
+    let mut unfixed = Vec::new();
+
+    if let Some(case) = config.models.case {
+        let renames = rename_keys(&mut api_docs.models, case, None, &mut unfixed);
+        rename_model_uses(api_docs, &renames);
+    }
+
+    if let Some(case) = config.fields.case.filter(|_| !rename_fields) {
+        let rule = NamingConfig {
+            fields: NamingRule {
+                case: Some(case),
+                pattern: None,
+            },
+            ..Default::default()
+        };
+        for (name, model) in &api_docs.models {
+            let mut lints = Vec::new();
+            check_field_names(name, &model.fields, &rule, &mut lints);
+            unfixed.extend(lints.into_iter().map(|lint| {
+                Diagnostic::warning(
+                    lint.path,
+                    format!(
+                        "{}; renaming fields changes payloads on the wire, pass \
+                         `--rename-fields` to rename it anyway",
+                        lint.message
+                    ),
+                )
+            }));
+        }
+    } else if let Some(case) = config.fields.case {
+        let wire = wire_models(api_docs);
+        let mut renames = BTreeMap::new();
+        for (name, model) in &mut api_docs.models {
+            if wire.contains(name) {
+                continue;
+            }
+            renames.insert(
+                name.clone(),
+                fix_field_names(name, &mut model.fields, case, &mut unfixed),
+            );
+        }
+        for route in api_docs.routes.values_mut() {
+            let query = route.query.as_ref().and_then(|query| renames.get(query));
+            let returns = renames.get(&route.returns);
+            let fields = match &mut route.pagination {
+                Some(Pagination::Cursor {
+                    cursor,
+                    next_cursor,
+                }) => [(cursor, query), (next_cursor, returns)],
+                Some(Pagination::Offset { offset, items }) => [(offset, query), (items, returns)],
+                None => continue,
+            };
+            for (field, renames) in fields {
+                if let Some(renamed) = renames.and_then(|renames| renames.get(field)) {
+                    *field = renamed.clone();
+                }
+            }
+        }
+    }
+
+    unfixed
+}
+
+/// Returns the models whose field names are sent as header or query
+/// parameter names.
+fn wire_models(api_docs: &ApiDocs) -> BTreeSet<String> {
+    api_docs
+        .routes
+        .values()
+        .flat_map(|route| route.headers.iter().chain(&route.query))
+        .filter(|name| api_docs.models.contains_key(*name))
+        .flat_map(|name| {
+            std::iter::once(name.as_str()).chain(crate::validate::ancestors(api_docs, name))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Replaces every use of the models renamed in `renames`, whose keys in
+/// `models` are already renamed.
+pub(crate) fn rename_model_uses(api_docs: &mut ApiDocs, renames: &BTreeMap<String, String>) {
+    for route in api_docs.routes.values_mut() {
+        for model in [&mut route.accepts, &mut route.returns]
+            .into_iter()
+            .chain(&mut route.query)
+            .chain(&mut route.headers)
+            .chain(route.errors.values_mut())
+        {
+            if let Some(renamed) = renames.get(model) {
+                *model = renamed.clone();
+            }
+        }
+    }
+    for socket in api_docs.sockets.values_mut() {
+        for model in socket
+            .sends
+            .values_mut()
+            .chain(socket.receives.values_mut())
+        {
+            if let Some(renamed) = renames.get(model) {
+                *model = renamed.clone();
+            }
+        }
+    }
+    for model in api_docs.models.values_mut() {
+        for parent in &mut model.extends {
+            if let Some(renamed) = renames.get(parent) {
+                *parent = renamed.clone();
+            }
+        }
+        for field in model.fields.values_mut() {
+            rename_refs(field, renames);
+        }
+    }
+}
+
+/// Renames `fields` and the fields nested in them, returning the renames of
+/// `fields` themselves.
+fn fix_field_names(
+    path: &str, fields: &mut ApiDocsModelObject, case: Case, unfixed: &mut Vec<Diagnostic>,
+) -> BTreeMap<String, String> {
+    let renames = rename_keys(fields, case, Some(path), unfixed);
+    for (name, model) in fields.iter_mut() {
+        fix_nested_field_names(&format!("{path}.{name}"), model, case, unfixed);
+    }
+    renames
+}
+
+fn fix_nested_field_names(
+    path: &str, model: &mut ApiDocsModel, case: Case, unfixed: &mut Vec<Diagnostic>,
+) {
+    if let Some(fields) = &mut model.fields {
+        fix_field_names(path, fields, case, unfixed);
+    }
+    if let Some(model) = &mut model.model {
+        fix_nested_field_names(&format!("{path}[]"), model, case, unfixed);
+    }
+    if let Some(model) = &mut model.values {
+        fix_nested_field_names(&format!("{path}{{}}"), model, case, unfixed);
+    }
+    for (index, model) in model.items.iter_mut().flatten().enumerate() {
+        fix_nested_field_names(&format!("{path}[{index}]"), model, case, unfixed);
+    }
+    for (segment, model) in model.alternatives_mut() {
+        fix_nested_field_names(&format!("{path}|{segment}"), model, case, unfixed);
+    }
+}
+
+fn rename_refs(model: &mut ApiDocsModel, renames: &BTreeMap<String, String>) {
+    if let Some(renamed) = model.r#ref.as_ref().and_then(|name| renames.get(name)) {
+        model.r#ref = Some(renamed.clone());
+    }
+    for field in model
+        .fields
+        .iter_mut()
+        .flat_map(|fields| fields.values_mut())
+    {
+        rename_refs(field, renames);
+    }
+    if let Some(item) = &mut model.model {
+        rename_refs(item, renames);
+    }
+    if let Some(value) = &mut model.values {
+        rename_refs(value, renames);
+    }
+    for item in model.items.iter_mut().flatten() {
+        rename_refs(item, renames);
+    }
+    for (_, alternative) in model.alternatives_mut() {
+        rename_refs(alternative, renames);
+    }
+}
+
+/// Renames the keys of `map` to `case`, returning the applied renames.
+fn rename_keys<V>(
+    map: &mut BTreeMap<String, V>, case: Case, parent: Option<&str>, unfixed: &mut Vec<Diagnostic>,
+) -> BTreeMap<String, String> {
+    let mut targets = BTreeMap::<String, usize>::new();
+    for name in map.keys() {
+        *targets.entry(case.convert(name)).or_default() += 1;
+    }
+
+    let mut renames = BTreeMap::new();
+    for (name, value) in std::mem::take(map) {
+        let target = case.convert(&name);
+        let name = if target == name {
+            name
+        } else if targets[&target] > 1 {
+            unfixed.push(Diagnostic::warning(
+                parent.map_or_else(|| name.clone(), |parent| format!("{parent}.{name}")),
+                format!("can't rename `{name}` to `{target}`; the name is taken"),
+            ));
+            name
+        } else {
+            renames.insert(name, target.clone());
+            target
+        };
+        map.insert(name, value);
+    }
+    renames
+}
+
+/// Reports models and inline objects whose fields are identical or nearly
+/// identical, which usually means a spec fragment was copy-pasted.
+fn duplicate_structures(api_docs: &ApiDocs) -> Vec<Diagnostic> {
+    let mut objects = Vec::new();
+    for (name, model) in &api_docs.models {
+        collect_objects(name, &model.fields, &mut objects);
+    }
+
+    let mut lints = Vec::new();
+    for (i, (path, fields)) in objects.iter().enumerate() {
+        for (other_path, other_fields) in &objects[i + 1..] {
+            let shared = fields.intersection(other_fields).count();
+            let total = fields.union(other_fields).count();
+            if shared == total {
+                lints.push(Diagnostic::warning(
+                    path.clone(),
+                    format!("has the same fields as `{other_path}`; consider using a shared model"),
+                ));
+            } else if fields.len().min(other_fields.len()) >= NEAR_DUPLICATE_MIN_FIELDS
+                && shared as f64 / total as f64 >= NEAR_DUPLICATE_RATIO
+            {
+                lints.push(Diagnostic::warning(
+                    path.clone(),
+                    format!(
+                        "shares {shared} of {total} fields with `{other_path}`; consider \
+                         extracting a shared model"
+                    ),
+                ));
+            }
+        }
+    }
+    lints
+}
+
+/// Collects every object reachable from `fields` as the set of its rendered
+/// fields, which compares equal for structurally identical fields.
+fn collect_objects(
+    path: &str, fields: &ApiDocsModelObject, objects: &mut Vec<(String, BTreeSet<String>)>,
+) {
+    if fields.is_empty() {
+        return;
+    }
+
+    let config = TypeScriptConfig::default();
+    let renderer = TypeScriptRenderer::without_docs(&config);
+    objects.push((
+        path.to_string(),
+        fields
+            .iter()
+            .map(|(name, model)| renderer.render_field(name, model, 0))
+            .collect(),
+    ));
+    for (name, model) in fields {
+        collect_nested_objects(&format!("{path}.{name}"), model, objects);
+    }
+}
+
+fn collect_nested_objects(
+    path: &str, model: &ApiDocsModel, objects: &mut Vec<(String, BTreeSet<String>)>,
+) {
+    match model.r#type {
+        ApiDocsModelObjectType::Object => {
+            if let Some(fields) = &model.fields {
+                collect_objects(path, fields, objects);
+            }
+        },
+        ApiDocsModelObjectType::Array => {
+            if let Some(model) = &model.model {
+                collect_nested_objects(&format!("{path}[]"), model, objects);
+            }
+        },
+        ApiDocsModelObjectType::Map => {
+            if let Some(model) = &model.values {
+                collect_nested_objects(&format!("{path}{{}}"), model, objects);
+            }
+        },
+        ApiDocsModelObjectType::Tuple => {
+            for (index, model) in model.items.iter().flatten().enumerate() {
+                collect_nested_objects(&format!("{path}[{index}]"), model, objects);
+            }
+        },
+        ApiDocsModelObjectType::Union => {
+            for (segment, model) in model.alternatives() {
+                collect_nested_objects(&format!("{path}|{segment}"), model, objects);
+            }
+        },
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_docs(models: serde_json::Value) -> ApiDocs {
+        serde_json::from_value(serde_json::json!({ "models": models, "routes": {} })).unwrap()
+    }
+
+    fn lint_config(config: serde_json::Value) -> LintConfig {
+        serde_json::from_value(config).unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_structures_identical_inline_object() {
+        let lints = lint(
+            &api_docs(serde_json::json!({
+                "address": {
+                    "city": { "type": "String", "required": true },
+                    "street": { "type": "String", "required": true }
+                },
+                "user": {
+                    "home": {
+                        "type": "Object",
+                        "required": true,
+                        "fields": {
+                            "city": { "type": "String", "required": true },
+                            "street": { "type": "String", "required": true }
+                        }
+                    }
+                }
+            })),
+            &LintConfig::default(),
+        );
+        k9::snapshot!(
+            lints
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "warning: address: has the same fields as `user.home`; consider using a shared model"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_structures_near_duplicate() {
+        let lints = lint(
+            &api_docs(serde_json::json!({
+                "a": {
+                    "id": { "type": "Number", "required": true },
+                    "created_at": { "type": "String", "required": true },
+                    "updated_at": { "type": "String", "required": true },
+                    "name": { "type": "String", "required": true },
+                    "owner": { "type": "String", "required": true }
+                },
+                "b": {
+                    "id": { "type": "Number", "required": true },
+                    "created_at": { "type": "String", "required": true },
+                    "updated_at": { "type": "String", "required": true },
+                    "name": { "type": "String", "required": true }
+                },
+                "c": {
+                    "id": { "type": "Number", "required": true },
+                    "name": { "type": "Boolean", "required": true }
+                }
+            })),
+            &LintConfig::default(),
+        );
+        k9::snapshot!(
+            lints
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "warning: a: shares 4 of 5 fields with `b`; consider extracting a shared model"
+        );
+    }
+
+    #[test]
+    fn test_naming() {
+        let lints = lint(
+            &api_docs(serde_json::json!({
+                "UserProfile": {
+                    "display_name": { "type": "String", "required": true },
+                    "tmpFlag": { "type": "Boolean", "required": true }
+                }
+            })),
+            &lint_config(serde_json::json!({
+                "naming": {
+                    "models": { "case": "snake_case" },
+                    "fields": { "pattern": "^[a-z][a-zA-Z0-9]*$" },
+                    "banned_prefixes": ["tmp"]
+                }
+            })),
+        );
+        k9::snapshot!(
+            lints
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            r#"
+warning: UserProfile: model name `UserProfile` is not snake_case
+warning: UserProfile.display_name: field name `display_name` doesn't match `^[a-z][a-zA-Z0-9]*$`
+warning: UserProfile.tmpFlag: field name `tmpFlag` has banned prefix `tmp`
+"#
+        );
+    }
+
+    #[test]
+    fn test_lint_many_models_ordered() {
+        let models = (0..100)
+            .map(|index| {
+                let field = serde_json::json!({ "type": "Number", "required": true });
+                (
+                    format!("Model{index}"),
+                    serde_json::json!({ format!("f{index}"): field }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let api_docs = api_docs(models.into());
+        let config = lint_config(serde_json::json!({
+            "naming": { "models": { "case": "snake_case" } },
+            "descriptions": { "required": true }
+        }));
+
+        let render =
+            |lints: Vec<Diagnostic>| lints.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let lints = render(lint(&api_docs, &config));
+        // Passes are reported one after the other, each in the order of the
+        // models.
+        let naming = lints
+            .iter()
+            .take_while(|lint| lint.contains("is not snake_case"))
+            .count();
+        assert_eq!(naming, 100);
+        assert!(lints[naming..]
+            .iter()
+            .all(|lint| lint.ends_with("missing description")));
+        assert!(lints[..naming].is_sorted_by_key(|lint| lint.split(':').nth(1).map(str::to_string)));
+        for _ in 0..3 {
+            assert_eq!(render(lint(&api_docs, &config)), lints);
+        }
+    }
+
+    #[test]
+    fn test_fix_naming() {
+        let mut api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "UserProfile": {
+                    "display_name": { "type": "String", "required": true },
+                    "displayName": { "type": "String", "required": true },
+                    "home_address": {
+                        "type": "Object",
+                        "required": true,
+                        "fields": { "zip_code": { "type": "String", "required": true } }
+                    }
+                },
+                "Team": {
+                    "members": {
+                        "type": "Array",
+                        "required": true,
+                        "model": { "type": "Ref", "required": true, "ref": "UserProfile" }
+                    }
+                }
+            },
+            "routes": {
+                "get_profile": { "accepts": "UserProfile", "returns": "UserProfile" }
+            }
+        }))
+        .unwrap();
+        let config = lint_config(serde_json::json!({
+            "naming": {
+                "models": { "case": "snake_case" },
+                "fields": { "case": "camelCase" }
+            }
+        }));
+
+        let unfixed = fix_naming(&mut api_docs, &config.naming, true);
+        k9::snapshot!(
+            unfixed
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            r#"warning: user_profile.display_name: can't rename `display_name` to `displayName`; the name is taken"#
+        );
+        k9::snapshot!(
+            crate::tests::render_interfaces(&api_docs.models),
+            "
+export interface Team {
+  members: Array<UserProfile>;
+}
+
+export interface UserProfile {
+  displayName: string;
+  display_name: string;
+  homeAddress: {
+    zipCode: string;
+  };
+}
+
+"
+        );
+        assert_eq!(api_docs.routes["get_profile"].accepts, "user_profile");
+        assert!(crate::validate::validate(&api_docs).is_empty());
+    }
+
+    #[test]
+    fn test_fix_naming_reports_fields() {
+        let mut api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "UserProfile": {
+                    "display_name": { "type": "String", "required": true },
+                    "home_address": {
+                        "type": "Object",
+                        "required": true,
+                        "fields": { "zip_code": { "type": "String", "required": true } }
+                    }
+                }
+            },
+            "routes": {
+                "get_profile": { "accepts": "UserProfile", "returns": "UserProfile" }
+            }
+        }))
+        .unwrap();
+        let config = lint_config(serde_json::json!({
+            "naming": {
+                "models": { "case": "snake_case" },
+                "fields": { "case": "camelCase" }
+            }
+        }));
+
+        let unfixed = fix_naming(&mut api_docs, &config.naming, false);
+        k9::snapshot!(
+            unfixed
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "
+warning: user_profile.display_name: field name `display_name` is not camelCase; renaming fields changes payloads on the wire, pass `--rename-fields` to rename it anyway
+warning: user_profile.home_address: field name `home_address` is not camelCase; renaming fields changes payloads on the wire, pass `--rename-fields` to rename it anyway
+warning: user_profile.home_address.zip_code: field name `zip_code` is not camelCase; renaming fields changes payloads on the wire, pass `--rename-fields` to rename it anyway
+"
+        );
+        k9::snapshot!(
+            crate::tests::render_interfaces(&api_docs.models),
+            "
+export interface UserProfile {
+  display_name: string;
+  home_address: {
+    zip_code: string;
+  };
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_fix_naming_keeps_wire_names() {
+        let mut api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "tracing": { "X-Request-Id": { "type": "String", "required": false } },
+                "tenant": {
+                    "extends": ["tracing"],
+                    "fields": { "X-Tenant-Id": { "type": "String", "required": true } }
+                },
+                "user_filter": { "created_after": { "type": "String", "required": false } },
+                "user": { "display_name": { "type": "String", "required": true } }
+            },
+            "routes": {
+                "get_user": {
+                    "accepts": "user",
+                    "returns": "user",
+                    "method": "POST",
+                    "path": "/users/{user_id}",
+                    "params": { "user_id": { "type": "Number", "required": true } },
+                    "query": "user_filter",
+                    "headers": "tenant"
+                }
+            }
+        }))
+        .unwrap();
+        let config = lint_config(serde_json::json!({
+            "naming": { "fields": { "case": "camelCase" } }
+        }));
+
+        assert!(fix_naming(&mut api_docs, &config.naming, true).is_empty());
+        k9::snapshot!(
+            crate::tests::render_interfaces(&api_docs.models),
+            r#"
+export interface Tenant extends Tracing {
+  "X-Tenant-Id": string;
+}
+
+export interface Tracing {
+  "X-Request-Id"?: string | undefined;
+}
+
+export interface User {
+  displayName: string;
+}
+
+export interface UserFilter {
+  created_after?: string | undefined;
+}
+
+"#
+        );
+        assert!(api_docs.routes["get_user"].params.contains_key("user_id"));
+    }
+
+    #[test]
+    fn test_description_coverage() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "description": "A registered user.",
+                    "fields": {
+                        "id": { "type": "Number", "required": true, "description": "Unique id." },
+                        "tags": {
+                            "type": "Array",
+                            "required": true,
+                            "model": {
+                                "type": "Object",
+                                "required": true,
+                                "fields": { "label": { "type": "String", "required": true } }
+                            }
+                        }
+                    }
+                }
+            },
+            "routes": {
+                "get_user": { "accepts": "user", "returns": "user" }
+            }
+        }))
+        .unwrap();
+        let lints = lint(
+            &api_docs,
+            &lint_config(serde_json::json!({
+                "descriptions": { "required": true, "min_coverage": 50 }
+            })),
+        );
+        k9::snapshot!(
+            lints
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "
+warning: user.tags: missing description
+warning: user.tags[].label: missing description
+warning: routes.get_user: missing description
+error: spec: description coverage is 40.0% (2 of 5), below the required 50%
+"
+        );
+    }
+
+    #[test]
+    fn test_deprecated_uses() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "legacy_user": {
+                    "deprecated": "Use `user` instead.",
+                    "fields": { "id": { "type": "Number", "required": true } }
+                },
+                "post": {
+                    "author": { "type": "Ref", "required": true, "ref": "legacy_user" },
+                    "editors": {
+                        "type": "Array",
+                        "required": true,
+                        "deprecated": true,
+                        "model": { "type": "Ref", "required": true, "ref": "legacy_user" }
+                    }
+                }
+            },
+            "routes": {
+                "get_user": { "accepts": "post", "returns": "legacy_user" },
+                "get_legacy_user": { "accepts": "post", "returns": "legacy_user", "deprecated": true }
+            }
+        }))
+        .unwrap();
+        let lints = lint(
+            &api_docs,
+            &lint_config(serde_json::json!({ "fail_on_deprecated_use": true })),
+        );
+        k9::snapshot!(
+            lints
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "
+error: post.author: uses deprecated model `legacy_user`
+error: routes.get_user: uses deprecated model `legacy_user`
+"
+        );
+    }
+}
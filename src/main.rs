@@ -1,433 +1,650 @@
-#![allow(unused)]
-
 use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Write;
-
+use std::io::{BufRead, Write};
+
+use api_generator::capabilities;
+use api_generator::config::{Config, LimitsConfig, TypeScriptClient};
+use api_generator::diagnostics::{Diagnostics, Severity};
+use api_generator::infer::infer_model;
+use api_generator::limits;
+use api_generator::snippets::Snippets;
+use api_generator::timings::Timings;
+use api_generator::{
+    generate_targets, lint, merge, openapi, overrides, report, sarif, smithy, validate, ApiDocs,
+    ApiDocsModel, ApiDocsModelObjectType, ApiDocsRoute, Target,
+};
 use eyre::{Context, Result};
-use indoc::indoc;
-use serde::{Deserialize, Serialize};
-use string_template::Template;
-
-#[derive(Copy, Clone, PartialEq, Eq, Deserialize)]
-enum ApiDocsModelObjectType {
-    String,
-    Number,
-    Boolean,
-    Object,
-    Array,
-    Enum,
+
+struct Args {
+    file: Option<String>,
+    /// Specs of services to merge into one client instead of `file`, as
+    /// `name=path`.
+    services: Vec<(String, String)>,
+    /// One output file per target, in the same order.
+    out: Vec<String>,
+    input_format: InputFormat,
+    targets: Vec<Target>,
+    config: Option<String>,
+    max_line_width: Option<usize>,
+    hoist_inline_objects: bool,
+    optimize_size: bool,
+    client: Option<TypeScriptClient>,
+    /// Fail instead of generating output that is less precise than the spec.
+    strict: bool,
+    /// Print how long each phase took.
+    timings: bool,
+    /// File to write the phases to as a Chrome trace.
+    trace_out: Option<String>,
 }
 
-type ApiDocsModelObject = BTreeMap<String, ApiDocsModel>;
-type ApiDocsModelsObject = BTreeMap<String, ApiDocsModel>;
-
-#[derive(Deserialize)]
-struct ApiDocsModel {
-    r#type: ApiDocsModelObjectType,
-    /// Model if `type` is `object`
-    fields: Option<ApiDocsModelObject>,
-    /// Model if `type` is `array`
-    model: Option<Box<ApiDocsModel>>,
-    /// Model if `type` is `enum`
-    members: Option<Vec<serde_json::Value>>,
-    required: bool,
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum InputFormat {
+    ApiDocs,
+    OpenApi,
+    /// Smithy JSON AST
+    Smithy,
 }
 
-#[derive(Deserialize)]
-struct ApiDocsRoute {
-    accepts: String,
-    returns: String,
+impl std::str::FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "api-docs" => Ok(Self::ApiDocs),
+            "openapi" => Ok(Self::OpenApi),
+            "smithy" => Ok(Self::Smithy),
+            _ => Err(format!(
+                "unknown input format `{s}`, expected `api-docs`, `openapi` or `smithy`"
+            )),
+        }
+    }
 }
 
-#[derive(Deserialize)]
-struct ApiDocs {
-    models: BTreeMap<String, ApiDocsModelsObject>,
-    routes: BTreeMap<String, ApiDocsRoute>,
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum LintFormat {
+    Text,
+    Sarif,
 }
 
-struct Args {
+impl std::str::FromStr for LintFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "sarif" => Ok(Self::Sarif),
+            _ => Err(format!(
+                "unknown lint format `{s}`, expected `text` or `sarif`"
+            )),
+        }
+    }
+}
+
+struct LintArgs {
+    file: String,
+    input_format: InputFormat,
+    lint_format: LintFormat,
+    config: Option<String>,
+    min_doc_coverage: Option<f64>,
+    fail_on_deprecated_use: bool,
+}
+
+struct ApiReportArgs {
     file: String,
+    input_format: InputFormat,
+    config: Option<String>,
+    /// Printed to stdout when not set.
+    out: Option<String>,
+}
+
+struct FmtArgs {
+    file: String,
+    config: Option<String>,
+    /// Also rename fields, changing the keys of payloads on the wire.
+    rename_fields: bool,
+}
+
+struct NewArgs {
     out: String,
+    /// Config file written along the spec, with every option at its default.
+    config_out: String,
+}
+
+struct FromJsonArgs {
+    name: String,
+    samples: Vec<String>,
+}
+
+fn from_json(mut args: pico_args::Arguments) -> Result<()> {
+    let name: String = args.value_from_str("--name")?;
+    let mut samples = Vec::new();
+    while let Some(sample) = args.opt_free_from_str::<String>()? {
+        samples.push(sample);
+    }
+    let args = FromJsonArgs { name, samples };
+
+    if args.samples.is_empty() {
+        eyre::bail!("`from-json` needs at least one sample file");
+    }
+
+    let values = args
+        .samples
+        .iter()
+        .map(|sample| {
+            let file = File::open(sample).wrap_err_with(|| format!("Failed to open: {sample}"))?;
+            serde_json::from_reader(file).wrap_err_with(|| format!("Failed to parse: {sample}"))
+        })
+        .collect::<Result<Vec<serde_json::Value>>>()?;
+
+    let model = infer_model(&args.name, &values.iter().collect::<Vec<_>>(), true)?;
+    let fields = model
+        .fields
+        .ok_or_else(|| eyre::eyre!("Samples for `{}` must be JSON objects", args.name))?;
+
+    let models: BTreeMap<_, _> = [(args.name, fields)].into();
+    println!("{}", serde_json::to_string_pretty(&models)?);
+
+    Ok(())
+}
+
+/// Asks `question` and returns the trimmed answer, or `None` at end of input.
+fn prompt(
+    input: &mut impl BufRead, output: &mut impl Write, question: &str,
+) -> Result<Option<String>> {
+    write!(output, "{question}: ")?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    if input.read_line(&mut answer)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(answer.trim().to_string()))
 }
 
-fn interface_field_template(name: &str, r#type: &str) -> String {
-    Template::new(indoc! {"
-        {{name}}: {{type}},
-    "})
-    .render(&[("name", name), ("type", r#type)].into())
+fn prompt_field_type(
+    input: &mut impl BufRead, output: &mut impl Write,
+) -> Result<ApiDocsModelObjectType> {
+    loop {
+        let answer = prompt(input, output, "  Type (string, number, boolean)")?
+            .ok_or_else(|| eyre::eyre!("Unexpected end of input"))?;
+        match answer.to_lowercase().as_str() {
+            "string" => return Ok(ApiDocsModelObjectType::String),
+            "number" => return Ok(ApiDocsModelObjectType::Number),
+            "boolean" => return Ok(ApiDocsModelObjectType::Boolean),
+            _ => writeln!(output, "  Unknown type: {answer}")?,
+        }
+    }
 }
 
-fn interface_field_object_template(content: &str) -> String {
-    Template::new(indoc! {"
+/// Interactively builds a starter spec. An empty answer to a name prompt
+/// moves on to the next section.
+fn scaffold_spec(input: &mut impl BufRead, output: &mut impl Write) -> Result<ApiDocs> {
+    let mut models = BTreeMap::new();
+    writeln!(output, "Models (leave the name empty to continue)")?;
+    while let Some(model_name) = prompt(input, output, "Model name")?.filter(|a| !a.is_empty()) {
+        let mut fields = BTreeMap::new();
+        while let Some(field_name) =
+            prompt(input, output, "  Field name")?.filter(|a| !a.is_empty())
         {
-            {{content}}
-        },
-    "})
-    .render(&[("content", content)].into())
+            let r#type = prompt_field_type(input, output)?;
+            let required = prompt(input, output, "  Required? [Y/n]")?
+                .is_none_or(|a| !a.eq_ignore_ascii_case("n"));
+            fields.insert(
+                field_name,
+                ApiDocsModel {
+                    r#type,
+                    required,
+                    ..Default::default()
+                },
+            );
+        }
+        models.insert(model_name, fields.into());
+    }
+
+    let mut routes = BTreeMap::new();
+    writeln!(output, "Routes (leave the name empty to finish)")?;
+    while let Some(route_name) = prompt(input, output, "Route name")?.filter(|a| !a.is_empty()) {
+        let accepts = prompt(input, output, "  Accepts model")?.unwrap_or_default();
+        let returns = prompt(input, output, "  Returns model")?.unwrap_or_default();
+        for model in [&accepts, &returns] {
+            if !models.contains_key(model) {
+                writeln!(output, "  Warning: model `{model}` is not defined")?;
+            }
+        }
+        routes.insert(
+            route_name,
+            ApiDocsRoute {
+                accepts,
+                returns,
+                ..Default::default()
+            },
+        );
+    }
+
+    Ok(ApiDocs {
+        models,
+        routes,
+        sockets: BTreeMap::new(),
+    })
+}
+
+/// Renders a config file for `--config` with every option at its default,
+/// as a starting point for tweaking them.
+fn scaffold_config() -> Result<String> {
+    Ok(serde_json::to_string_pretty(&Config::default())?)
 }
 
-fn interface_template(name: &str, content: &str) -> String {
-    Template::new(indoc! {"
-        interface {{name}} {
-            {{content}}
+fn new(mut args: pico_args::Arguments) -> Result<()> {
+    let args = NewArgs {
+        out: args
+            .opt_value_from_str("--out")?
+            .unwrap_or_else(|| "api.json".to_string()),
+        config_out: args
+            .opt_value_from_str("--config-out")?
+            .unwrap_or_else(|| "api-gen.config.json".to_string()),
+    };
+
+    for path in [&args.out, &args.config_out] {
+        if std::path::Path::new(path).exists() {
+            eyre::bail!("Refusing to overwrite existing file: {path}");
         }
-    "})
-    .render(&[("name", name), ("content", content)].into())
+    }
+
+    let api_docs = scaffold_spec(&mut std::io::stdin().lock(), &mut std::io::stdout())?;
+    let out_file =
+        File::create(&args.out).wrap_err_with(|| format!("Failed to create: {}", args.out))?;
+    serde_json::to_writer_pretty(out_file, &api_docs)?;
+    std::fs::write(&args.config_out, scaffold_config()?)
+        .wrap_err_with(|| format!("Failed to create: {}", args.config_out))?;
+    println!("Wrote {} and {}", args.out, args.config_out);
+
+    Ok(())
 }
 
-fn render_field_type(obj: &ApiDocsModel) -> String {
-    let inner_type = match &obj.r#type {
-        ApiDocsModelObjectType::String => "string".to_string(),
-        ApiDocsModelObjectType::Number => "number".to_string(),
-        ApiDocsModelObjectType::Boolean => "boolean".to_string(),
-        ApiDocsModelObjectType::Array => {
-            format!(
-                "Array<{}>",
-                render_field_type(
-                    obj.model
-                        .as_ref()
-                        .expect("`model` must be present if `type` is `\"array\"`")
-                )
-            )
+fn load_config(path: Option<&str>) -> Result<Config> {
+    let Some(path) = path else {
+        return Ok(Config::default());
+    };
+    let file = File::open(path).wrap_err_with(|| format!("Failed to open: {path}"))?;
+    let config: Config =
+        serde_json::from_reader(file).wrap_err_with(|| format!("Invalid config: {path}"))?;
+    config
+        .validate()
+        .wrap_err_with(|| format!("Invalid config: {path}"))?;
+    Ok(config)
+}
+
+/// Reads the spec at `path`, failing if it's over the size `limits`. Returns
+/// the snippets of the spec too, only known for specs in our own format.
+fn load_api_docs(
+    path: &str, input_format: InputFormat, limits: &LimitsConfig,
+) -> Result<(ApiDocs, Snippets)> {
+    let file = File::open(path).wrap_err_with(|| format!("Failed to open: {path}"))?;
+    limits::check_file_size(path, file.metadata()?.len(), limits)?;
+    let mut snippets = Snippets::default();
+    let api_docs = match input_format {
+        InputFormat::ApiDocs => {
+            let text = std::io::read_to_string(file)?;
+            let api_docs = serde_json::from_str(&text)?;
+            snippets = Snippets::new(path, text);
+            api_docs
         },
-        ApiDocsModelObjectType::Object => {
-            format!(
-                "{{ {} }}",
-                render_fields(
-                    obj.fields
-                        .as_ref()
-                        .expect("`fields` must be set if `type` is `\"object\"`.")
-                )
-            )
+        InputFormat::OpenApi => {
+            let (api_docs, warnings) = openapi::import(serde_json::from_reader(file)?)
+                .wrap_err_with(|| format!("Failed to import OpenAPI document: {path}"))?;
+            for warning in &warnings {
+                eprintln!("{}", snippets.render(warning));
+            }
+            api_docs
         },
-        _ => todo!(),
+        InputFormat::Smithy => smithy::import(serde_json::from_reader(file)?)
+            .wrap_err_with(|| format!("Failed to import Smithy model: {path}"))?,
     };
-
-    if !obj.required {
-        format!("Optional<{inner_type}>")
-    } else {
-        inner_type
+    let errors = limits::check(&api_docs, limits);
+    if !errors.is_empty() {
+        return Err(diagnostics_error(&snippets, Diagnostics(errors)));
     }
+    Ok((api_docs, snippets))
 }
 
-fn render_field(name: &str, model: &ApiDocsModel) -> String {
-    format!(
-        "{name}{opt}: {type},",
-        opt = model.required.then_some("").unwrap_or("?"),
-        r#type = render_field_type(model)
-    )
+/// Parses a `--service` as `name=path`.
+fn parse_service(value: &str) -> Result<(String, String)> {
+    match value.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+            Ok((name.to_string(), path.to_string()))
+        },
+        _ => eyre::bail!("Expected `--service` as `name=path`, got `{value}`"),
+    }
 }
 
-fn render_fields(obj: &ApiDocsModelsObject) -> String {
-    obj.iter()
-        .map(|(name, model)| render_field(name, model))
-        .collect::<String>()
+/// Turns `diagnostics` into an error showing their snippets.
+fn diagnostics_error(snippets: &Snippets, diagnostics: Diagnostics) -> eyre::Report {
+    eyre::eyre!(snippets.render_all(&diagnostics))
 }
 
-fn render_interface(name: &str, obj: &ApiDocsModelObject) -> String {
-    format!("interface {name} {{ {} }}", render_fields(obj))
+/// Applies the override files in the directory of the spec at `path`. `fmt`
+/// doesn't, so they never end up in the spec.
+fn load_overrides(path: &str, api_docs: &mut ApiDocs) -> Result<()> {
+    let dir = match std::path::Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    let mut entries = std::fs::read_dir(dir)
+        .wrap_err_with(|| format!("Failed to read: {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        let Some(name) = entry
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(overrides::SUFFIX))
+        else {
+            continue;
+        };
+        let file =
+            File::open(&entry).wrap_err_with(|| format!("Failed to open: {}", entry.display()))?;
+        let model_override = serde_json::from_reader(file)
+            .wrap_err_with(|| format!("Invalid overrides: {}", entry.display()))?;
+        overrides::apply(api_docs, name, model_override)
+            .wrap_err_with(|| format!("Invalid overrides: {}", entry.display()))?;
+    }
+    Ok(())
 }
 
-fn render_interfaces(models: &BTreeMap<String, ApiDocsModelObject>) -> String {
-    models
+fn lint(mut args: pico_args::Arguments) -> Result<()> {
+    let args = LintArgs {
+        file: args.value_from_str("--file")?,
+        input_format: args
+            .opt_value_from_str("--input-format")?
+            .unwrap_or(InputFormat::ApiDocs),
+        lint_format: args
+            .opt_value_from_str("--lint-format")?
+            .unwrap_or(LintFormat::Text),
+        config: args.opt_value_from_str("--config")?,
+        min_doc_coverage: args.opt_value_from_str("--min-doc-coverage")?,
+        fail_on_deprecated_use: args.contains("--fail-on-deprecated-use"),
+    };
+
+    let mut config = load_config(args.config.as_deref())?;
+    if let Some(min_doc_coverage) = args.min_doc_coverage {
+        config.lint.descriptions.min_coverage = Some(min_doc_coverage);
+    }
+    if args.fail_on_deprecated_use {
+        config.lint.fail_on_deprecated_use = true;
+    }
+    let (mut api_docs, snippets) = load_api_docs(&args.file, args.input_format, &config.limits)?;
+    load_overrides(&args.file, &mut api_docs)?;
+    let errors = validate::validate(&api_docs);
+    if !errors.is_empty() {
+        if args.lint_format == LintFormat::Sarif {
+            println!("{:#}", sarif::sarif_log(&errors, &args.file, &snippets));
+        }
+        return Err(diagnostics_error(&snippets, Diagnostics(errors)));
+    }
+
+    let diagnostics = lint::lint(&api_docs, &config.lint);
+    match args.lint_format {
+        LintFormat::Text => {
+            for diagnostic in &diagnostics {
+                println!("{}", snippets.render(diagnostic));
+            }
+        },
+        LintFormat::Sarif => {
+            println!(
+                "{:#}",
+                sarif::sarif_log(&diagnostics, &args.file, &snippets)
+            );
+        },
+    }
+
+    let errors = diagnostics
         .iter()
-        .map(|(model_name, model)| {
-            let name = heck::AsPascalCase(model_name).to_string();
-            render_interface(&name, model)
-        })
-        .collect::<String>()
-}
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        eyre::bail!("Lint failed with {errors} error(s)");
+    }
 
-fn main() -> Result<()> {
-    color_eyre::install()?;
+    Ok(())
+}
 
-    let mut args = pico_args::Arguments::from_env();
-    let args = Args {
+fn api_report(mut args: pico_args::Arguments) -> Result<()> {
+    let args = ApiReportArgs {
         file: args.value_from_str("--file")?,
-        out: args.value_from_str("--out")?,
+        input_format: args
+            .opt_value_from_str("--input-format")?
+            .unwrap_or(InputFormat::ApiDocs),
+        config: args.opt_value_from_str("--config")?,
+        out: args.opt_value_from_str("--out")?,
     };
 
-    let api_docs: ApiDocs = serde_json::from_reader(
-        File::open(&args.file)
-            .wrap_err_with(|| format!("Failed to open: {}", args.file.clone()))?,
-    )?;
+    let config = load_config(args.config.as_deref())?;
+    let (mut api_docs, snippets) = load_api_docs(&args.file, args.input_format, &config.limits)?;
+    load_overrides(&args.file, &mut api_docs)?;
+    let report = report::api_report(&api_docs, &config.typescript)
+        .map_err(|diagnostics| diagnostics_error(&snippets, diagnostics))?;
+    match &args.out {
+        Some(out) => {
+            std::fs::write(out, report).wrap_err_with(|| format!("Failed to write: {out}"))
+        },
+        None => {
+            print!("{report}");
+            Ok(())
+        },
+    }
+}
 
-    let interfaces = render_interfaces(&api_docs.models);
+/// Rewrites the spec in place, pretty-printed and with the naming fixes
+/// that can be applied safely.
+fn fmt(mut args: pico_args::Arguments) -> Result<()> {
+    let args = FmtArgs {
+        file: args.value_from_str("--file")?,
+        config: args.opt_value_from_str("--config")?,
+        rename_fields: args.contains("--rename-fields"),
+    };
 
-    let mut out_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&args.out)?;
+    let config = load_config(args.config.as_deref())?;
+    let (mut api_docs, _) = load_api_docs(&args.file, InputFormat::ApiDocs, &config.limits)?;
+    for diagnostic in lint::fix_naming(&mut api_docs, &config.lint.naming, args.rename_fields) {
+        println!("{diagnostic}");
+    }
 
-    out_file.write_all(interfaces.as_bytes());
+    let mut json = serde_json::to_string_pretty(&api_docs)?;
+    json.push('\n');
+    std::fs::write(&args.file, json).wrap_err_with(|| format!("Failed to write: {}", args.file))?;
 
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn generate(mut args: pico_args::Arguments) -> Result<()> {
+    let args = Args {
+        file: args.opt_value_from_str("--file")?,
+        services: args.values_from_fn("--service", parse_service)?,
+        out: args.values_from_str("--out")?,
+        input_format: args
+            .opt_value_from_str("--input-format")?
+            .unwrap_or(InputFormat::ApiDocs),
+        targets: args.values_from_str("--target")?,
+        config: args.opt_value_from_str("--config")?,
+        max_line_width: args.opt_value_from_str("--max-line-width")?,
+        hoist_inline_objects: args.contains("--hoist-inline-objects"),
+        optimize_size: args.contains("--optimize-size"),
+        client: args.opt_value_from_str("--client")?,
+        strict: args.contains("--strict"),
+        timings: args.contains("--timings"),
+        trace_out: args.opt_value_from_str("--trace-out")?,
+    };
 
-    #[test]
-    fn test_render_field_type_string() {
-        let rendered = render_field_type(&ApiDocsModel {
-            r#type: ApiDocsModelObjectType::String,
-            fields: None,
-            model: None,
-            members: None,
-            required: true,
-        });
-        k9::snapshot!(rendered, "string");
+    let targets = if args.targets.is_empty() {
+        vec![Target::TypeScript]
+    } else {
+        args.targets
+    };
+    if targets.len() != args.out.len() {
+        eyre::bail!(
+            "Expected one `--out` per target, got {} for {} target(s)",
+            args.out.len(),
+            targets.len()
+        );
     }
-
-    #[test]
-    fn test_render_field_type_number() {
-        let rendered = render_field_type(&ApiDocsModel {
-            r#type: ApiDocsModelObjectType::Number,
-            fields: None,
-            model: None,
-            members: None,
-            required: false,
-        });
-        k9::snapshot!(rendered, "Optional<number>");
+    // Targets are rendered in parallel, so which of them would end up in a
+    // repeated output depends on timing.
+    for (index, out) in args.out.iter().enumerate() {
+        if let Some(first) = args.out[..index].iter().position(|other| other == out) {
+            eyre::bail!(
+                "`--out {out}` is given for both `{}` and `{}`",
+                targets[first],
+                targets[index]
+            );
+        }
     }
 
-    #[test]
-    fn test_render_field_type_boolean() {
-        let rendered = render_field_type(&ApiDocsModel {
-            r#type: ApiDocsModelObjectType::Boolean,
-            fields: None,
-            model: None,
-            members: None,
-            required: false,
-        });
-        k9::snapshot!(rendered, "Optional<boolean>");
+    let mut config = load_config(args.config.as_deref())?;
+    if args.max_line_width.is_some() {
+        config.typescript.max_line_width = args.max_line_width;
     }
-
-    #[test]
-    fn test_render_field_type_array_of_scalar() {
-        let rendered = render_field_type(&ApiDocsModel {
-            r#type: ApiDocsModelObjectType::Array,
-            fields: None,
-            members: None,
-            model: Some(Box::new(ApiDocsModel {
-                r#type: ApiDocsModelObjectType::Boolean,
-                fields: None,
-                model: None,
-                members: None,
-                required: true,
-            })),
-            required: true,
-        });
-        k9::snapshot!(rendered, "Array<boolean>");
+    if args.hoist_inline_objects {
+        config.typescript.hoist_inline_objects = true;
     }
-
-    #[test]
-    fn test_render_field_type_array_of_object() {
-        let rendered = render_field_type(&ApiDocsModel {
-            r#type: ApiDocsModelObjectType::Array,
-            fields: None,
-            members: None,
-            model: Some(Box::new(ApiDocsModel {
-                r#type: ApiDocsModelObjectType::Object,
-                members: None,
-                model: None,
-                fields: Some(
-                    [
-                        (
-                            "foo".to_string(),
-                            ApiDocsModel {
-                                r#type: ApiDocsModelObjectType::String,
-                                fields: None,
-                                members: None,
-                                model: None,
-                                required: true,
-                            },
-                        ),
-                        (
-                            "bar".to_string(),
-                            ApiDocsModel {
-                                r#type: ApiDocsModelObjectType::Boolean,
-                                fields: None,
-                                members: None,
-                                model: None,
-                                required: true,
-                            },
-                        ),
-                    ]
-                    .into(),
-                ),
-                required: true,
-            })),
-            required: false,
-        });
-        k9::snapshot!(rendered, "Optional<Array<{ bar: boolean,foo: string, }>>");
+    if args.optimize_size {
+        config.typescript.optimize_size = true;
     }
-
-    #[test]
-    fn test_render_field_type_array_of_array() {
-        let rendered = render_field_type(&ApiDocsModel {
-            r#type: ApiDocsModelObjectType::Array,
-            fields: None,
-            members: None,
-            model: Some(Box::new(ApiDocsModel {
-                r#type: ApiDocsModelObjectType::Array,
-                fields: None,
-                members: None,
-                model: Some(Box::new(ApiDocsModel {
-                    r#type: ApiDocsModelObjectType::String,
-                    fields: None,
-                    members: None,
-                    model: None,
-                    required: true,
-                })),
-                required: true,
-            })),
-            required: false,
-        });
-        k9::snapshot!(rendered, "Optional<Array<Array<string>>>");
+    if let Some(client) = args.client {
+        config.typescript.client = client;
     }
-
-    #[test]
-    fn test_render_required_field() {
-        let rendered = render_field(
-            "foo",
-            &ApiDocsModel {
-                r#type: ApiDocsModelObjectType::Boolean,
-                fields: None,
-                model: None,
-                members: None,
-                required: true,
+    let timings = Timings::default();
+    let (api_docs, snippets) = timings.time("parse", || -> Result<_> {
+        match (&args.file, args.services.as_slice()) {
+            (Some(file), []) => {
+                let (mut api_docs, snippets) =
+                    load_api_docs(file, args.input_format, &config.limits)?;
+                load_overrides(file, &mut api_docs)?;
+                Ok((api_docs, snippets))
             },
-        );
-        k9::snapshot!(rendered, "foo: boolean,");
+            (None, [_, ..]) => {
+                let mut services = Vec::new();
+                for (name, path) in &args.services {
+                    let (mut api_docs, _) = load_api_docs(path, args.input_format, &config.limits)?;
+                    load_overrides(path, &mut api_docs)?;
+                    services.push((name.clone(), api_docs));
+                }
+                let (api_docs, diagnostics) = merge::merge(services);
+                for diagnostic in &diagnostics {
+                    println!("{diagnostic}");
+                }
+                // Paths in the merged spec don't point into any one file.
+                Ok((api_docs, Snippets::default()))
+            },
+            _ => eyre::bail!("Expected either `--file` or `--service`"),
+        }
+    })?;
+    let mut diagnostics = timings.time("check capabilities", || {
+        targets
+            .iter()
+            .flat_map(|target| capabilities::check(&api_docs, *target))
+            .collect::<Vec<_>>()
+    });
+    if args.strict && !diagnostics.is_empty() {
+        for diagnostic in &mut diagnostics {
+            diagnostic.severity = Severity::Error;
+        }
+        return Err(diagnostics_error(&snippets, Diagnostics(diagnostics)));
+    }
+    for diagnostic in &diagnostics {
+        println!("{}", snippets.render(diagnostic));
     }
 
-    #[test]
-    fn test_render_non_required_field() {
-        let rendered = render_field(
-            "foo",
-            &ApiDocsModel {
-                r#type: ApiDocsModelObjectType::Boolean,
-                fields: None,
-                model: None,
-                members: None,
-                required: false,
-            },
-        );
-        k9::snapshot!(rendered, "foo?: Optional<boolean>,");
+    generate_targets(&api_docs, &targets, &config, &timings, |index, output| {
+        let out = &args.out[index];
+        timings.time(format!("write {out}"), || {
+            std::fs::write(out, output).wrap_err_with(|| format!("Failed to write: {out}"))
+        })
+    })
+    .map_err(|error| match error.downcast::<Diagnostics>() {
+        Ok(diagnostics) => diagnostics_error(&snippets, diagnostics),
+        Err(error) => error,
+    })?;
+
+    if args.timings {
+        eprint!("{}", timings.report());
     }
+    if let Some(trace_out) = &args.trace_out {
+        std::fs::write(trace_out, timings.chrome_trace().to_string())
+            .wrap_err_with(|| format!("Failed to write: {trace_out}"))?;
+    }
+    Ok(())
+}
 
-    #[test]
-    fn test_render_interface_simple() {
-        let rendered = render_interface(
-            "Foo",
-            &[
-                (
-                    "foo".to_string(),
-                    ApiDocsModel {
-                        r#type: ApiDocsModelObjectType::String,
-                        fields: None,
-                        members: None,
-                        model: None,
-                        required: true,
-                    },
-                ),
-                (
-                    "bar".to_string(),
-                    ApiDocsModel {
-                        r#type: ApiDocsModelObjectType::Boolean,
-                        fields: None,
-                        members: None,
-                        model: None,
-                        required: true,
-                    },
-                ),
-            ]
-            .into(),
-        );
-        k9::snapshot!(rendered, "interface Foo { bar: boolean,foo: string, }");
+fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    let mut args = pico_args::Arguments::from_env();
+    match args.subcommand()?.as_deref() {
+        Some("from-json") => from_json(args),
+        Some("new") => new(args),
+        Some("lint") => lint(args),
+        Some("fmt") => fmt(args),
+        Some("api-report") => api_report(args),
+        Some(command) => eyre::bail!("Unknown subcommand: {command}"),
+        None => generate(args),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use api_generator::generate_typescript;
+    use indoc::indoc;
+
+    use super::*;
 
     #[test]
-    fn test_render_interface_with_nested_objects() {
-        let rendered = render_interface(
-            "Foo",
-            &[
-                (
-                    "foo".to_string(),
-                    ApiDocsModel {
-                        r#type: ApiDocsModelObjectType::String,
-                        fields: None,
-                        members: None,
-                        model: None,
-                        required: true,
-                    },
-                ),
-                (
-                    "bar".to_string(),
-                    ApiDocsModel {
-                        r#type: ApiDocsModelObjectType::Object,
-                        fields: Some(
-                            [
-                                (
-                                    "foo".to_string(),
-                                    ApiDocsModel {
-                                        r#type: ApiDocsModelObjectType::String,
-                                        fields: None,
-                                        members: None,
-                                        model: None,
-                                        required: true,
-                                    },
-                                ),
-                                (
-                                    "bar".to_string(),
-                                    ApiDocsModel {
-                                        r#type: ApiDocsModelObjectType::Boolean,
-                                        fields: None,
-                                        members: None,
-                                        model: None,
-                                        required: true,
-                                    },
-                                ),
-                            ]
-                            .into(),
-                        ),
-                        members: None,
-                        model: None,
-                        required: true,
-                    },
-                ),
-            ]
-            .into(),
-        );
+    fn test_scaffold_spec() {
+        let mut input = std::io::Cursor::new(indoc! {"
+            user
+            id
+            number
+
+            name
+            text
+            string
+            n
+
+
+            get_user
+            user_id
+            user
+
+        "});
+        let mut output = Vec::new();
+        let api_docs = scaffold_spec(&mut input, &mut output).unwrap();
         k9::snapshot!(
-            rendered,
-            "interface Foo { bar: { bar: boolean,foo: string, },foo: string, }"
-        );
-    }
+            generate_typescript(
+                &ApiDocs {
+                    models: api_docs.models,
+                    routes: BTreeMap::new(),
+                    sockets: BTreeMap::new(),
+                },
+                &Default::default()
+            )
+            .unwrap(),
+            "
+export interface User {
+  id: number;
+  name?: string | undefined;
+}
 
-    #[test]
-    fn test_render_models_simple() {
-        let rendered = render_interfaces(
-            &[(
-                "Foo".to_string(),
-                [(
-                    "baz".to_string(),
-                    ApiDocsModel {
-                        r#type: ApiDocsModelObjectType::Boolean,
-                        required: true,
-                        fields: None,
-                        members: None,
-                        model: None,
-                    },
-                )]
-                .into(),
-            )]
-            .into(),
+"
         );
-        k9::snapshot!(rendered, "interface Foo { baz: boolean, }");
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Unknown type: text"));
+        assert!(output.contains("Warning: model `user_id` is not defined"));
+        assert_eq!(api_docs.routes["get_user"].returns, "user");
+
+        let config: Config = serde_json::from_str(&scaffold_config().unwrap()).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.typescript.tab_width, 2);
+        assert_eq!(config.typescript.client, TypeScriptClient::Functions);
+        assert_eq!(config.smoke.schemas, "./schemas");
     }
 }
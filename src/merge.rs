@@ -0,0 +1,249 @@
+//! Merging of the specs of several services into one, so that frontends get
+//! a single client namespaced by service, e.g. `api.billing.getInvoice`.
+//!
+//! Routes and sockets are prefixed with their service, e.g.
+//! `billing_get_invoice`. Models are shared between services that define
+//! them the same way. Models defined differently by several services are
+//! prefixed with their service instead, along with every use of them.
+
+use std::collections::BTreeMap;
+
+use crate::diagnostics::Diagnostic;
+use crate::lint::rename_model_uses;
+use crate::ApiDocs;
+
+/// Merges the specs of `services`, keyed by service name. Returns a warning
+/// for every model renamed because services define it differently.
+pub fn merge(services: Vec<(String, ApiDocs)>) -> (ApiDocs, Vec<Diagnostic>) {
+    let mut definitions = BTreeMap::<&str, Vec<(&str, serde_json::Value)>>::new();
+    for (service, api_docs) in &services {
+        for (name, model) in &api_docs.models {
+            let definition = serde_json::to_value(model).unwrap_or_default();
+            definitions
+                .entry(name)
+                .or_default()
+                .push((service, definition));
+        }
+    }
+    let mut diagnostics = Vec::new();
+    let mut conflicts = Vec::new();
+    for (name, definitions) in &definitions {
+        let (_, first) = &definitions[0];
+        if definitions
+            .iter()
+            .all(|(_, definition)| definition == first)
+        {
+            continue;
+        }
+        let services = definitions
+            .iter()
+            .map(|(service, _)| format!("`{service}`"))
+            .collect::<Vec<_>>();
+        let renamed = definitions
+            .iter()
+            .map(|(service, _)| format!("`{service}_{name}`"))
+            .collect::<Vec<_>>();
+        diagnostics.push(Diagnostic::warning(
+            *name,
+            format!(
+                "is defined differently by {}; renamed to {}",
+                join(&services),
+                join(&renamed)
+            ),
+        ));
+        conflicts.push(name.to_string());
+    }
+
+    let mut merged = ApiDocs {
+        models: BTreeMap::new(),
+        routes: BTreeMap::new(),
+        sockets: BTreeMap::new(),
+    };
+    for (service, mut api_docs) in services {
+        let renames = conflicts
+            .iter()
+            .filter(|name| api_docs.models.contains_key(*name))
+            .map(|name| (name.clone(), format!("{service}_{name}")))
+            .collect::<BTreeMap<_, _>>();
+        for (name, renamed) in &renames {
+            if let Some(model) = api_docs.models.remove(name) {
+                api_docs.models.insert(renamed.clone(), model);
+            }
+        }
+        rename_model_uses(&mut api_docs, &renames);
+
+        for (name, model) in api_docs.models {
+            merged.models.entry(name).or_insert(model);
+        }
+        for (name, mut route) in api_docs.routes {
+            route.service = Some(service.clone());
+            merged.routes.insert(format!("{service}_{name}"), route);
+        }
+        for (name, socket) in api_docs.sockets {
+            merged.sockets.insert(format!("{service}_{name}"), socket);
+        }
+    }
+    (merged, diagnostics)
+}
+
+/// Joins `items` with commas and a final `and`.
+fn join(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let billing: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": { "id": { "type": "Number", "required": true } },
+                "money": { "cents": { "type": "Number", "required": true } },
+                "invoice": {
+                    "customer": { "type": "Ref", "required": true, "ref": "user" },
+                    "total": { "type": "Ref", "required": true, "ref": "money" }
+                }
+            },
+            "routes": {
+                "get_invoice": { "accepts": "user", "returns": "invoice" }
+            }
+        }))
+        .unwrap();
+        let identity: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": { "id": { "type": "String", "required": true } },
+                "money": { "cents": { "type": "Number", "required": true } }
+            },
+            "routes": {
+                "get_user": { "accepts": "user", "returns": "user" }
+            }
+        }))
+        .unwrap();
+        let (merged, diagnostics) = merge(vec![
+            ("billing".to_string(), billing),
+            ("identity".to_string(), identity),
+        ]);
+        k9::snapshot!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "warning: user: is defined differently by `billing` and `identity`; renamed to \
+             `billing_user` and `identity_user`"
+        );
+        k9::snapshot!(
+            serde_json::to_string_pretty(&merged).unwrap(),
+            r#"
+{
+  "models": {
+    "billing_user": {
+      "id": {
+        "type": "Number",
+        "required": true
+      }
+    },
+    "identity_user": {
+      "id": {
+        "type": "String",
+        "required": true
+      }
+    },
+    "invoice": {
+      "customer": {
+        "type": "Ref",
+        "ref": "billing_user",
+        "required": true
+      },
+      "total": {
+        "type": "Ref",
+        "ref": "money",
+        "required": true
+      }
+    },
+    "money": {
+      "cents": {
+        "type": "Number",
+        "required": true
+      }
+    }
+  },
+  "routes": {
+    "billing_get_invoice": {
+      "accepts": "billing_user",
+      "returns": "invoice",
+      "service": "billing"
+    },
+    "identity_get_user": {
+      "accepts": "identity_user",
+      "returns": "identity_user",
+      "service": "identity"
+    }
+  }
+}
+"#
+        );
+        k9::snapshot!(
+            crate::generate_typescript(&merged, &crate::config::TypeScriptConfig::default())
+                .unwrap(),
+            r#"
+export interface BillingUser {
+  id: number;
+}
+
+export interface IdentityUser {
+  id: string;
+}
+
+export interface Invoice {
+  customer: BillingUser;
+  total: Money;
+}
+
+export interface Money {
+  cents: number;
+}
+
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: { service?: "billing" | "identity" },
+) => Promise<unknown>;
+
+export function billingGetInvoice(
+  request: ApiRequest,
+  body: BillingUser,
+): Promise<Invoice> {
+  return request("billing_get_invoice", body, {
+    service: "billing",
+  }) as Promise<Invoice>;
+}
+
+export function identityGetUser(
+  request: ApiRequest,
+  body: IdentityUser,
+): Promise<IdentityUser> {
+  return request("identity_get_user", body, {
+    service: "identity",
+  }) as Promise<IdentityUser>;
+}
+
+export const api = {
+  billing: {
+    getInvoice: billingGetInvoice,
+  },
+  identity: {
+    getUser: identityGetUser,
+  },
+};
+
+"#
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Generation of MSW request handlers returning a payload of the `returns`
+//! model of every route, so that tests and stories get mocks matching the
+//! spec.
+//!
+//! Payloads are made of the `example`s of fields, or else their `default`s,
+//! or else values synthesized from their type: strings are placeholders of
+//! their format and length, numbers are within their bounds and arrays have
+//! as many items as they need. Like in the fetch client, routes without a
+//! path are matched at `/{route}` and routes without a method with `POST`.
+
+use std::collections::BTreeMap;
+
+use crate::config::TypeScriptConfig;
+use crate::{
+    flatten_extends, render_header, ApiDocs, ApiDocsModel, ApiDocsModelDefinition,
+    ApiDocsModelFormat, ApiDocsModelObject, ApiDocsModelObjectType, Method, PATH_PARAM,
+};
+
+pub(crate) fn render_msw(api_docs: &ApiDocs, config: &TypeScriptConfig) -> String {
+    let models = flatten_extends(&api_docs.models);
+    let param = regex::Regex::new(PATH_PARAM).expect("valid regex");
+    let handlers = api_docs
+        .routes
+        .iter()
+        .map(|(name, route)| {
+            let function = match route.method {
+                None => "post".to_string(),
+                Some(Method::Trace) => "all".to_string(),
+                Some(method) => method.as_str().to_lowercase(),
+            };
+            let path = match &route.path {
+                Some(path) => param.replace_all(path, ":$1").into_owned(),
+                None => format!("/{name}"),
+            };
+            let payload = Synthesizer {
+                models: &models,
+                stack: Vec::new(),
+            }
+            .synthesize_model(&route.returns)
+            .unwrap_or_default();
+            format!(
+                "{}http.{function}({}, () => HttpResponse.json({payload})),\n",
+                config.indent(1),
+                serde_json::Value::from(format!("*{path}")),
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        "{}import {{ http, HttpResponse }} from \"msw\";\n\
+         \n\
+         export const handlers = [\n\
+         {handlers}\
+         ];\n",
+        render_header(config)
+    )
+}
+
+struct Synthesizer<'a> {
+    models: &'a BTreeMap<String, ApiDocsModelDefinition>,
+    /// Models being synthesized, which can't be nested in themselves.
+    stack: Vec<&'a str>,
+}
+
+impl<'a> Synthesizer<'a> {
+    /// Synthesizes a value of the model `name`, or nothing if it's already
+    /// being synthesized.
+    fn synthesize_model(&mut self, name: &'a str) -> Option<serde_json::Value> {
+        if self.stack.contains(&name) {
+            return None;
+        }
+        let model = self.models.get(name)?;
+        self.stack.push(name);
+        let value = self.synthesize_fields(&model.fields);
+        self.stack.pop();
+        Some(value)
+    }
+
+    /// Synthesizes an object with the fields that can be synthesized.
+    fn synthesize_fields(&mut self, fields: &'a ApiDocsModelObject) -> serde_json::Value {
+        fields
+            .iter()
+            .filter_map(|(name, field)| Some((name.clone(), self.synthesize(field)?)))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    /// Synthesizes a value of `model`, or nothing if it would nest a model
+    /// in itself and `model` can't be left out.
+    fn synthesize(&mut self, model: &'a ApiDocsModel) -> Option<serde_json::Value> {
+        if let Some(value) = model.example.as_ref().or(model.default.as_ref()) {
+            return Some(value.clone());
+        }
+        let value = match model.r#type {
+            ApiDocsModelObjectType::String => Some(synthesize_string(model).into()),
+            ApiDocsModelObjectType::Number => Some(synthesize_number(model)),
+            ApiDocsModelObjectType::Boolean => Some(false.into()),
+            ApiDocsModelObjectType::File => Some(serde_json::Value::Null),
+            ApiDocsModelObjectType::Object => model
+                .fields
+                .as_ref()
+                .map(|fields| self.synthesize_fields(fields)),
+            ApiDocsModelObjectType::Map => Some(serde_json::json!({})),
+            ApiDocsModelObjectType::Array => {
+                let count = model.min_length.unwrap_or(0).max(1);
+                let count = model
+                    .max_length
+                    .map_or(count, |max_length| count.min(max_length));
+                let items = model
+                    .model
+                    .as_deref()
+                    .and_then(|item| {
+                        (0..count)
+                            .map(|_| self.synthesize(item))
+                            .collect::<Option<Vec<_>>>()
+                    })
+                    .unwrap_or_default();
+                Some(items.into())
+            },
+            ApiDocsModelObjectType::Tuple => model
+                .items
+                .iter()
+                .flatten()
+                .map(|item| self.synthesize(item))
+                .collect::<Option<Vec<_>>>()
+                .map(Into::into),
+            ApiDocsModelObjectType::Enum => model.members.iter().flatten().next().cloned(),
+            ApiDocsModelObjectType::Literal => model.value.clone(),
+            ApiDocsModelObjectType::Ref => model
+                .r#ref
+                .as_deref()
+                .and_then(|name| self.synthesize_model(name)),
+            ApiDocsModelObjectType::Union => match (&model.discriminator, &model.variants) {
+                (Some(discriminator), Some(variants)) => {
+                    variants.iter().find_map(|(tag, variant)| {
+                        let mut value = self.synthesize(variant)?;
+                        value
+                            .as_object_mut()?
+                            .insert(discriminator.clone(), tag.as_str().into());
+                        Some(value)
+                    })
+                },
+                _ => model
+                    .models
+                    .iter()
+                    .flatten()
+                    .find_map(|alternative| self.synthesize(alternative)),
+            },
+        };
+        // Optional fields are left out instead. Validation only lets arrays
+        // nest a model in itself otherwise, and they are left empty.
+        value.or_else(|| model.nullable.then_some(serde_json::Value::Null))
+    }
+}
+
+fn synthesize_string(model: &ApiDocsModel) -> String {
+    let value = match model.format {
+        Some(ApiDocsModelFormat::Date) => "1970-01-01",
+        Some(ApiDocsModelFormat::DateTime) => "1970-01-01T00:00:00Z",
+        Some(ApiDocsModelFormat::Time) => "00:00:00",
+        Some(ApiDocsModelFormat::Uuid) => "00000000-0000-0000-0000-000000000000",
+        Some(ApiDocsModelFormat::Email) => "user@example.com",
+        Some(ApiDocsModelFormat::Uri) => "https://example.com",
+        _ => "string",
+    };
+    let mut value = value.to_string();
+    if let Some(max_length) = model.max_length {
+        value.truncate(max_length);
+    }
+    if let Some(min_length) = model.min_length {
+        while value.len() < min_length {
+            value.push('x');
+        }
+    }
+    value
+}
+
+/// Returns the number closest to zero within the bounds of `model`, rounded
+/// up to an integer for integers.
+fn synthesize_number(model: &ApiDocsModel) -> serde_json::Value {
+    let mut value = 0.0_f64;
+    if let Some(minimum) = model.minimum {
+        value = value.max(minimum);
+    }
+    if let Some(maximum) = model.maximum {
+        value = value.min(maximum);
+    }
+    if model.format == Some(ApiDocsModelFormat::Integer) {
+        value = value.ceil();
+    }
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        (value as i64).into()
+    } else {
+        value.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_msw() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "entity": {
+                    "id": { "type": "String", "format": "uuid", "required": true }
+                },
+                "user": {
+                    "extends": ["entity"],
+                    "fields": {
+                        "name": { "type": "String", "required": true, "example": "Ada" },
+                        "role": { "type": "Enum", "required": true, "members": ["admin", "member"] },
+                        "age": { "type": "Number", "required": false, "minimum": 18 },
+                        "code": { "type": "String", "required": true, "max_length": 3 },
+                        "manager": { "type": "Ref", "required": false, "ref": "user" },
+                        "reports": {
+                            "type": "Array",
+                            "required": true,
+                            "model": { "type": "Ref", "required": true, "ref": "user" }
+                        },
+                        "shape": {
+                            "type": "Union",
+                            "required": true,
+                            "discriminator": "kind",
+                            "variants": {
+                                "circle": {
+                                    "type": "Object",
+                                    "required": true,
+                                    "fields": { "radius": { "type": "Number", "required": true } }
+                                }
+                            }
+                        }
+                    }
+                },
+                "empty": {}
+            },
+            "routes": {
+                "get_user": {
+                    "accepts": "empty",
+                    "returns": "user",
+                    "method": "GET",
+                    "path": "/users/{id}",
+                    "params": { "id": { "type": "Number", "required": true } }
+                },
+                "ping": { "accepts": "empty", "returns": "empty" }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_msw(&api_docs, &TypeScriptConfig::default()),
+            r#"
+import { http, HttpResponse } from "msw";
+
+export const handlers = [
+  http.get("*/users/:id", () => HttpResponse.json({"age":18,"code":"str","id":"00000000-0000-0000-0000-000000000000","name":"Ada","reports":[],"role":"admin","shape":{"kind":"circle","radius":0}})),
+  http.post("*/ping", () => HttpResponse.json({})),
+];
+
+"#
+        );
+    }
+}
@@ -0,0 +1,699 @@
+//! Conversion of OpenAPI 3.x documents into [`ApiDocs`].
+//!
+//! Only JSON documents are supported. `components.schemas` become models and
+//! every operation under `paths` becomes a route. References to object
+//! schemas become model refs; other schemas can't be models and are inlined.
+//! Schemas composed with `allOf` extend the object schemas they reference.
+//! What can't be converted is skipped with a warning.
+
+use std::collections::BTreeMap;
+
+use eyre::Result;
+use serde::Deserialize;
+
+use crate::diagnostics::Diagnostic;
+use crate::{
+    ApiDocs, ApiDocsModel, ApiDocsModelDefinition, ApiDocsModelFormat, ApiDocsModelObject,
+    ApiDocsModelObjectType, ApiDocsRoute, ContentType,
+};
+
+const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Name of the model used for operations without a request or response body.
+const EMPTY_MODEL: &str = "empty";
+
+#[derive(Deserialize)]
+struct OpenApi {
+    #[serde(default)]
+    paths: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    #[serde(default)]
+    components: Components,
+}
+
+#[derive(Default, Deserialize)]
+struct Components {
+    #[serde(default)]
+    schemas: BTreeMap<String, Schema>,
+}
+
+#[derive(Deserialize)]
+struct Schema {
+    #[serde(rename = "$ref")]
+    reference: Option<String>,
+    r#type: Option<String>,
+    format: Option<String>,
+    #[serde(default)]
+    properties: BTreeMap<String, Schema>,
+    #[serde(default)]
+    required: Vec<String>,
+    items: Option<Box<Schema>>,
+    /// Schema of the values of maps. `true` and `false` are ignored.
+    #[serde(
+        default,
+        rename = "additionalProperties",
+        deserialize_with = "deserialize_additional_properties"
+    )]
+    additional_properties: Option<Box<Schema>>,
+    r#enum: Option<Vec<serde_json::Value>>,
+    r#const: Option<serde_json::Value>,
+    description: Option<String>,
+    example: Option<serde_json::Value>,
+    #[serde(default)]
+    nullable: bool,
+    #[serde(rename = "minLength", alias = "minItems")]
+    min_length: Option<usize>,
+    #[serde(rename = "maxLength", alias = "maxItems")]
+    max_length: Option<usize>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    pattern: Option<String>,
+    #[serde(rename = "allOf")]
+    all_of: Option<Vec<Schema>>,
+    #[serde(rename = "oneOf")]
+    one_of: Option<Vec<Schema>>,
+    #[serde(rename = "anyOf")]
+    any_of: Option<Vec<Schema>>,
+}
+
+#[derive(Deserialize)]
+struct Operation {
+    #[serde(rename = "operationId")]
+    operation_id: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    #[serde(rename = "requestBody")]
+    request_body: Option<Body>,
+    #[serde(default)]
+    responses: BTreeMap<String, Body>,
+}
+
+#[derive(Deserialize)]
+struct Body {
+    #[serde(default)]
+    content: BTreeMap<String, MediaType>,
+}
+
+#[derive(Deserialize)]
+struct MediaType {
+    schema: Option<Schema>,
+}
+
+struct Importer<'a> {
+    schemas: &'a BTreeMap<String, Schema>,
+    models: BTreeMap<String, ApiDocsModelDefinition>,
+}
+
+/// Converts an OpenAPI document, along with warnings about the schemas and
+/// operations it skips.
+pub fn import(document: serde_json::Value) -> Result<(ApiDocs, Vec<Diagnostic>)> {
+    let document: OpenApi = serde_json::from_value(document)?;
+    let mut warnings = Vec::new();
+    let mut importer = Importer {
+        schemas: &document.components.schemas,
+        models: BTreeMap::new(),
+    };
+
+    for (name, schema) in &document.components.schemas {
+        let path = format!("#/components/schemas/{name}");
+        if let Some(parts) = &schema.all_of {
+            let definition = importer.convert_all_of(&path, name, schema, parts)?;
+            importer.models.insert(name.clone(), definition);
+            continue;
+        }
+        let model = importer.convert(&path, schema, true, &mut vec![name.as_str()])?;
+        match model.fields {
+            Some(fields) => {
+                let definition = ApiDocsModelDefinition {
+                    description: model.description,
+                    fields,
+                    ..Default::default()
+                };
+                importer.models.insert(name.clone(), definition);
+            },
+            None => warnings.push(Diagnostic::warning(
+                format!("components.schemas.{name}"),
+                "skipped non-object schema; it is inlined where used",
+            )),
+        }
+    }
+
+    let mut routes = BTreeMap::new();
+    for (path, item) in &document.paths {
+        for (method, operation) in item {
+            if !HTTP_METHODS.contains(&method.as_str()) {
+                continue;
+            }
+            let operation: Operation = serde_json::from_value(operation.clone())?;
+            let name = operation
+                .operation_id
+                .as_ref()
+                .map(|id| heck::AsSnakeCase(id).to_string())
+                .unwrap_or_else(|| heck::AsSnakeCase(format!("{method} {path}")).to_string());
+
+            let request = operation.request_body.as_ref();
+            let response = operation
+                .responses
+                .iter()
+                .find(|(status, _)| status.starts_with('2'))
+                .map(|(_, response)| response);
+
+            let accepts = importer.body_model(&format!("{name}_request"), request)?;
+            let content_type = request
+                .filter(|request| !request.content.contains_key("application/json"))
+                .filter(|request| request.content.contains_key("multipart/form-data"))
+                .map(|_| ContentType::Multipart);
+            let returns = importer.body_model(&format!("{name}_response"), response)?;
+            let mut errors = BTreeMap::new();
+            for (status, response) in &operation.responses {
+                if !status
+                    .parse()
+                    .is_ok_and(|status: u16| (400..600).contains(&status))
+                {
+                    continue;
+                }
+                let error =
+                    importer.body_model(&format!("{name}_error_{status}"), Some(response))?;
+                match error {
+                    Some(error) => _ = errors.insert(status.clone(), error),
+                    None => warnings.push(Diagnostic::warning(
+                        format!("paths.{path}.{method}.responses.{status}"),
+                        "skipped response; bodies must be object schemas",
+                    )),
+                }
+            }
+            match (accepts, returns) {
+                (Some(accepts), Some(returns)) => {
+                    let description = operation.description.or(operation.summary);
+                    routes.insert(
+                        name,
+                        ApiDocsRoute {
+                            accepts,
+                            returns,
+                            method: serde_json::from_value(method.to_uppercase().into()).ok(),
+                            content_type,
+                            errors,
+                            description,
+                            ..Default::default()
+                        },
+                    );
+                },
+                _ => warnings.push(Diagnostic::warning(
+                    format!("paths.{path}.{method}"),
+                    "skipped operation; bodies must be object schemas",
+                )),
+            }
+        }
+    }
+
+    let api_docs = ApiDocs {
+        models: importer.models,
+        routes,
+        sockets: BTreeMap::new(),
+    };
+    Ok((api_docs, warnings))
+}
+
+impl Schema {
+    /// Whether the schema converts to an object model.
+    fn is_object(&self) -> bool {
+        self.reference.is_none()
+            && self.r#enum.is_none()
+            && self.r#const.is_none()
+            && self.one_of.is_none()
+            && self.any_of.is_none()
+            && !self.is_map()
+            && matches!(self.r#type.as_deref(), Some("object") | None)
+    }
+
+    /// Whether the schema converts to a map model.
+    fn is_map(&self) -> bool {
+        self.properties.is_empty() && self.additional_properties.is_some()
+    }
+}
+
+impl<'a> Importer<'a> {
+    /// Returns the name of the model describing `body`, registering a new
+    /// model named `fallback_name` when the body schema is defined inline.
+    ///
+    /// Returns `None` if the body is not an object and so can't be a model.
+    fn body_model(&mut self, fallback_name: &str, body: Option<&Body>) -> Result<Option<String>> {
+        let schema = body.and_then(|body| {
+            body.content
+                .get("application/json")
+                .or_else(|| body.content.values().next())
+                .and_then(|media| media.schema.as_ref())
+        });
+
+        let Some(schema) = schema else {
+            self.models.entry(EMPTY_MODEL.to_string()).or_default();
+            return Ok(Some(EMPTY_MODEL.to_string()));
+        };
+
+        if let Some(name) = schema
+            .reference
+            .as_deref()
+            .and_then(|reference| reference.strip_prefix("#/components/schemas/"))
+        {
+            if self.models.contains_key(name) {
+                return Ok(Some(name.to_string()));
+            }
+        }
+
+        let model = self.convert(fallback_name, schema, true, &mut Vec::new())?;
+        Ok(model.fields.map(|fields| {
+            self.models.insert(fallback_name.to_string(), fields.into());
+            fallback_name.to_string()
+        }))
+    }
+
+    /// Converts the `allOf` of the component `name` into a model extending
+    /// the referenced object schemas, with the fields of the inline ones.
+    fn convert_all_of(
+        &self, path: &str, name: &'a str, schema: &Schema, parts: &'a [Schema],
+    ) -> Result<ApiDocsModelDefinition> {
+        let mut definition = ApiDocsModelDefinition {
+            description: schema.description.clone(),
+            ..Default::default()
+        };
+        for (index, part) in parts.iter().enumerate() {
+            let path = format!("{path}/allOf/{index}");
+            let parent = part
+                .reference
+                .as_deref()
+                .and_then(|reference| reference.strip_prefix("#/components/schemas/"))
+                .filter(|parent| self.schemas.get(*parent).is_some_and(Schema::is_object));
+            match parent {
+                Some(parent) => definition.extends.push(parent.to_string()),
+                None => {
+                    let model = self.convert(&path, part, true, &mut vec![name])?;
+                    let fields = model
+                        .fields
+                        .ok_or_else(|| eyre::eyre!("Unsupported non-object schema at `{path}`"))?;
+                    definition.fields.extend(fields);
+                },
+            }
+        }
+        Ok(definition)
+    }
+
+    fn convert(
+        &self, path: &str, schema: &'a Schema, required: bool, refs: &mut Vec<&'a str>,
+    ) -> Result<ApiDocsModel> {
+        if schema.all_of.is_some() {
+            eyre::bail!("Unsupported schema composition at `{path}`");
+        }
+
+        if let Some(reference) = &schema.reference {
+            let name = reference
+                .strip_prefix("#/components/schemas/")
+                .ok_or_else(|| eyre::eyre!("Unsupported reference `{reference}` at `{path}`"))?;
+            let target = self
+                .schemas
+                .get(name)
+                .ok_or_else(|| eyre::eyre!("Unknown reference `{reference}` at `{path}`"))?;
+
+            if target.is_object() {
+                return Ok(ApiDocsModel {
+                    r#type: ApiDocsModelObjectType::Ref,
+                    r#ref: Some(name.to_string()),
+                    required,
+                    description: schema.description.clone(),
+                    ..Default::default()
+                });
+            }
+
+            if refs.contains(&name) {
+                eyre::bail!("Recursive reference `{reference}` at `{path}` cannot be inlined");
+            }
+            refs.push(name);
+            let model = self.convert(path, target, required, refs);
+            refs.pop();
+            return model;
+        }
+
+        let mut model = ApiDocsModel {
+            r#type: ApiDocsModelObjectType::String,
+            required,
+            nullable: schema.nullable,
+            min_length: schema.min_length,
+            max_length: schema.max_length,
+            minimum: schema.minimum,
+            maximum: schema.maximum,
+            pattern: schema.pattern.clone(),
+            example: schema.example.clone(),
+            description: schema.description.clone(),
+            ..Default::default()
+        };
+
+        if let Some((keyword, alternatives)) = (schema.one_of.as_ref().map(|a| ("oneOf", a)))
+            .or_else(|| schema.any_of.as_ref().map(|a| ("anyOf", a)))
+        {
+            model.r#type = ApiDocsModelObjectType::Union;
+            model.models = Some(
+                alternatives
+                    .iter()
+                    .enumerate()
+                    .map(|(index, alternative)| {
+                        let path = format!("{path}/{keyword}/{index}");
+                        self.convert(&path, alternative, true, refs)
+                    })
+                    .collect::<Result<_>>()?,
+            );
+            return Ok(model);
+        }
+
+        if let Some(value) = &schema.r#const {
+            model.r#type = ApiDocsModelObjectType::Literal;
+            model.value = Some(value.clone());
+            return Ok(model);
+        }
+        if let Some(members) = &schema.r#enum {
+            model.r#type = ApiDocsModelObjectType::Enum;
+            model.members = Some(members.clone());
+            return Ok(model);
+        }
+
+        match schema.r#type.as_deref() {
+            Some("string") if schema.format.as_deref() == Some("binary") => {
+                model.r#type = ApiDocsModelObjectType::File;
+            },
+            Some("string") => {
+                model.format = match schema.format.as_deref() {
+                    Some("date") => Some(ApiDocsModelFormat::Date),
+                    Some("date-time") => Some(ApiDocsModelFormat::DateTime),
+                    Some("time") => Some(ApiDocsModelFormat::Time),
+                    Some("uuid") => Some(ApiDocsModelFormat::Uuid),
+                    Some("email") => Some(ApiDocsModelFormat::Email),
+                    Some("uri") => Some(ApiDocsModelFormat::Uri),
+                    _ => None,
+                };
+            },
+            Some("integer") => {
+                model.r#type = ApiDocsModelObjectType::Number;
+                model.format = Some(ApiDocsModelFormat::Integer);
+            },
+            Some("number") => {
+                model.r#type = ApiDocsModelObjectType::Number;
+                if let Some("float" | "double") = schema.format.as_deref() {
+                    model.format = Some(ApiDocsModelFormat::Float);
+                }
+            },
+            Some("boolean") => model.r#type = ApiDocsModelObjectType::Boolean,
+            Some("array") => {
+                let items = schema
+                    .items
+                    .as_ref()
+                    .ok_or_else(|| eyre::eyre!("Array schema at `{path}` has no `items`"))?;
+                model.r#type = ApiDocsModelObjectType::Array;
+                model.model = Some(Box::new(self.convert(
+                    &format!("{path}/items"),
+                    items,
+                    true,
+                    refs,
+                )?));
+            },
+            Some("object") | None if schema.is_map() => {
+                let values = schema
+                    .additional_properties
+                    .as_ref()
+                    .expect("checked by `is_map`");
+                model.r#type = ApiDocsModelObjectType::Map;
+                model.values = Some(Box::new(self.convert(
+                    &format!("{path}/additionalProperties"),
+                    values,
+                    true,
+                    refs,
+                )?));
+            },
+            Some("object") | None => {
+                model.r#type = ApiDocsModelObjectType::Object;
+                let fields = schema
+                    .properties
+                    .iter()
+                    .map(|(name, property)| {
+                        let required = schema.required.contains(name);
+                        let path = format!("{path}/properties/{name}");
+                        Ok((name.clone(), self.convert(&path, property, required, refs)?))
+                    })
+                    .collect::<Result<_>>()?;
+                model.fields = Some(fields);
+            },
+            Some(other) => eyre::bail!("Unsupported schema type `{other}` at `{path}`"),
+        }
+
+        Ok(model)
+    }
+}
+
+fn deserialize_additional_properties<'de, D>(
+    deserializer: D,
+) -> Result<Option<Box<Schema>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(_) => Ok(None),
+        value => serde_json::from_value(value).map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::render_interfaces;
+
+    #[test]
+    fn test_import_schemas_and_paths() {
+        let (api_docs, warnings) = import(serde_json::json!({
+            "openapi": "3.0.0",
+            "paths": {
+                "/users": {
+                    "parameters": [],
+                    "post": {
+                        "operationId": "createUser",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/user" }
+                                }
+                            }
+                        },
+                        "responses": {
+                            "201": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": { "$ref": "#/components/schemas/user" }
+                                    }
+                                }
+                            },
+                            "422": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "type": "object",
+                                            "properties": { "message": { "type": "string" } }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "get": {
+                        "responses": {
+                            "200": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "type": "object",
+                                            "properties": {
+                                                "users": {
+                                                    "type": "array",
+                                                    "items": { "$ref": "#/components/schemas/user" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "delete": {
+                        "responses": {
+                            "200": {
+                                "content": {
+                                    "application/json": {
+                                        "schema": { "type": "array", "items": { "type": "string" } }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "role": { "type": "string", "enum": ["admin", "member"] },
+                    "contact": {
+                        "oneOf": [
+                            { "type": "string" },
+                            { "$ref": "#/components/schemas/user" }
+                        ]
+                    },
+                    "user": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {
+                            "id": { "type": "integer" },
+                            "nickname": { "type": "string", "nullable": true, "example": "ada" },
+                            "manager": { "$ref": "#/components/schemas/contact" },
+                            "settings": {
+                                "type": "object",
+                                "additionalProperties": { "type": "boolean" }
+                            },
+                            "role": { "$ref": "#/components/schemas/role" }
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        k9::snapshot!(
+            render_interfaces(&api_docs.models),
+            r#"
+export interface CreateUserError422 {
+  message?: string | undefined;
+}
+
+export interface Empty {}
+
+export interface GetUsersResponse {
+  users?: Array<User> | undefined;
+}
+
+export interface User {
+  id: number;
+  manager?: string | User | undefined;
+  nickname?: string | null | undefined;
+  role?: "admin" | "member" | undefined;
+  settings?: Record<string, boolean> | undefined;
+}
+
+"#
+        );
+        k9::snapshot!(
+            api_docs
+                .routes
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(","),
+            "create_user,get_users"
+        );
+        k9::snapshot!(
+            warnings
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "
+warning: components.schemas.contact: skipped non-object schema; it is inlined where used
+warning: components.schemas.role: skipped non-object schema; it is inlined where used
+warning: paths./users.delete: skipped operation; bodies must be object schemas
+"
+        );
+        assert_eq!(api_docs.routes["create_user"].accepts, "user");
+        assert_eq!(api_docs.routes["get_users"].accepts, "empty");
+        assert_eq!(
+            api_docs.models["user"].fields["nickname"].example,
+            Some("ada".into())
+        );
+    }
+
+    #[test]
+    fn test_import_recursive_object_references() {
+        let (api_docs, _) = import(serde_json::json!({
+            "components": {
+                "schemas": {
+                    "node": {
+                        "type": "object",
+                        "properties": {
+                            "children": {
+                                "type": "array",
+                                "items": { "$ref": "#/components/schemas/node" }
+                            }
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_interfaces(&api_docs.models),
+            "
+export interface Node {
+  children?: Array<Node> | undefined;
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_recursive_inlined_references() {
+        let error = import(serde_json::json!({
+            "components": {
+                "schemas": {
+                    "list": {
+                        "type": "array",
+                        "items": { "$ref": "#/components/schemas/list" }
+                    }
+                }
+            }
+        }))
+        .err()
+        .unwrap();
+        k9::snapshot!(
+            error.to_string(),
+            "Recursive reference `#/components/schemas/list` at `#/components/schemas/list/items` \
+             cannot be inlined"
+        );
+    }
+
+    #[test]
+    fn test_import_all_of() {
+        let (api_docs, _) = import(serde_json::json!({
+            "components": {
+                "schemas": {
+                    "base_entity": {
+                        "type": "object",
+                        "properties": { "id": { "type": "string" } },
+                        "required": ["id"]
+                    },
+                    "user": {
+                        "allOf": [
+                            { "$ref": "#/components/schemas/base_entity" },
+                            { "type": "object", "properties": { "name": { "type": "string" } } }
+                        ]
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_interfaces(&api_docs.models),
+            "
+export interface BaseEntity {
+  id: string;
+}
+
+export interface User extends BaseEntity {
+  name?: string | undefined;
+}
+
+"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Sidecar files adjusting a model of a spec without editing the spec
+//! itself, e.g. for a fork or an environment. The overrides for `user` live
+//! in `user.overrides.json` next to the spec.
+
+use serde::Deserialize;
+
+use crate::{ApiDocs, ApiDocsModelObject};
+
+/// File name suffix of override files, after the model name.
+pub const SUFFIX: &str = ".overrides.json";
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelOverride {
+    /// Replaces the description of the model.
+    pub description: Option<String>,
+    /// Fields added to the model, replacing existing fields of the same name.
+    pub fields: ApiDocsModelObject,
+    /// Names of fields removed from the model.
+    pub remove: Vec<String>,
+}
+
+/// Applies `model_override` to the model `name`. Fields are removed before
+/// others are added, so a field can be both removed and added back.
+pub fn apply(
+    api_docs: &mut ApiDocs, name: &str, model_override: ModelOverride,
+) -> eyre::Result<()> {
+    let Some(model) = api_docs.models.get_mut(name) else {
+        eyre::bail!("Overrides for unknown model `{name}`");
+    };
+    for field in &model_override.remove {
+        if model.fields.remove(field).is_none() {
+            eyre::bail!("Can't remove unknown field `{field}` of `{name}`");
+        }
+    }
+    model.fields.extend(model_override.fields);
+    if model_override.description.is_some() {
+        model.description = model_override.description;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let mut api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "id": { "type": "Number", "required": true },
+                    "name": { "type": "String", "required": true },
+                    "legacy_id": { "type": "String", "required": false }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        let model_override = serde_json::from_value(serde_json::json!({
+            "fields": {
+                "name": { "type": "String", "required": false },
+                "tenant": { "type": "String", "required": true }
+            },
+            "remove": ["legacy_id"]
+        }))
+        .unwrap();
+        apply(&mut api_docs, "user", model_override).unwrap();
+        k9::snapshot!(
+            crate::generate_typescript(&api_docs, &Default::default()).unwrap(),
+            "
+export interface User {
+  id: number;
+  name?: string | undefined;
+  tenant: string;
+}
+
+"
+        );
+
+        let error = apply(&mut api_docs, "post", ModelOverride::default()).unwrap_err();
+        k9::snapshot!(error.to_string(), "Overrides for unknown model `post`");
+    }
+}
@@ -0,0 +1,279 @@
+//! Generation of React Query hooks: the TypeScript target along with a
+//! `useQuery` hook for every route without a body, e.g. `useGetUser`, and a
+//! `useMutation` hook for every other route, e.g. `useCreateUser`.
+//!
+//! Query keys start with the name of the route, followed by its inputs, so
+//! that all queries of a route can be invalidated with `[name]`.
+
+use crate::config::TypeScriptConfig;
+use crate::hooks::{hooks_config, render_hooks_file, route_hooks, RouteHook};
+use crate::{ApiDocs, ApiDocsRoute};
+
+pub(crate) fn render_react_query(api_docs: &ApiDocs, config: &TypeScriptConfig) -> String {
+    let config = hooks_config(config);
+    let indent = config.indent(1);
+
+    let has_queries = api_docs.routes.values().any(|route| !route.has_body());
+    let has_mutations = api_docs.routes.values().any(ApiDocsRoute::has_body);
+    let mut imports = Vec::new();
+    if has_mutations {
+        imports.extend(["useMutation", "type UseMutationOptions"]);
+    }
+    if has_queries {
+        imports.extend(["useQuery", "type UseQueryOptions"]);
+    }
+
+    let hooks = route_hooks(api_docs, &config)
+        .iter()
+        .map(|route_hook| {
+            let RouteHook { name, route, function, hook, returns, parameters, inputs, doc } =
+                route_hook;
+            if !route.has_body() {
+                let key = std::iter::once(serde_json::Value::from(*name).to_string())
+                    .chain(inputs.iter().cloned())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let mut hook_parameters = parameters.clone();
+                hook_parameters.push(format!(
+                    "options?: Omit<UseQueryOptions<{returns}>, \"queryKey\" | \"queryFn\">"
+                ));
+                format!(
+                    "export function {function}QueryKey({}) {{\n\
+                     {indent}return [{key}] as const;\n\
+                     }}\n\
+                     \n\
+                     {doc}export function use{hook}({}) {{\n\
+                     {indent}const request = useApiRequest();\n\
+                     {indent}return useQuery({{\n\
+                     {indent}{indent}...options,\n\
+                     {indent}{indent}queryKey: {function}QueryKey({}),\n\
+                     {indent}{indent}queryFn: () => {},\n\
+                     {indent}}});\n\
+                     }}\n",
+                    parameters.join(", "),
+                    hook_parameters.join(", "),
+                    inputs.join(", "),
+                    route_hook.render_call(),
+                )
+            } else {
+                // A lone body is the variables itself, e.g. `mutate(newUser)`.
+                let (variables, mutation_fn) = match inputs.as_slice() {
+                    [] => ("void".to_string(), format!("() => {}", route_hook.render_call())),
+                    [body] if body == "body" => (
+                        heck::AsPascalCase(&route.accepts).to_string(),
+                        format!("(body: {hook}Variables) => {}", route_hook.render_call()),
+                    ),
+                    _ => (
+                        format!("{{ {} }}", parameters.join("; ")),
+                        format!(
+                            "({{ {} }}: {hook}Variables) => {}",
+                            inputs.join(", "),
+                            route_hook.render_call()
+                        ),
+                    ),
+                };
+                format!(
+                    "export type {hook}Variables = {variables};\n\
+                     \n\
+                     {doc}export function use{hook}(\n\
+                     {indent}options?: Omit<UseMutationOptions<{returns}, Error, {hook}Variables>, \"mutationFn\">,\n\
+                     ) {{\n\
+                     {indent}const request = useApiRequest();\n\
+                     {indent}return useMutation({{\n\
+                     {indent}{indent}...options,\n\
+                     {indent}{indent}mutationFn: {mutation_fn},\n\
+                     {indent}}});\n\
+                     }}\n"
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let imports = format!(
+        "import {{ {} }} from \"@tanstack/react-query\";\n",
+        imports.join(", ")
+    );
+    render_hooks_file(api_docs, &config, &imports, &hooks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_react_query() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": { "id": { "type": "Number", "required": true } },
+                "new_user": { "name": { "type": "String", "required": true } },
+                "user_filter": { "name": { "type": "String", "required": false } },
+                "empty": {}
+            },
+            "routes": {
+                "get_user": {
+                    "accepts": "empty",
+                    "returns": "user",
+                    "method": "GET",
+                    "path": "/users/{id}",
+                    "params": { "id": { "type": "Number", "required": true } },
+                    "query": "user_filter",
+                    "description": "Returns a user."
+                },
+                "create_user": {
+                    "accepts": "new_user",
+                    "returns": "user",
+                    "method": "POST",
+                    "path": "/users"
+                },
+                "rename_user": {
+                    "accepts": "new_user",
+                    "returns": "user",
+                    "method": "PATCH",
+                    "path": "/users/{id}",
+                    "params": { "id": { "type": "Number", "required": true } }
+                }
+            }
+        }))
+        .unwrap();
+        let output = render_react_query(&api_docs, &TypeScriptConfig::default());
+        // The output imports modules, so it must export what callers use.
+        assert!(output.contains("export function useGetUser("));
+        assert!(output.contains("export const ApiRequestProvider"));
+        k9::snapshot!(
+            output,
+            r#"
+import { createContext, useContext } from "react";
+import { useMutation, type UseMutationOptions, useQuery, type UseQueryOptions } from "@tanstack/react-query";
+
+export interface Empty {}
+
+export interface NewUser {
+  name: string;
+}
+
+export interface User {
+  id: number;
+}
+
+export interface UserFilter {
+  name?: string | undefined;
+}
+
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: {
+    method?:
+      | "GET"
+      | "HEAD"
+      | "POST"
+      | "PUT"
+      | "PATCH"
+      | "DELETE"
+      | "OPTIONS"
+      | "TRACE";
+    path?: string;
+    query?: string;
+  },
+) => Promise<unknown>;
+
+function serializeQuery(query: object): string {
+  const search = new URLSearchParams();
+  for (const [key, value] of Object.entries(query)) {
+    for (const item of Array.isArray(value) ? value : [value]) {
+      if (item !== undefined && item !== null) {
+        search.append(key, String(item));
+      }
+    }
+  }
+  const serialized = search.toString();
+  return serialized === "" ? "" : `?${serialized}`;
+}
+
+export function createUser(request: ApiRequest, body: NewUser): Promise<User> {
+  return request("create_user", body, {
+    method: "POST",
+    path: `/users`,
+  }) as Promise<User>;
+}
+
+/** Returns a user. */
+export function getUser(
+  request: ApiRequest,
+  params: { id: number },
+  query: UserFilter,
+): Promise<User> {
+  return request("get_user", undefined, {
+    method: "GET",
+    path: `/users/${encodeURIComponent(params.id)}`,
+    query: serializeQuery(query),
+  }) as Promise<User>;
+}
+
+export function renameUser(
+  request: ApiRequest,
+  params: { id: number },
+  body: NewUser,
+): Promise<User> {
+  return request("rename_user", body, {
+    method: "PATCH",
+    path: `/users/${encodeURIComponent(params.id)}`,
+  }) as Promise<User>;
+}
+
+const ApiRequestContext = createContext<ApiRequest | undefined>(undefined);
+
+/** Provides the transport the hooks send requests with. */
+export const ApiRequestProvider = ApiRequestContext.Provider;
+
+function useApiRequest(): ApiRequest {
+  const request = useContext(ApiRequestContext);
+  if (request === undefined) {
+    throw new Error("API hooks must be used inside of an `ApiRequestProvider`");
+  }
+  return request;
+}
+
+export type CreateUserVariables = NewUser;
+
+export function useCreateUser(
+  options?: Omit<UseMutationOptions<User, Error, CreateUserVariables>, "mutationFn">,
+) {
+  const request = useApiRequest();
+  return useMutation({
+    ...options,
+    mutationFn: (body: CreateUserVariables) => createUser(request, body),
+  });
+}
+
+export function getUserQueryKey(params: { id: number }, query: UserFilter) {
+  return ["get_user", params, query] as const;
+}
+
+/** Returns a user. */
+export function useGetUser(params: { id: number }, query: UserFilter, options?: Omit<UseQueryOptions<User>, "queryKey" | "queryFn">) {
+  const request = useApiRequest();
+  return useQuery({
+    ...options,
+    queryKey: getUserQueryKey(params, query),
+    queryFn: () => getUser(request, params, query),
+  });
+}
+
+export type RenameUserVariables = { params: { id: number }; body: NewUser };
+
+export function useRenameUser(
+  options?: Omit<UseMutationOptions<User, Error, RenameUserVariables>, "mutationFn">,
+) {
+  const request = useApiRequest();
+  return useMutation({
+    ...options,
+    mutationFn: ({ params, body }: RenameUserVariables) => renameUser(request, params, body),
+  });
+}
+
+"#
+        );
+    }
+}
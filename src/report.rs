@@ -0,0 +1,99 @@
+//! API reports: the public surface of the generated TypeScript client,
+//! normalized so that diffs between two reports only show changes that
+//! affect users of the client.
+//!
+//! Declarations are sorted by name and rendered without bodies, comments or
+//! layout options. Options changing the types themselves, like `optional`,
+//! still apply.
+
+use crate::config::TypeScriptConfig;
+use crate::diagnostics::Diagnostics;
+use crate::{validate, ApiDocs, TypeScriptRenderer};
+
+/// Validates the spec, then renders its API report.
+pub fn api_report(api_docs: &ApiDocs, config: &TypeScriptConfig) -> Result<String, Diagnostics> {
+    let errors = validate::validate(api_docs);
+    if !errors.is_empty() {
+        return Err(Diagnostics(errors));
+    }
+
+    let config = TypeScriptConfig {
+        integer_type: config.integer_type.clone(),
+        formats: config.formats.clone(),
+        optional: config.optional.clone(),
+        ..Default::default()
+    };
+    let renderer = TypeScriptRenderer::without_docs(&config);
+
+    let mut interfaces = api_docs
+        .models
+        .iter()
+        .map(|(name, model)| {
+            let name = heck::AsPascalCase(name).to_string();
+            let interface = renderer.render_model_interface(&name, model);
+            (name, interface)
+        })
+        .collect::<Vec<_>>();
+    interfaces.sort();
+
+    let mut functions = api_docs
+        .routes
+        .iter()
+        .map(|(name, route)| format!("{};\n", renderer.render_route_signature(name, route)))
+        .collect::<Vec<_>>();
+    functions.sort();
+
+    let mut sections = interfaces
+        .into_iter()
+        .map(|(_, interface)| interface)
+        .collect::<Vec<_>>();
+    if !functions.is_empty() {
+        sections.push(functions.concat());
+    }
+    Ok(sections.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_report() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user_profile": {
+                    "description": "Not part of the report.",
+                    "fields": {
+                        "name": { "type": "String", "required": true, "description": "Nor this." }
+                    }
+                },
+                "account": { "id": { "type": "Number", "required": true } }
+            },
+            "routes": {
+                "update_profile": { "accepts": "user_profile", "returns": "user_profile" },
+                "get_account": { "accepts": "account", "returns": "account" }
+            }
+        }))
+        .unwrap();
+        let config = TypeScriptConfig {
+            use_tabs: true,
+            ..Default::default()
+        };
+        k9::snapshot!(
+            api_report(&api_docs, &config).unwrap(),
+            "
+export interface Account {
+  id: number;
+}
+
+export interface UserProfile {
+  name: string;
+}
+
+function getAccount(request: ApiRequest, body: Account): Promise<Account>;
+function updateProfile(request: ApiRequest, body: UserProfile): Promise<UserProfile>;
+
+"
+        );
+    }
+}
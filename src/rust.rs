@@ -0,0 +1,579 @@
+//! Generation of Rust types deriving serde's `Serialize` and `Deserialize`,
+//! for backends sharing the spec with the frontend.
+//!
+//! Inline objects and string enums can't be anonymous in Rust, so they become
+//! items named after the model and field they appear in.
+
+use std::collections::BTreeSet;
+
+use crate::config::RustConfig;
+use crate::diagnostics::Diagnostics;
+use crate::{
+    flatten_extends, recursive_models, render_comment, validate, ApiDocs, ApiDocsModel,
+    ApiDocsModelFormat, ApiDocsModelObject, ApiDocsModelObjectType,
+};
+
+const INDENT: &str = "    ";
+
+const KEYWORDS: [&str; 37] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while",
+];
+
+/// Validates the spec, then renders a struct for every model.
+pub fn generate_rust(api_docs: &ApiDocs, config: &RustConfig) -> Result<String, Diagnostics> {
+    let errors = validate::validate(api_docs);
+    if !errors.is_empty() {
+        return Err(Diagnostics(errors));
+    }
+    Ok(render_rust(api_docs, config))
+}
+
+pub(crate) fn render_rust(api_docs: &ApiDocs, config: &RustConfig) -> String {
+    let models = flatten_extends(&api_docs.models);
+    let renderer = RustRenderer {
+        config,
+        recursive: recursive_models(&models),
+    };
+    let mut items = Vec::new();
+    for (name, model) in &models {
+        let index = items.len();
+        renderer.render_struct(
+            &heck::AsPascalCase(name).to_string(),
+            &model.fields,
+            &mut items,
+        );
+        items[index] = format!(
+            "{}{}{}",
+            render_comment(model.comment_before.as_deref()),
+            items[index],
+            render_comment(model.comment_after.as_deref()),
+        );
+    }
+    let header = config
+        .suppressions
+        .iter()
+        .map(|suppression| format!("{}\n", suppression.attribute()))
+        .collect::<String>();
+    let header = if header.is_empty() {
+        header
+    } else {
+        format!("{header}\n")
+    };
+    format!(
+        "{header}use serde::{{Deserialize, Serialize}};\n\n{}",
+        items.join("\n")
+    )
+}
+
+struct RustRenderer<'a> {
+    config: &'a RustConfig,
+    /// Models that reference themselves, which need a `Box` to have a size.
+    recursive: BTreeSet<&'a str>,
+}
+
+impl RustRenderer<'_> {
+    /// Renders the struct `name` into `items`, followed by the items for its
+    /// inline objects and enums.
+    fn render_struct(&self, name: &str, fields: &ApiDocsModelObject, items: &mut Vec<String>) {
+        let index = items.len();
+        items.push(String::new());
+
+        let mut body = String::new();
+        for (field_name, model) in fields {
+            let item_name = format!("{name}{}", heck::AsPascalCase(field_name));
+            let r#type = self.render_type(&item_name, model, false, items);
+            let ident = escape_ident(heck::AsSnakeCase(field_name).to_string());
+            // serde strips the `r#` of raw identifiers.
+            if ident.trim_start_matches("r#") != field_name {
+                body.push_str(&format!("{INDENT}#[serde(rename = {field_name:?})]\n"));
+            }
+            if !model.required {
+                body.push_str(&format!(
+                    "{INDENT}#[serde(default, skip_serializing_if = \"Option::is_none\")]\n"
+                ));
+            }
+            body.push_str(&format!("{INDENT}pub {ident}: {type},\n"));
+        }
+
+        items[index] = format!(
+            "#[derive(Clone, Debug, Serialize, Deserialize)]\npub struct {name} {{\n{body}}}\n"
+        );
+    }
+
+    /// Renders the type of `model`, adding the items it needs to `items` under
+    /// `name`. Refs to recursive models are boxed unless `indirect`, i.e.
+    /// already behind a heap allocation like a `Vec`.
+    fn render_type(
+        &self, name: &str, model: &ApiDocsModel, indirect: bool, items: &mut Vec<String>,
+    ) -> String {
+        let r#type = match model.r#type {
+            ApiDocsModelObjectType::String => model
+                .format
+                .and_then(|format| self.config.formats.get(&format))
+                .cloned()
+                .unwrap_or_else(|| "String".to_string()),
+            ApiDocsModelObjectType::Number => match model.format {
+                Some(ApiDocsModelFormat::Integer) => "i64".to_string(),
+                _ => "f64".to_string(),
+            },
+            ApiDocsModelObjectType::Boolean => "bool".to_string(),
+            ApiDocsModelObjectType::File => "Vec<u8>".to_string(),
+            ApiDocsModelObjectType::Array => format!(
+                "Vec<{}>",
+                self.render_type(
+                    &format!("{name}Item"),
+                    model
+                        .model
+                        .as_ref()
+                        .expect("validated: `model` is set for arrays"),
+                    true,
+                    items
+                )
+            ),
+            ApiDocsModelObjectType::Map => format!(
+                "std::collections::BTreeMap<String, {}>",
+                self.render_type(
+                    &format!("{name}Value"),
+                    model
+                        .values
+                        .as_ref()
+                        .expect("validated: `values` is set for maps"),
+                    true,
+                    items
+                )
+            ),
+            ApiDocsModelObjectType::Tuple => {
+                let types = model
+                    .items
+                    .as_ref()
+                    .expect("validated: `items` is set for tuples")
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        self.render_type(&format!("{name}{index}"), item, false, items)
+                    })
+                    .collect::<Vec<_>>();
+                match types.as_slice() {
+                    [r#type] => format!("({type},)"),
+                    _ => format!("({})", types.join(", ")),
+                }
+            },
+            ApiDocsModelObjectType::Object => {
+                self.render_struct(
+                    name,
+                    model
+                        .fields
+                        .as_ref()
+                        .expect("validated: `fields` is set for objects"),
+                    items,
+                );
+                name.to_string()
+            },
+            ApiDocsModelObjectType::Enum => render_enum(
+                name,
+                model
+                    .members
+                    .as_ref()
+                    .expect("validated: `members` is set for enums"),
+                items,
+            ),
+            ApiDocsModelObjectType::Literal => render_enum(
+                name,
+                std::slice::from_ref(
+                    model
+                        .value
+                        .as_ref()
+                        .expect("validated: `value` is set for literals"),
+                ),
+                items,
+            ),
+            ApiDocsModelObjectType::Ref => {
+                let r#ref = model
+                    .r#ref
+                    .as_deref()
+                    .expect("validated: `ref` is set for refs");
+                let r#type = heck::AsPascalCase(r#ref).to_string();
+                if self.recursive.contains(r#ref) && !indirect {
+                    format!("Box<{type}>")
+                } else {
+                    r#type
+                }
+            },
+            ApiDocsModelObjectType::Union => self.render_union(name, model, items),
+        };
+
+        if model.required && !model.nullable {
+            r#type
+        } else {
+            format!("Option<{type}>")
+        }
+    }
+
+    /// Renders discriminated unions as internally tagged enums and other unions
+    /// as untagged enums, whose variants serde tries in order.
+    fn render_union(&self, name: &str, model: &ApiDocsModel, items: &mut Vec<String>) -> String {
+        let index = items.len();
+        items.push(String::new());
+
+        let mut variants = String::new();
+        let tagging = match &model.discriminator {
+            Some(discriminator) => {
+                let tagged = model
+                    .variants
+                    .as_ref()
+                    .expect("validated: `variants` is set for discriminated unions");
+                for (tag, model) in tagged {
+                    let variant = heck::AsPascalCase(tag).to_string();
+                    let r#type =
+                        self.render_variant_type(&format!("{name}{variant}"), model, items);
+                    variants.push_str(&format!(
+                        "{INDENT}#[serde(rename = {tag:?})]\n{INDENT}{variant}({type}),\n"
+                    ));
+                }
+                format!("tag = {discriminator:?}")
+            },
+            None => {
+                let models = model
+                    .models
+                    .as_ref()
+                    .expect("validated: `models` is set for unions");
+                for (position, model) in models.iter().enumerate() {
+                    let variant = match &model.r#ref {
+                        Some(r#ref) => heck::AsPascalCase(r#ref).to_string(),
+                        None => format!("Variant{position}"),
+                    };
+                    let r#type =
+                        self.render_variant_type(&format!("{name}{variant}"), model, items);
+                    variants.push_str(&format!("{INDENT}{variant}({type}),\n"));
+                }
+                "untagged".to_string()
+            },
+        };
+
+        items[index] = format!(
+        "#[derive(Clone, Debug, Serialize, Deserialize)]\n#[serde({tagging})]\npub enum {name} \
+         {{\n{variants}}}\n"
+    );
+        name.to_string()
+    }
+
+    /// Renders the type of a variant of a union. With `lint_clean`, variants
+    /// are boxed so that they don't differ in size, which clippy warns about.
+    fn render_variant_type(
+        &self, name: &str, model: &ApiDocsModel, items: &mut Vec<String>,
+    ) -> String {
+        let r#type = self.render_type(name, model, self.config.lint_clean, items);
+        if self.config.lint_clean {
+            format!("Box<{type}>")
+        } else {
+            r#type
+        }
+    }
+}
+
+/// Renders string enums as Rust enums. Other enums are typed by their members
+/// only, as serde can't match variants against numbers or booleans.
+fn render_enum(name: &str, members: &[serde_json::Value], items: &mut Vec<String>) -> String {
+    if members.iter().all(serde_json::Value::is_number) {
+        return "f64".to_string();
+    }
+    if members.iter().all(serde_json::Value::is_boolean) {
+        return "bool".to_string();
+    }
+    if !members.iter().all(serde_json::Value::is_string) {
+        return "serde_json::Value".to_string();
+    }
+
+    let variants = members
+        .iter()
+        .filter_map(serde_json::Value::as_str)
+        .map(|member| {
+            let mut variant = heck::AsPascalCase(member).to_string();
+            if !variant.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                variant.insert(0, 'V');
+            }
+            format!("{INDENT}#[serde(rename = {member:?})]\n{INDENT}{variant},\n")
+        })
+        .collect::<String>();
+    items.push(format!(
+        "#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]\npub enum {name} \
+         {{\n{variants}}}\n"
+    ));
+    name.to_string()
+}
+
+fn escape_ident(ident: String) -> String {
+    match ident.as_str() {
+        // Can't be raw identifiers.
+        "crate" | "self" | "super" => format!("{ident}_"),
+        _ if KEYWORDS.contains(&ident.as_str()) => format!("r#{ident}"),
+        _ => ident,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_rust() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "id": { "type": "Number", "required": true },
+                    "type": { "type": "Enum", "required": true, "members": ["admin", "member"] },
+                    "level": { "type": "Enum", "required": false, "members": [1, 2] },
+                    "homeAddress": {
+                        "type": "Object",
+                        "required": false,
+                        "fields": { "city": { "type": "String", "required": true } }
+                    },
+                    "friends": {
+                        "type": "Array",
+                        "required": true,
+                        "model": { "type": "Ref", "required": true, "ref": "user" }
+                    }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_rust(&api_docs, &RustConfig::default()).unwrap(),
+            r#"
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct User {
+    pub friends: Vec<User>,
+    #[serde(rename = "homeAddress")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_address: Option<UserHomeAddress>,
+    pub id: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<f64>,
+    pub r#type: UserType,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserHomeAddress {
+    pub city: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UserType {
+    #[serde(rename = "admin")]
+    Admin,
+    #[serde(rename = "member")]
+    Member,
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_rust_string_formats() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "event": {
+                    "at": { "type": "String", "format": "date-time", "required": true },
+                    "day": { "type": "String", "format": "date", "required": false }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        let config = RustConfig {
+            formats: [(
+                ApiDocsModelFormat::DateTime,
+                "chrono::DateTime<chrono::Utc>".to_string(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+        k9::snapshot!(
+            generate_rust(&api_docs, &config).unwrap(),
+            r#"
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Event {
+    pub at: chrono::DateTime<chrono::Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<String>,
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_rust_with_suppressions() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": { "user": { "id": { "type": "Number", "required": true } } },
+            "routes": {}
+        }))
+        .unwrap();
+        let config = RustConfig {
+            suppressions: vec![crate::config::RustSuppression::Clippy],
+            ..Default::default()
+        };
+        k9::snapshot!(
+            generate_rust(&api_docs, &config).unwrap(),
+            "
+#![allow(clippy::all)]
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct User {
+    pub id: f64,
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn test_generate_rust_lint_clean() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "node": {
+                    "shape": {
+                        "type": "Union",
+                        "required": true,
+                        "discriminator": "kind",
+                        "variants": {
+                            "leaf": {
+                                "type": "Object",
+                                "required": true,
+                                "fields": { "value": { "type": "Number", "required": true } }
+                            },
+                            "branch": { "type": "Ref", "required": true, "ref": "node" }
+                        }
+                    },
+                    "id": {
+                        "type": "Union",
+                        "required": true,
+                        "models": [
+                            { "type": "String", "required": true },
+                            { "type": "Number", "required": true }
+                        ]
+                    }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        let config = RustConfig {
+            lint_clean: true,
+            ..Default::default()
+        };
+        let output = generate_rust(&api_docs, &config).unwrap();
+        // Variants are boxed for clippy's `large_enum_variant` instead.
+        assert!(!output.contains("allow("), "{output}");
+        k9::snapshot!(
+            output,
+            r#"
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Node {
+    pub id: NodeId,
+    pub shape: NodeShape,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NodeId {
+    Variant0(Box<String>),
+    Variant1(Box<f64>),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum NodeShape {
+    #[serde(rename = "branch")]
+    Branch(Box<Node>),
+    #[serde(rename = "leaf")]
+    Leaf(Box<NodeShapeLeaf>),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeShapeLeaf {
+    pub value: f64,
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_rust_recursive_models() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "node": {
+                    "parent": { "type": "Ref", "required": false, "ref": "node" },
+                    "children": {
+                        "type": "Array",
+                        "required": true,
+                        "model": { "type": "Ref", "required": true, "ref": "node" }
+                    }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_rust(&api_docs, &RustConfig::default()).unwrap(),
+            r#"
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Node {
+    pub children: Vec<Node>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Box<Node>>,
+}
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_rust_extends() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "base_entity": { "id": { "type": "Number", "format": "integer", "required": true } },
+                "user": {
+                    "extends": ["base_entity"],
+                    "fields": { "name": { "type": "String", "required": true } }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_rust(&api_docs, &RustConfig::default()).unwrap(),
+            "
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BaseEntity {
+    pub id: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+}
+
+"
+        );
+    }
+}
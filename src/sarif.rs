@@ -0,0 +1,141 @@
+//! Diagnostics in SARIF, the format code scanning tools take to annotate
+//! pull requests.
+
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::snippets::Snippets;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Renders `diagnostics` about the spec at `uri` as a SARIF log, located in
+/// the spec with `snippets` when possible.
+pub fn sarif_log(diagnostics: &[Diagnostic], uri: &str, snippets: &Snippets) -> serde_json::Value {
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut physical_location = serde_json::json!({
+                "artifactLocation": { "uri": uri }
+            });
+            if let Some((line, column)) = snippets.position(&diagnostic.path) {
+                physical_location["region"] = serde_json::json!({
+                    "startLine": line,
+                    "startColumn": column,
+                });
+            }
+            serde_json::json!({
+                "level": match diagnostic.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                },
+                "message": { "text": format!("{}: {}", diagnostic.path, diagnostic.message) },
+                "locations": [{
+                    "physicalLocation": physical_location,
+                    "logicalLocations": [{ "fullyQualifiedName": diagnostic.path }],
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sarif_log() {
+        let text = indoc::indoc! {r#"
+            {
+              "models": {
+                "user": { "userName": { "type": "String", "required": true } }
+              },
+              "routes": {}
+            }
+        "#};
+        let diagnostics = [
+            Diagnostic::warning("user.userName", "should be snake_case"),
+            Diagnostic::error("user.missing", "not in the spec"),
+        ];
+        let snippets = Snippets::new("spec.json", text);
+        k9::snapshot!(
+            serde_json::to_string_pretty(&sarif_log(&diagnostics, "spec.json", &snippets)).unwrap(),
+            r#"
+{
+  "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+  "runs": [
+    {
+      "results": [
+        {
+          "level": "warning",
+          "locations": [
+            {
+              "logicalLocations": [
+                {
+                  "fullyQualifiedName": "user.userName"
+                }
+              ],
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "spec.json"
+                },
+                "region": {
+                  "startColumn": 15,
+                  "startLine": 3
+                }
+              }
+            }
+          ],
+          "message": {
+            "text": "user.userName: should be snake_case"
+          }
+        },
+        {
+          "level": "error",
+          "locations": [
+            {
+              "logicalLocations": [
+                {
+                  "fullyQualifiedName": "user.missing"
+                }
+              ],
+              "physicalLocation": {
+                "artifactLocation": {
+                  "uri": "spec.json"
+                },
+                "region": {
+                  "startColumn": 5,
+                  "startLine": 3
+                }
+              }
+            }
+          ],
+          "message": {
+            "text": "user.missing: not in the spec"
+          }
+        }
+      ],
+      "tool": {
+        "driver": {
+          "name": "api-generator",
+          "version": "0.1.0"
+        }
+      }
+    }
+  ],
+  "version": "2.1.0"
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Conversion of Smithy models in the JSON AST format into [`ApiDocs`].
+//!
+//! Structures become models and every operation becomes a route. Other
+//! shapes are inlined where used. Unions are serialized as an object with a
+//! single member set, so they become a union of one-field objects.
+//!
+//! TypeSpec can emit OpenAPI, which is imported by [`crate::openapi`].
+
+use std::collections::BTreeMap;
+
+use eyre::Result;
+use serde::Deserialize;
+
+use crate::{
+    ApiDocs, ApiDocsModel, ApiDocsModelDefinition, ApiDocsModelFormat, ApiDocsModelObjectType,
+    ApiDocsRoute,
+};
+
+/// Name of the model used for operations without an input or output.
+const EMPTY_MODEL: &str = "empty";
+
+const PRELUDE: &str = "smithy.api#";
+
+#[derive(Deserialize)]
+struct Smithy {
+    #[serde(default)]
+    shapes: BTreeMap<String, Shape>,
+}
+
+#[derive(Deserialize)]
+struct Shape {
+    r#type: String,
+    #[serde(default)]
+    members: BTreeMap<String, Member>,
+    /// Item of lists and sets
+    member: Option<Member>,
+    /// Value of maps
+    value: Option<Member>,
+    input: Option<Target>,
+    output: Option<Target>,
+    #[serde(default)]
+    traits: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct Member {
+    target: String,
+    #[serde(default)]
+    traits: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct Target {
+    target: String,
+}
+
+pub fn import(document: serde_json::Value) -> Result<ApiDocs> {
+    let document: Smithy = serde_json::from_value(document)?;
+    let importer = Importer {
+        shapes: &document.shapes,
+    };
+
+    let mut models = BTreeMap::new();
+    let mut routes = BTreeMap::new();
+    for (id, shape) in &document.shapes {
+        match shape.r#type.as_str() {
+            "structure" => {
+                let fields = shape
+                    .members
+                    .iter()
+                    .map(|(name, member)| {
+                        let path = format!("{id}${name}");
+                        Ok((
+                            name.clone(),
+                            importer.convert_member(&path, member, &mut Vec::new())?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?;
+                let definition = ApiDocsModelDefinition {
+                    description: documentation(&shape.traits),
+                    fields,
+                    ..Default::default()
+                };
+                models.insert(shape_name(id).to_string(), definition);
+            },
+            "operation" => {
+                let [accepts, returns] = [&shape.input, &shape.output].map(|target| {
+                    target
+                        .as_ref()
+                        .map_or(EMPTY_MODEL, |target| shape_name(&target.target))
+                });
+                if shape.input.is_none() || shape.output.is_none() {
+                    models.entry(EMPTY_MODEL.to_string()).or_default();
+                }
+                routes.insert(
+                    heck::AsSnakeCase(shape_name(id)).to_string(),
+                    ApiDocsRoute {
+                        accepts: accepts.to_string(),
+                        returns: returns.to_string(),
+                        description: documentation(&shape.traits),
+                        ..Default::default()
+                    },
+                );
+            },
+            _ => {},
+        }
+    }
+
+    Ok(ApiDocs {
+        models,
+        routes,
+        sockets: BTreeMap::new(),
+    })
+}
+
+struct Importer<'a> {
+    shapes: &'a BTreeMap<String, Shape>,
+}
+
+impl<'a> Importer<'a> {
+    fn convert_member(
+        &self, path: &str, member: &'a Member, refs: &mut Vec<&'a str>,
+    ) -> Result<ApiDocsModel> {
+        let mut model = self.convert(path, &member.target, refs)?;
+        model.required = member.traits.contains_key("smithy.api#required");
+        model.description = documentation(&member.traits).or(model.description);
+        Ok(model)
+    }
+
+    /// Converts the shape `target` into a required model.
+    fn convert(
+        &self, path: &str, target: &'a str, refs: &mut Vec<&'a str>,
+    ) -> Result<ApiDocsModel> {
+        let mut model = ApiDocsModel {
+            required: true,
+            ..Default::default()
+        };
+
+        let shape = match target.strip_prefix(PRELUDE) {
+            Some(prelude) => {
+                convert_simple(&mut model, &heck::AsLowerCamelCase(prelude).to_string())
+                    .ok_or_else(|| eyre::eyre!("Unsupported shape `{target}` at `{path}`"))?;
+                return Ok(model);
+            },
+            None => self
+                .shapes
+                .get(target)
+                .ok_or_else(|| eyre::eyre!("Unknown shape `{target}` at `{path}`"))?,
+        };
+        model.description = documentation(&shape.traits);
+        if convert_simple(&mut model, &shape.r#type).is_some() {
+            return Ok(model);
+        }
+
+        if refs.contains(&target) {
+            eyre::bail!("Recursive shape `{target}` at `{path}` cannot be inlined");
+        }
+        refs.push(target);
+        let result = self.convert_aggregate(&mut model, path, target, shape, refs);
+        refs.pop();
+        result.map(|()| model)
+    }
+
+    fn convert_aggregate(
+        &self, model: &mut ApiDocsModel, path: &str, target: &'a str, shape: &'a Shape,
+        refs: &mut Vec<&'a str>,
+    ) -> Result<()> {
+        let sparse = shape.traits.contains_key("smithy.api#sparse");
+        match shape.r#type.as_str() {
+            "structure" => {
+                model.r#type = ApiDocsModelObjectType::Ref;
+                model.r#ref = Some(shape_name(target).to_string());
+            },
+            "list" | "set" => {
+                let member = shape
+                    .member
+                    .as_ref()
+                    .ok_or_else(|| eyre::eyre!("List `{target}` at `{path}` has no `member`"))?;
+                let mut item = self.convert(&format!("{path}[]"), &member.target, refs)?;
+                item.nullable = sparse;
+                model.r#type = ApiDocsModelObjectType::Array;
+                model.model = Some(Box::new(item));
+            },
+            "map" => {
+                let value = shape
+                    .value
+                    .as_ref()
+                    .ok_or_else(|| eyre::eyre!("Map `{target}` at `{path}` has no `value`"))?;
+                let mut value = self.convert(&format!("{path}{{}}"), &value.target, refs)?;
+                value.nullable = sparse;
+                model.r#type = ApiDocsModelObjectType::Map;
+                model.values = Some(Box::new(value));
+            },
+            "enum" | "intEnum" => {
+                model.r#type = ApiDocsModelObjectType::Enum;
+                model.members = Some(
+                    shape
+                        .members
+                        .iter()
+                        .map(|(name, member)| {
+                            member
+                                .traits
+                                .get("smithy.api#enumValue")
+                                .cloned()
+                                .unwrap_or_else(|| name.as_str().into())
+                        })
+                        .collect(),
+                );
+            },
+            "union" => {
+                model.r#type = ApiDocsModelObjectType::Union;
+                model.models = Some(
+                    shape
+                        .members
+                        .iter()
+                        .map(|(name, member)| {
+                            let mut field =
+                                self.convert_member(&format!("{path}|{name}"), member, refs)?;
+                            field.required = true;
+                            Ok(ApiDocsModel {
+                                r#type: ApiDocsModelObjectType::Object,
+                                fields: Some([(name.clone(), field)].into()),
+                                required: true,
+                                ..Default::default()
+                            })
+                        })
+                        .collect::<Result<_>>()?,
+                );
+            },
+            r#type => eyre::bail!("Unsupported shape type `{type}` of `{target}` at `{path}`"),
+        }
+        Ok(())
+    }
+}
+
+/// Converts shapes of simple `type`, returning `None` for other shapes.
+fn convert_simple(model: &mut ApiDocsModel, r#type: &str) -> Option<()> {
+    let (r#type, format) = match r#type {
+        "string" | "blob" => (ApiDocsModelObjectType::String, None),
+        "timestamp" => (
+            ApiDocsModelObjectType::String,
+            Some(ApiDocsModelFormat::DateTime),
+        ),
+        "boolean" | "primitiveBoolean" => (ApiDocsModelObjectType::Boolean, None),
+        "byte" | "short" | "integer" | "long" | "bigInteger" | "primitiveByte"
+        | "primitiveShort" | "primitiveInteger" | "primitiveLong" => (
+            ApiDocsModelObjectType::Number,
+            Some(ApiDocsModelFormat::Integer),
+        ),
+        "float" | "double" | "bigDecimal" | "primitiveFloat" | "primitiveDouble" => (
+            ApiDocsModelObjectType::Number,
+            Some(ApiDocsModelFormat::Float),
+        ),
+        _ => return None,
+    };
+    model.r#type = r#type;
+    model.format = format;
+    Some(())
+}
+
+/// Returns the name of the shape `id` without its namespace.
+fn shape_name(id: &str) -> &str {
+    id.rsplit_once('#').map_or(id, |(_, name)| name)
+}
+
+fn documentation(traits: &BTreeMap<String, serde_json::Value>) -> Option<String> {
+    traits
+        .get("smithy.api#documentation")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::render_interfaces;
+
+    #[test]
+    fn test_import_shapes() {
+        let api_docs = import(serde_json::json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example.weather#City": {
+                    "type": "structure",
+                    "members": {
+                        "id": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#required": {} }
+                        },
+                        "population": { "target": "smithy.api#Long" },
+                        "founded": { "target": "smithy.api#Timestamp" },
+                        "climate": { "target": "example.weather#Climate" },
+                        "neighbors": { "target": "example.weather#CityList" },
+                        "location": { "target": "example.weather#Location" }
+                    },
+                    "traits": { "smithy.api#documentation": "A city." }
+                },
+                "example.weather#Climate": {
+                    "type": "enum",
+                    "members": {
+                        "DRY": { "target": "smithy.api#Unit", "traits": { "smithy.api#enumValue": "dry" } },
+                        "WET": { "target": "smithy.api#Unit", "traits": { "smithy.api#enumValue": "wet" } }
+                    }
+                },
+                "example.weather#CityList": {
+                    "type": "list",
+                    "member": { "target": "example.weather#City" }
+                },
+                "example.weather#Location": {
+                    "type": "union",
+                    "members": {
+                        "address": { "target": "smithy.api#String" },
+                        "coordinates": { "target": "example.weather#Coordinates" }
+                    }
+                },
+                "example.weather#Coordinates": {
+                    "type": "map",
+                    "key": { "target": "smithy.api#String" },
+                    "value": { "target": "smithy.api#Double" }
+                },
+                "example.weather#GetCity": {
+                    "type": "operation",
+                    "input": { "target": "example.weather#City" },
+                    "output": { "target": "example.weather#City" }
+                },
+                "example.weather#Ping": { "type": "operation" }
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            render_interfaces(&api_docs.models),
+            r#"
+/** A city. */
+export interface City {
+  climate?: "dry" | "wet" | undefined;
+  founded?: string | undefined;
+  id: string;
+  location?: {
+    address: string;
+  } | {
+    coordinates: Record<string, number>;
+  } | undefined;
+  neighbors?: Array<City> | undefined;
+  population?: number | undefined;
+}
+
+export interface Empty {}
+
+"#
+        );
+        k9::snapshot!(
+            api_docs
+                .routes
+                .iter()
+                .map(|(name, route)| format!("{name}: {} -> {}", route.accepts, route.returns))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "
+get_city: City -> City
+ping: empty -> empty
+"
+        );
+    }
+}
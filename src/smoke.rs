@@ -0,0 +1,321 @@
+//! Generation of a smoke test script, which calls `GET` routes of a deployed
+//! API and checks their responses with the schemas of the Zod target.
+//!
+//! Only `GET` routes are called, as they're safe to call against any
+//! environment. The script takes the base URL of the API as its first
+//! argument, or from `API_BASE_URL`, and exits with an error if any check
+//! failed.
+
+use std::collections::BTreeSet;
+
+use crate::config::{SmokeConfig, SmokeRoute, TypeScriptConfig};
+use crate::diagnostics::Diagnostic;
+use crate::{render_header, ApiDocs, ApiDocsRoute, Method};
+
+/// Checks that the routes of `config` can be called: they exist, are `GET`
+/// routes with a path and have a value for every parameter of their path.
+pub fn check(api_docs: &ApiDocs, config: &SmokeConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (name, smoke_route) in &config.routes {
+        let path = format!("smoke.routes.{name}");
+        let Some(route) = api_docs.routes.get(name) else {
+            diagnostics.push(Diagnostic::error(path, format!("unknown route `{name}`")));
+            continue;
+        };
+        if route.method != Some(Method::Get) {
+            diagnostics.push(Diagnostic::error(
+                path,
+                "only `GET` routes can be smoke tested, as they're safe to call",
+            ));
+            continue;
+        }
+        if route.path.is_none() {
+            diagnostics.push(Diagnostic::error(path, "the route has no `path` to call"));
+            continue;
+        }
+        for param in route.params.keys() {
+            if !smoke_route.params.contains_key(param) {
+                diagnostics.push(Diagnostic::error(
+                    format!("{path}.params"),
+                    format!("missing the value of the parameter `{param}`"),
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Renders the smoke test script for a validated spec, calling the routes of
+/// `config` or, if there are none, every `GET` route that can be called
+/// without any value.
+pub(crate) fn render_smoke(
+    api_docs: &ApiDocs, config: &SmokeConfig, typescript: &TypeScriptConfig,
+) -> String {
+    let default_route = SmokeRoute::default();
+    let routes = api_docs
+        .routes
+        .iter()
+        .filter_map(|(name, route)| match config.routes.get(name) {
+            Some(smoke_route) => Some((name, route, smoke_route)),
+            None if config.routes.is_empty() && is_callable(route) => {
+                Some((name, route, &default_route))
+            },
+            None => None,
+        })
+        .collect::<Vec<_>>();
+
+    let schemas = routes
+        .iter()
+        .map(|(_, route, _)| format!("{}Schema", heck::AsPascalCase(&route.returns)))
+        .collect::<BTreeSet<_>>();
+    let checks = routes
+        .iter()
+        .map(|(name, route, smoke_route)| {
+            format!(
+                "{}{{ route: {}, path: {}, schema: {}Schema }},\n",
+                typescript.indent(1),
+                serde_json::Value::from(name.as_str()),
+                serde_json::Value::from(render_path(route, smoke_route)),
+                heck::AsPascalCase(&route.returns),
+            )
+        })
+        .collect::<String>();
+
+    let indent = |level| typescript.indent(level);
+    let mut output = render_header(typescript);
+    if !schemas.is_empty() {
+        output.push_str(&format!(
+            "import {{ {} }} from {};\n\n",
+            schemas.into_iter().collect::<Vec<_>>().join(", "),
+            serde_json::Value::from(config.schemas.as_str()),
+        ));
+    }
+    output.push_str(&format!(
+        "const baseUrl = process.argv[2] ?? process.env.API_BASE_URL;\n\
+         \n\
+         const checks = [\n\
+         {checks}\
+         ];\n\
+         \n\
+         async function main() {{\n\
+         {i1}if (baseUrl === undefined) {{\n\
+         {i2}console.error(\"usage: smoke <base url>, or set API_BASE_URL\");\n\
+         {i2}process.exitCode = 2;\n\
+         {i2}return;\n\
+         {i1}}}\n\
+         {i1}for (const check of checks) {{\n\
+         {i2}try {{\n\
+         {i3}const response = await fetch(`${{baseUrl.replace(/\\/$/, \"\")}}${{check.path}}`);\n\
+         {i3}if (!response.ok) {{\n\
+         {i4}throw new Error(`status ${{response.status}}`);\n\
+         {i3}}}\n\
+         {i3}check.schema.parse(await response.json());\n\
+         {i3}console.log(`ok ${{check.route}}`);\n\
+         {i2}}} catch (error) {{\n\
+         {i3}console.error(`failed ${{check.route}}: ${{error}}`);\n\
+         {i3}process.exitCode = 1;\n\
+         {i2}}}\n\
+         {i1}}}\n\
+         }}\n\
+         \n\
+         void main();\n",
+        i1 = indent(1),
+        i2 = indent(2),
+        i3 = indent(3),
+        i4 = indent(4),
+    ));
+    output
+}
+
+/// Whether `route` is a `GET` route that can be called without any value.
+fn is_callable(route: &ApiDocsRoute) -> bool {
+    route.method == Some(Method::Get)
+        && route.path.is_some()
+        && route.params.is_empty()
+        && route.query.is_none()
+        && route.headers.is_none()
+}
+
+/// Returns the path of `route` with its parameters interpolated, followed by
+/// the query string.
+fn render_path(route: &ApiDocsRoute, smoke_route: &SmokeRoute) -> String {
+    let mut path = route.path.clone().unwrap_or_default();
+    for (name, value) in &smoke_route.params {
+        path = path.replace(&format!("{{{name}}}"), &encode(value));
+    }
+    let query = smoke_route
+        .query
+        .iter()
+        .flat_map(|(name, value)| {
+            match value {
+                serde_json::Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            }
+            .into_iter()
+            .map(move |value| format!("{}={}", encode_str(name), encode(value)))
+        })
+        .collect::<Vec<_>>();
+    if !query.is_empty() {
+        path.push('?');
+        path.push_str(&query.join("&"));
+    }
+    path
+}
+
+fn encode(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => encode_str(value),
+        value => encode_str(&value.to_string()),
+    }
+}
+
+/// Percent-encodes everything but the unreserved characters of URLs.
+fn encode_str(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            },
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_docs() -> ApiDocs {
+        serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": { "id": { "type": "Number", "required": true } },
+                "user_list": {
+                    "users": {
+                        "type": "Array",
+                        "required": true,
+                        "model": { "type": "Ref", "required": true, "ref": "user" }
+                    }
+                },
+                "search": { "name": { "type": "String", "required": false } },
+                "empty": {}
+            },
+            "routes": {
+                "list_users": {
+                    "accepts": "empty",
+                    "returns": "user_list",
+                    "method": "GET",
+                    "path": "/users"
+                },
+                "get_user": {
+                    "accepts": "empty",
+                    "returns": "user",
+                    "method": "GET",
+                    "path": "/users/{id}",
+                    "params": { "id": { "type": "Number", "required": true } }
+                },
+                "search_users": {
+                    "accepts": "empty",
+                    "returns": "user_list",
+                    "method": "GET",
+                    "path": "/users/search",
+                    "query": "search"
+                },
+                "delete_user": {
+                    "accepts": "user",
+                    "returns": "empty",
+                    "method": "DELETE",
+                    "path": "/users"
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check() {
+        let config: SmokeConfig = serde_json::from_value(serde_json::json!({
+            "routes": {
+                "get_user": {},
+                "delete_user": {},
+                "get_post": {}
+            }
+        }))
+        .unwrap();
+        k9::snapshot!(
+            check(&api_docs(), &config)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            r#"
+error: smoke.routes.delete_user: only `GET` routes can be smoke tested, as they're safe to call
+error: smoke.routes.get_post: unknown route `get_post`
+error: smoke.routes.get_user.params: missing the value of the parameter `id`
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_smoke() {
+        let typescript = TypeScriptConfig::default();
+        k9::snapshot!(
+            render_smoke(&api_docs(), &SmokeConfig::default(), &typescript),
+            r#"
+import { UserListSchema } from "./schemas";
+
+const baseUrl = process.argv[2] ?? process.env.API_BASE_URL;
+
+const checks = [
+  { route: "list_users", path: "/users", schema: UserListSchema },
+];
+
+async function main() {
+  if (baseUrl === undefined) {
+    console.error("usage: smoke <base url>, or set API_BASE_URL");
+    process.exitCode = 2;
+    return;
+  }
+  for (const check of checks) {
+    try {
+      const response = await fetch(`${baseUrl.replace(/\\/$/, "")}${check.path}`);
+      if (!response.ok) {
+        throw new Error(`status ${response.status}`);
+      }
+      check.schema.parse(await response.json());
+      console.log(`ok ${check.route}`);
+    } catch (error) {
+      console.error(`failed ${check.route}: ${error}`);
+      process.exitCode = 1;
+    }
+  }
+}
+
+void main();
+
+"#
+        );
+
+        let config: SmokeConfig = serde_json::from_value(serde_json::json!({
+            "schemas": "./generated/schemas",
+            "routes": {
+                "get_user": { "params": { "id": 1 } },
+                "search_users": { "query": { "name": "Ada L", "tag": ["a", "b"] } }
+            }
+        }))
+        .unwrap();
+        let script = render_smoke(&api_docs(), &config, &typescript);
+        k9::snapshot!(
+            script.lines().take(7).collect::<Vec<_>>().join("\n"),
+            r#"
+import { UserListSchema, UserSchema } from "./generated/schemas";
+
+const baseUrl = process.argv[2] ?? process.env.API_BASE_URL;
+
+const checks = [
+  { route: "get_user", path: "/users/1", schema: UserSchema },
+  { route: "search_users", path: "/users/search?name=Ada%20L&tag=a&tag=b", schema: UserListSchema },
+"#
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! Rendering of diagnostics along with the lines of the spec file they're
+//! about, so that problems can be fixed without searching the spec for
+//! their path.
+//!
+//! Diagnostic paths are resolved against the JSON of the spec as far as they
+//! go, following the different forms models can be written in.
+
+use std::ops::Range;
+
+use crate::diagnostics::{Diagnostic, Diagnostics};
+
+/// The source of a spec, if it's known.
+#[derive(Default)]
+pub struct Snippets {
+    source: Option<Source>,
+}
+
+struct Source {
+    name: String,
+    text: String,
+    root: Node,
+}
+
+/// A JSON value, with the spans of object keys and array items.
+enum Node {
+    Object(Vec<(String, Range<usize>, Node)>),
+    Array(Vec<(Range<usize>, Node)>),
+    Scalar,
+}
+
+enum Segment<'a> {
+    Name(&'a str),
+    /// `[]`
+    Item,
+    /// `[0]`
+    Index(&'a str),
+    /// `{}`
+    Value,
+    /// `|0` or `|tag`
+    Alternative(&'a str),
+}
+
+impl Snippets {
+    /// Snippets from the spec `text` read from the file `name`. Without
+    /// snippets if `text` isn't valid JSON.
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        let text = text.into();
+        let root = Parser {
+            text: text.as_bytes(),
+            pos: 0,
+        }
+        .parse_value()
+        .map(|(node, _)| node);
+        Self {
+            source: root.map(|root| Source {
+                name: name.into(),
+                text,
+                root,
+            }),
+        }
+    }
+
+    /// Renders `diagnostic` followed by the line it's about, if found.
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let snippet = self
+            .source
+            .as_ref()
+            .and_then(|source| source.snippet(&diagnostic.path));
+        format!("{diagnostic}{}", snippet.unwrap_or_default())
+    }
+
+    /// Returns the line and column, both starting at 1, of what `path`
+    /// points at, if found.
+    pub fn position(&self, path: &str) -> Option<(usize, usize)> {
+        let source = self.source.as_ref()?;
+        let span = source.locate(path)?;
+        let (_, number, column) = source.position(span.start);
+        Some((number, column))
+    }
+
+    /// Renders `diagnostics` like their `Display` implementation does, with
+    /// snippets.
+    pub fn render_all(&self, diagnostics: &Diagnostics) -> String {
+        let mut rendered = diagnostics
+            .0
+            .iter()
+            .map(|diagnostic| format!("{}\n", self.render(diagnostic)))
+            .collect::<String>();
+        rendered.push_str(&format!(
+            "found {} problem(s) in the spec",
+            diagnostics.0.len()
+        ));
+        rendered
+    }
+}
+
+impl Source {
+    fn snippet(&self, path: &str) -> Option<String> {
+        let span = self.locate(path)?;
+        let (line_start, number, column) = self.position(span.start);
+        let line_end = self.text[span.start..]
+            .find('\n')
+            .map_or(self.text.len(), |i| span.start + i);
+        let line = &self.text[line_start..line_end];
+
+        // Keep tabs so that the carets line up with the line.
+        let indent = self.text[line_start..span.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let carets = "^".repeat(
+            self.text[span.start..span.end.min(line_end)]
+                .chars()
+                .count(),
+        );
+        let pad = " ".repeat(number.to_string().len());
+        Some(format!(
+            "\n{pad}--> {}:{number}:{column}\n{pad} |\n{number} | {line}\n{pad} | {indent}{carets}",
+            self.name
+        ))
+    }
+
+    /// Returns the start of the line of `offset`, and its line and column.
+    fn position(&self, offset: usize) -> (usize, usize, usize) {
+        let line_start = self.text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let number = self.text[..offset].matches('\n').count() + 1;
+        let column = self.text[line_start..offset].chars().count() + 1;
+        (line_start, number, column)
+    }
+
+    /// Returns the span of the deepest part of `path` found in the spec.
+    fn locate(&self, path: &str) -> Option<Range<usize>> {
+        let mut node = &self.root;
+        let mut span = None;
+        for (index, segment) in parse_path(path).into_iter().enumerate() {
+            let candidates: Vec<Vec<&str>> = match segment {
+                Segment::Name(name) if index == 0 => vec![vec!["models", name], vec![name]],
+                Segment::Name(name) => vec![vec![name], vec!["fields", name]],
+                Segment::Item => vec![vec!["model"]],
+                Segment::Index(index) => vec![vec!["items", index], vec![index]],
+                Segment::Value => vec![vec!["values"]],
+                Segment::Alternative(alternative) => {
+                    vec![vec!["models", alternative], vec!["variants", alternative]]
+                },
+            };
+            let Some((found, found_span)) = candidates.iter().find_map(|keys| {
+                keys.iter().try_fold((node, None), |(node, _), key| {
+                    node.child(key).map(|(span, child)| (child, Some(span)))
+                })
+            }) else {
+                break;
+            };
+            node = found;
+            span = found_span;
+        }
+        span
+    }
+}
+
+impl Node {
+    fn child(&self, key: &str) -> Option<(Range<usize>, &Node)> {
+        match self {
+            Node::Object(members) => members
+                .iter()
+                .find(|(name, _, _)| name == key)
+                .map(|(_, span, node)| (span.clone(), node)),
+            Node::Array(items) => {
+                let (span, node) = items.get(key.parse::<usize>().ok()?)?;
+                Some((span.clone(), node))
+            },
+            Node::Scalar => None,
+        }
+    }
+}
+
+/// Splits a diagnostic path into its segments, e.g. `user`, `tags`, `[]`
+/// for `user.tags[]`.
+fn parse_path(path: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("[]") {
+            segments.push(Segment::Item);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{}") {
+            segments.push(Segment::Value);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').unwrap_or(after.len());
+            segments.push(Segment::Index(&after[..end]));
+            rest = after.get(end + 1..).unwrap_or_default();
+        } else {
+            let alternative = rest.starts_with('|');
+            let after = rest.strip_prefix(['.', '|']).unwrap_or(rest);
+            let end = after.find(['.', '[', '{', '|']).unwrap_or(after.len());
+            let name = &after[..end];
+            segments.push(match alternative {
+                true => Segment::Alternative(name),
+                false => Segment::Name(name),
+            });
+            rest = &after[end..];
+        }
+    }
+    segments
+}
+
+/// Parser of JSON keeping only what's needed to locate paths. Input that
+/// `serde_json` accepted is assumed.
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.text.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.text.get(self.pos) == Some(&byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn parse_value(&mut self) -> Option<(Node, Range<usize>)> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let node = match self.text.get(self.pos)? {
+            b'{' => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                if !self.eat(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key_span = self.parse_string()?;
+                        let key = std::str::from_utf8(&self.text[key_span.clone()]).ok()?;
+                        let key = serde_json::from_str(key).ok()?;
+                        if !self.eat(b':') {
+                            return None;
+                        }
+                        let (value, _) = self.parse_value()?;
+                        members.push((key, key_span, value));
+                        if !self.eat(b',') {
+                            break;
+                        }
+                    }
+                    if !self.eat(b'}') {
+                        return None;
+                    }
+                }
+                Node::Object(members)
+            },
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.eat(b']') {
+                    loop {
+                        let (item, span) = self.parse_value()?;
+                        items.push((span, item));
+                        if !self.eat(b',') {
+                            break;
+                        }
+                    }
+                    if !self.eat(b']') {
+                        return None;
+                    }
+                }
+                Node::Array(items)
+            },
+            b'"' => {
+                self.parse_string()?;
+                Node::Scalar
+            },
+            _ => {
+                while self
+                    .text
+                    .get(self.pos)
+                    .is_some_and(|byte| !b",}] \t\r\n".contains(byte))
+                {
+                    self.pos += 1;
+                }
+                Node::Scalar
+            },
+        };
+        Some((node, start..self.pos))
+    }
+
+    /// Parses a string, returning its span including the quotes.
+    fn parse_string(&mut self) -> Option<Range<usize>> {
+        let start = self.pos;
+        if self.text.get(self.pos) != Some(&b'"') {
+            return None;
+        }
+        self.pos += 1;
+        loop {
+            match self.text.get(self.pos)? {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        Some(start..self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{validate, ApiDocs};
+
+    #[test]
+    fn test_render() {
+        let text = indoc::indoc! {r#"
+            {
+              "models": {
+                "user": {
+                  "address": { "type": "Object", "required": true },
+                  "tags": {
+                    "type": "Array",
+                    "required": true,
+                    "model": { "type": "Enum", "required": true }
+                  }
+                },
+                "post": {
+                  "fields": { "author": { "type": "Ref", "required": true, "ref": "author" } }
+                }
+              },
+              "routes": {
+                "get_post": { "accepts": "post_id", "returns": "post" }
+              }
+            }
+        "#};
+        let api_docs: ApiDocs = serde_json::from_str(text).unwrap();
+        let snippets = Snippets::new("spec.json", text);
+        k9::snapshot!(
+            snippets.render_all(&Diagnostics(validate::validate(&api_docs))),
+            r#"
+error: post.author: unknown model `author`
+  --> spec.json:12:19
+   |
+12 |       "fields": { "author": { "type": "Ref", "required": true, "ref": "author" } }
+   |                   ^^^^^^^^
+error: routes.get_post: unknown model `post_id`
+  --> spec.json:16:5
+   |
+16 |     "get_post": { "accepts": "post_id", "returns": "post" }
+   |     ^^^^^^^^^^
+error: user.address: `fields` must be set for objects
+ --> spec.json:4:7
+  |
+4 |       "address": { "type": "Object", "required": true },
+  |       ^^^^^^^^^
+error: user.tags[]: `members` must be set for enums
+ --> spec.json:8:9
+  |
+8 |         "model": { "type": "Enum", "required": true }
+  |         ^^^^^^^
+found 4 problem(s) in the spec
+"#
+        );
+    }
+}
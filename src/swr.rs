@@ -0,0 +1,232 @@
+//! Generation of SWR hooks: the TypeScript target along with a `useSWR` hook
+//! for every route without a body, e.g. `useGetUser`.
+//!
+//! Keys start with the path of the route, e.g. `/users/{id}`, or its name if
+//! it has no path, followed by its inputs, so that they stay the same across
+//! renders and can be revalidated with `mutate`. Routes of merged services
+//! start with their service, as services may share paths.
+
+use crate::config::TypeScriptConfig;
+use crate::hooks::{hooks_config, render_hooks_file, route_hooks, RouteHook};
+use crate::ApiDocs;
+
+pub(crate) fn render_swr(api_docs: &ApiDocs, config: &TypeScriptConfig) -> String {
+    let config = hooks_config(config);
+    let indent = config.indent(1);
+
+    let hooks = route_hooks(api_docs, &config)
+        .into_iter()
+        .filter(|route_hook| !route_hook.route.has_body())
+        .collect::<Vec<_>>();
+    let rendered = hooks
+        .iter()
+        .map(|route_hook| {
+            let RouteHook {
+                name,
+                route,
+                function,
+                hook,
+                returns,
+                parameters,
+                inputs,
+                doc,
+            } = route_hook;
+            let key = route
+                .service
+                .iter()
+                .map(String::as_str)
+                .chain([route.path.as_deref().unwrap_or(name)])
+                .map(|part| serde_json::Value::from(part).to_string())
+                .chain(inputs.iter().cloned())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut hook_parameters = parameters.clone();
+            hook_parameters.push(format!("config?: SWRConfiguration<{returns}>"));
+            format!(
+                "export function {function}Key({}) {{\n\
+                 {indent}return [{key}] as const;\n\
+                 }}\n\
+                 \n\
+                 {doc}export function use{hook}({}) {{\n\
+                 {indent}const request = useApiRequest();\n\
+                 {indent}return useSWR({function}Key({}), () => {}, config);\n\
+                 }}\n",
+                parameters.join(", "),
+                hook_parameters.join(", "),
+                inputs.join(", "),
+                route_hook.render_call(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let imports = if hooks.is_empty() {
+        String::new()
+    } else {
+        "import useSWR, { type SWRConfiguration } from \"swr\";\n".to_string()
+    };
+    render_hooks_file(api_docs, &config, &imports, &rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_swr() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": { "id": { "type": "Number", "required": true } },
+                "new_user": { "name": { "type": "String", "required": true } },
+                "user_filter": { "name": { "type": "String", "required": false } },
+                "empty": {}
+            },
+            "routes": {
+                "get_user": {
+                    "accepts": "empty",
+                    "returns": "user",
+                    "method": "GET",
+                    "path": "/users/{id}",
+                    "params": { "id": { "type": "Number", "required": true } },
+                    "query": "user_filter",
+                    "description": "Returns a user."
+                },
+                "get_me": {
+                    "accepts": "empty",
+                    "returns": "user",
+                    "method": "GET",
+                    "path": "/me",
+                    "service": "identity"
+                },
+                "create_user": {
+                    "accepts": "new_user",
+                    "returns": "user",
+                    "method": "POST",
+                    "path": "/users"
+                }
+            }
+        }))
+        .unwrap();
+        let output = render_swr(&api_docs, &TypeScriptConfig::default());
+        k9::snapshot!(
+            output,
+            r#"
+import { createContext, useContext } from "react";
+import useSWR, { type SWRConfiguration } from "swr";
+
+export interface Empty {}
+
+export interface NewUser {
+  name: string;
+}
+
+export interface User {
+  id: number;
+}
+
+export interface UserFilter {
+  name?: string | undefined;
+}
+
+export type ApiRequest = (
+  route: string,
+  body: unknown,
+  options?: {
+    service?: "identity";
+    method?:
+      | "GET"
+      | "HEAD"
+      | "POST"
+      | "PUT"
+      | "PATCH"
+      | "DELETE"
+      | "OPTIONS"
+      | "TRACE";
+    path?: string;
+    query?: string;
+  },
+) => Promise<unknown>;
+
+function serializeQuery(query: object): string {
+  const search = new URLSearchParams();
+  for (const [key, value] of Object.entries(query)) {
+    for (const item of Array.isArray(value) ? value : [value]) {
+      if (item !== undefined && item !== null) {
+        search.append(key, String(item));
+      }
+    }
+  }
+  const serialized = search.toString();
+  return serialized === "" ? "" : `?${serialized}`;
+}
+
+export function createUser(request: ApiRequest, body: NewUser): Promise<User> {
+  return request("create_user", body, {
+    method: "POST",
+    path: `/users`,
+  }) as Promise<User>;
+}
+
+export function getMe(request: ApiRequest): Promise<User> {
+  return request("get_me", undefined, {
+    service: "identity",
+    method: "GET",
+    path: `/me`,
+  }) as Promise<User>;
+}
+
+/** Returns a user. */
+export function getUser(
+  request: ApiRequest,
+  params: { id: number },
+  query: UserFilter,
+): Promise<User> {
+  return request("get_user", undefined, {
+    method: "GET",
+    path: `/users/${encodeURIComponent(params.id)}`,
+    query: serializeQuery(query),
+  }) as Promise<User>;
+}
+
+export const api = {
+  identity: {
+    getMe: getMe,
+  },
+};
+
+const ApiRequestContext = createContext<ApiRequest | undefined>(undefined);
+
+/** Provides the transport the hooks send requests with. */
+export const ApiRequestProvider = ApiRequestContext.Provider;
+
+function useApiRequest(): ApiRequest {
+  const request = useContext(ApiRequestContext);
+  if (request === undefined) {
+    throw new Error("API hooks must be used inside of an `ApiRequestProvider`");
+  }
+  return request;
+}
+
+export function getMeKey() {
+  return ["identity", "/me"] as const;
+}
+
+export function useGetMe(config?: SWRConfiguration<User>) {
+  const request = useApiRequest();
+  return useSWR(getMeKey(), () => getMe(request), config);
+}
+
+export function getUserKey(params: { id: number }, query: UserFilter) {
+  return ["/users/{id}", params, query] as const;
+}
+
+/** Returns a user. */
+export function useGetUser(params: { id: number }, query: UserFilter, config?: SWRConfiguration<User>) {
+  const request = useApiRequest();
+  return useSWR(getUserKey(params, query), () => getUser(request, params, query), config);
+}
+
+"#
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Timings of the phases of a generation, to find out where time goes on
+//! large specs. Phases may run concurrently on several threads.
+
+use std::sync::Mutex;
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+pub struct Timings {
+    start: Instant,
+    spans: Mutex<Vec<Span>>,
+}
+
+struct Span {
+    name: String,
+    thread: ThreadId,
+    /// Since the creation of the `Timings`
+    start: Duration,
+    duration: Duration,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            spans: Mutex::default(),
+        }
+    }
+}
+
+impl Timings {
+    /// Runs `f` as the phase `name`.
+    pub fn time<T>(&self, name: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let span = Span {
+            name: name.into(),
+            thread: std::thread::current().id(),
+            start: start - self.start,
+            duration: start.elapsed(),
+        };
+        self.spans.lock().unwrap().push(span);
+        result
+    }
+
+    /// Renders the duration of every phase in the order they ended, one per
+    /// line.
+    pub fn report(&self) -> String {
+        let spans = self.spans.lock().unwrap();
+        let width = spans.iter().map(|span| span.name.len()).max().unwrap_or(0);
+        spans
+            .iter()
+            .map(|span| {
+                format!(
+                    "{:width$}  {:>10.3}ms\n",
+                    span.name,
+                    span.duration.as_secs_f64() * 1000.0
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the phases in the Trace Event Format, which `chrome://tracing`
+    /// and Perfetto open.
+    pub fn chrome_trace(&self) -> serde_json::Value {
+        let spans = self.spans.lock().unwrap();
+        let mut threads = Vec::new();
+        let events = spans
+            .iter()
+            .map(|span| {
+                let tid = match threads.iter().position(|thread| *thread == span.thread) {
+                    Some(tid) => tid,
+                    None => {
+                        threads.push(span.thread);
+                        threads.len() - 1
+                    },
+                };
+                serde_json::json!({
+                    "name": span.name,
+                    "ph": "X",
+                    "ts": span.start.as_micros() as u64,
+                    "dur": span.duration.as_micros() as u64,
+                    "pid": 0,
+                    "tid": tid,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({ "traceEvents": events })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chrome_trace() {
+        let timings = Timings::default();
+        assert_eq!(timings.time("parse", || 42), 42);
+        std::thread::scope(|scope| {
+            scope.spawn(|| timings.time("render", || {}));
+        });
+        let trace = timings.chrome_trace();
+        let events = trace["traceEvents"].as_array().unwrap();
+        k9::snapshot!(
+            events
+                .iter()
+                .map(|event| format!("{} {} {}", event["name"], event["ph"], event["tid"]))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            r#"
+"parse" "X" 0
+"render" "X" 1
+"#
+        );
+    }
+}
@@ -0,0 +1,1358 @@
+//! Validation of specs before rendering. Renderers rely on the invariants
+//! checked here.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::diagnostics::Diagnostic;
+use crate::{
+    flatten_extends, is_identifier, par_flat_map, ApiDocs, ApiDocsModel, ApiDocsModelDefinition,
+    ApiDocsModelFormat, ApiDocsModelObject, ApiDocsModelObjectType, ApiDocsRoute, ContentType,
+    Pagination, PATH_PARAM,
+};
+
+/// Validates the whole spec, with models validated in parallel. Diagnostics
+/// are sorted by path.
+pub fn validate(api_docs: &ApiDocs) -> Vec<Diagnostic> {
+    let flattened = flatten_extends(&api_docs.models);
+    let models = api_docs.models.iter().collect::<Vec<_>>();
+    let mut diagnostics = par_flat_map(&models, |(name, model)| {
+        let mut validator = Validator {
+            api_docs,
+            diagnostics: Vec::new(),
+        };
+        validator.validate_fields(name, &model.fields);
+        validator.validate_extends(name, model);
+        if contains_itself(&flattened, name) {
+            validator.error(
+                name,
+                "contains itself through required fields; make one of them optional, nullable \
+                 or an array",
+            );
+        }
+        validator.diagnostics
+    });
+
+    let mut validator = Validator {
+        api_docs,
+        diagnostics: Vec::new(),
+    };
+    for (name, route) in &api_docs.routes {
+        let path = format!("routes.{name}");
+        for model in [&route.accepts, &route.returns] {
+            validator.validate_model_name(&path, model);
+        }
+        validator.validate_route_path(&path, route);
+        if let Some(method) = route.method.filter(|method| !method.has_body()) {
+            let accepts = api_docs.models.get(&route.accepts);
+            if accepts.is_some_and(|model| !model.fields.is_empty() || !model.extends.is_empty()) {
+                validator.error(
+                    &format!("{path}.accepts"),
+                    format!(
+                        "`{}` requests can't have a body; move the fields of `{}` to `query` \
+                         or `params`",
+                        method.as_str(),
+                        route.accepts
+                    ),
+                );
+            }
+        }
+        let accepts = flattened.get(&route.accepts);
+        if route.content_type != Some(ContentType::Multipart)
+            && accepts.is_some_and(|model| model.fields.values().any(is_file))
+        {
+            validator.error(
+                &format!("{path}.accepts"),
+                format!(
+                    "`{}` has files, which can only be sent with the `multipart/form-data` \
+                     content type",
+                    route.accepts
+                ),
+            );
+        }
+        for (index, tag) in route.tags.iter().enumerate() {
+            let path = format!("{path}.tags[{index}]");
+            if tag.trim().is_empty() {
+                validator.error(&path, "must not be empty");
+            } else if route.tags[..index].contains(tag) {
+                validator.error(&path, format!("repeats `{tag}`"));
+            }
+        }
+        for (index, scope) in route.scopes.iter().enumerate() {
+            let path = format!("{path}.scopes[{index}]");
+            if scope.trim().is_empty() {
+                validator.error(&path, "must not be empty");
+            } else if route.scopes[..index].contains(scope) {
+                validator.error(&path, format!("repeats `{scope}`"));
+            }
+        }
+        for (status, model) in &route.errors {
+            let path = format!("{path}.errors.{status}");
+            if !status
+                .parse()
+                .is_ok_and(|status: u16| (400..600).contains(&status))
+            {
+                validator.error(&path, "must be an error status code, from 400 to 599");
+            }
+            validator.validate_model_name(&path, model);
+        }
+        if let Some(query) = &route.query {
+            validator.validate_parameters(&format!("{path}.query"), query, &flattened, true);
+        }
+        if let Some(headers) = &route.headers {
+            validator.validate_parameters(&format!("{path}.headers"), headers, &flattened, false);
+        }
+        if let Some(pagination) = &route.pagination {
+            validator.validate_pagination(
+                &format!("{path}.pagination"),
+                route,
+                pagination,
+                &flattened,
+            );
+        }
+    }
+    for (name, socket) in &api_docs.sockets {
+        let path = format!("sockets.{name}");
+        if !socket.path.starts_with('/') {
+            validator.error(&format!("{path}.path"), "must start with `/`");
+        }
+        for (direction, messages) in [("sends", &socket.sends), ("receives", &socket.receives)] {
+            for (r#type, model) in messages {
+                validator.validate_model_name(&format!("{path}.{direction}.{type}"), model);
+            }
+        }
+    }
+    validator.validate_route_conflicts();
+    diagnostics.append(&mut validator.diagnostics);
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+    diagnostics
+}
+
+struct Validator<'a> {
+    api_docs: &'a ApiDocs,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic::error(path, message));
+    }
+
+    fn validate_fields(&mut self, path: &str, fields: &ApiDocsModelObject) {
+        for (name, model) in fields {
+            self.validate_model(&format!("{path}.{name}"), model);
+        }
+    }
+
+    fn validate_model(&mut self, path: &str, model: &ApiDocsModel) {
+        let properties = [
+            (
+                "fields",
+                model.fields.is_some(),
+                ApiDocsModelObjectType::Object,
+                "objects",
+            ),
+            (
+                "model",
+                model.model.is_some(),
+                ApiDocsModelObjectType::Array,
+                "arrays",
+            ),
+            (
+                "values",
+                model.values.is_some(),
+                ApiDocsModelObjectType::Map,
+                "maps",
+            ),
+            (
+                "items",
+                model.items.is_some(),
+                ApiDocsModelObjectType::Tuple,
+                "tuples",
+            ),
+            (
+                "members",
+                model.members.is_some(),
+                ApiDocsModelObjectType::Enum,
+                "enums",
+            ),
+            (
+                "value",
+                model.value.is_some(),
+                ApiDocsModelObjectType::Literal,
+                "literals",
+            ),
+            (
+                "ref",
+                model.r#ref.is_some(),
+                ApiDocsModelObjectType::Ref,
+                "refs",
+            ),
+            (
+                "models",
+                model.models.is_some(),
+                ApiDocsModelObjectType::Union,
+                "unions",
+            ),
+            (
+                "discriminator",
+                model.discriminator.is_some(),
+                ApiDocsModelObjectType::Union,
+                "unions",
+            ),
+            (
+                "variants",
+                model.variants.is_some(),
+                ApiDocsModelObjectType::Union,
+                "unions",
+            ),
+        ];
+        self.validate_constraints(path, model);
+        for (property, value) in [("default", &model.default), ("example", &model.example)] {
+            if let Some(value) = value {
+                if !is_valid_default(model, value) {
+                    self.error(
+                        path,
+                        format!(
+                            "`{property}` must be a valid `{:?}`, found `{value}`",
+                            model.r#type
+                        ),
+                    );
+                }
+            }
+        }
+        if model.read_only && model.write_only {
+            self.error(path, "`read_only` and `write_only` can't both be set");
+        }
+        if model
+            .feature
+            .as_deref()
+            .is_some_and(|feature| feature.trim().is_empty())
+        {
+            self.error(path, "`feature` must not be empty");
+        }
+        if let Some(format) = model.format {
+            let (owner, owners) = match format {
+                ApiDocsModelFormat::Integer | ApiDocsModelFormat::Float => {
+                    (ApiDocsModelObjectType::Number, "numbers")
+                },
+                ApiDocsModelFormat::Date
+                | ApiDocsModelFormat::DateTime
+                | ApiDocsModelFormat::Time
+                | ApiDocsModelFormat::Uuid
+                | ApiDocsModelFormat::Email
+                | ApiDocsModelFormat::Uri => (ApiDocsModelObjectType::String, "strings"),
+            };
+            if model.r#type != owner {
+                self.error(
+                    path,
+                    format!(
+                        "`{}` format is only allowed for {owners}",
+                        heck::AsKebabCase(format!("{format:?}"))
+                    ),
+                );
+            }
+        }
+
+        for (property, is_set, owner, owners) in properties {
+            if is_set && model.r#type != owner {
+                self.error(
+                    path,
+                    format!(
+                        "`{property}` is only allowed for {owners}, not `{:?}`",
+                        model.r#type
+                    ),
+                );
+            }
+        }
+
+        match model.r#type {
+            ApiDocsModelObjectType::Object => match &model.fields {
+                Some(fields) => {
+                    for (name, field) in fields {
+                        if field.read_only || field.write_only {
+                            self.error(
+                                &format!("{path}.{name}"),
+                                "only fields of models can be read-only or write-only",
+                            );
+                        }
+                        if field.feature.is_some() {
+                            self.error(
+                                &format!("{path}.{name}"),
+                                "only fields of models can be behind a feature flag",
+                            );
+                        }
+                    }
+                    self.validate_fields(path, fields)
+                },
+                None => self.error(path, "`fields` must be set for objects"),
+            },
+            ApiDocsModelObjectType::Map => match &model.values {
+                Some(values) => self.validate_model(&format!("{path}{{}}"), values),
+                None => self.error(path, "`values` must be set for maps"),
+            },
+            ApiDocsModelObjectType::Tuple => match &model.items {
+                Some(items) if items.is_empty() => self.error(path, "`items` must not be empty"),
+                Some(items) => {
+                    for (index, item) in items.iter().enumerate() {
+                        self.validate_model(&format!("{path}[{index}]"), item);
+                    }
+                },
+                None => self.error(path, "`items` must be set for tuples"),
+            },
+            ApiDocsModelObjectType::Array => match &model.model {
+                Some(item) => self.validate_model(&format!("{path}[]"), item),
+                None => self.error(path, "`model` must be set for arrays"),
+            },
+            ApiDocsModelObjectType::Enum => match &model.members {
+                Some(members) if members.is_empty() => {
+                    self.error(path, "`members` must not be empty")
+                },
+                Some(members) => {
+                    for member in members {
+                        if !(member.is_string() || member.is_number() || member.is_boolean()) {
+                            self.error(
+                                path,
+                                format!(
+                                    "enum members must be strings, numbers or booleans, found \
+                                     `{member}`"
+                                ),
+                            );
+                        }
+                    }
+                },
+                None => self.error(path, "`members` must be set for enums"),
+            },
+            ApiDocsModelObjectType::Literal => match &model.value {
+                Some(value) if !(value.is_string() || value.is_number() || value.is_boolean()) => {
+                    self.error(
+                        path,
+                        format!("literals must be strings, numbers or booleans, found `{value}`"),
+                    )
+                },
+                Some(_) => {},
+                None => self.error(path, "`value` must be set for literals"),
+            },
+            ApiDocsModelObjectType::Ref => match &model.r#ref {
+                Some(name) => self.validate_model_name(path, name),
+                None => self.error(path, "`ref` must be set for refs"),
+            },
+            ApiDocsModelObjectType::Union => match &model.discriminator {
+                Some(discriminator) => self.validate_variants(path, discriminator, model),
+                None => match &model.models {
+                    Some(models) if models.is_empty() => {
+                        self.error(path, "`models` must not be empty")
+                    },
+                    Some(models) => {
+                        for (index, model) in models.iter().enumerate() {
+                            self.validate_model(&format!("{path}|{index}"), model);
+                        }
+                    },
+                    None if model.variants.is_some() => {
+                        self.error(path, "`variants` must be used with `discriminator`")
+                    },
+                    None => self.error(path, "`models` must be set for unions"),
+                },
+            },
+            ApiDocsModelObjectType::String
+            | ApiDocsModelObjectType::Number
+            | ApiDocsModelObjectType::Boolean
+            | ApiDocsModelObjectType::File => {},
+        }
+    }
+
+    fn validate_constraints(&mut self, path: &str, model: &ApiDocsModel) {
+        use ApiDocsModelObjectType::{Array, Number, String};
+
+        let constraints = [
+            (
+                "min_length",
+                model.min_length.is_some(),
+                &[String, Array][..],
+                "strings and arrays",
+            ),
+            (
+                "max_length",
+                model.max_length.is_some(),
+                &[String, Array],
+                "strings and arrays",
+            ),
+            ("minimum", model.minimum.is_some(), &[Number], "numbers"),
+            ("maximum", model.maximum.is_some(), &[Number], "numbers"),
+            ("pattern", model.pattern.is_some(), &[String], "strings"),
+        ];
+        for (constraint, is_set, owners, owners_name) in constraints {
+            if is_set && !owners.contains(&model.r#type) {
+                self.error(
+                    path,
+                    format!("`{constraint}` is only allowed for {owners_name}"),
+                );
+            }
+        }
+
+        if let (Some(min), Some(max)) = (model.min_length, model.max_length) {
+            if min > max {
+                self.error(path, "`min_length` is greater than `max_length`");
+            }
+        }
+        if let (Some(min), Some(max)) = (model.minimum, model.maximum) {
+            if min > max {
+                self.error(path, "`minimum` is greater than `maximum`");
+            }
+        }
+        if let Some(Err(_)) = model.pattern.as_deref().map(regex::Regex::new) {
+            self.error(path, "`pattern` is not a valid regular expression");
+        }
+    }
+
+    fn validate_variants(&mut self, path: &str, discriminator: &str, model: &ApiDocsModel) {
+        if model.models.is_some() {
+            self.error(
+                path,
+                "`models` can't be used with `discriminator`, use `variants` instead",
+            );
+        }
+        let variants = match &model.variants {
+            Some(variants) if variants.is_empty() => {
+                return self.error(path, "`variants` must not be empty")
+            },
+            Some(variants) => variants,
+            None => return self.error(path, "`variants` must be set for discriminated unions"),
+        };
+
+        for (tag, variant) in variants {
+            let path = format!("{path}|{tag}");
+            let fields = match variant.r#type {
+                ApiDocsModelObjectType::Object => variant.fields.as_ref(),
+                ApiDocsModelObjectType::Ref => variant
+                    .r#ref
+                    .as_ref()
+                    .and_then(|name| self.api_docs.models.get(name))
+                    .map(|definition| &definition.fields),
+                _ => {
+                    self.error(&path, "variants must be objects or refs");
+                    continue;
+                },
+            };
+            if !variant.required {
+                self.error(&path, "variants must be required");
+            }
+            if fields.is_some_and(|fields| fields.contains_key(discriminator)) {
+                self.error(
+                    &path,
+                    format!("field `{discriminator}` clashes with the discriminator"),
+                );
+            }
+            self.validate_model(&path, variant);
+        }
+    }
+
+    fn validate_extends(&mut self, name: &str, model: &ApiDocsModelDefinition) {
+        for (index, parent) in model.extends.iter().enumerate() {
+            self.validate_model_name(&format!("{name}.extends[{index}]"), parent);
+        }
+        let ancestors = ancestors(self.api_docs, name);
+        if ancestors.contains(&name) {
+            self.error(name, "extends itself");
+            return;
+        }
+        for field in model.fields.keys() {
+            if let Some(ancestor) = ancestors
+                .iter()
+                .find(|ancestor| self.api_docs.models[**ancestor].fields.contains_key(field))
+            {
+                self.error(
+                    &format!("{name}.{field}"),
+                    format!("redefines the field inherited from `{ancestor}`"),
+                );
+            }
+        }
+    }
+
+    fn validate_route_path(&mut self, path: &str, route: &ApiDocsRoute) {
+        let params_path = format!("{path}.params");
+        for (name, param) in &route.params {
+            let path = format!("{params_path}.{name}");
+            self.validate_model(&path, param);
+            if !is_scalar(param.r#type) || !param.required || param.nullable {
+                self.error(
+                    &path,
+                    "path parameters must be required strings, numbers, booleans, enums or \
+                     literals",
+                );
+            }
+        }
+
+        let Some(route_path) = &route.path else {
+            if !route.params.is_empty() {
+                self.error(&params_path, "`params` can only be set along with `path`");
+            }
+            return;
+        };
+        let path = format!("{path}.path");
+        if !route_path.starts_with('/') {
+            self.error(&path, "must start with `/`");
+        }
+        let path_param = regex::Regex::new(PATH_PARAM).expect("valid pattern");
+        let used = path_param
+            .captures_iter(route_path)
+            .map(|captures| captures.get(1).map_or("", |name| name.as_str()))
+            .collect::<Vec<_>>();
+        let unmatched = path_param.replace_all(route_path, "");
+        if unmatched.contains(['{', '}']) {
+            self.error(&path, "has unbalanced `{` or `}`");
+        }
+        for (index, name) in used.iter().enumerate() {
+            if name.is_empty() {
+                self.error(&path, "has a parameter without a name");
+            } else if used[..index].contains(name) {
+                self.error(&path, format!("uses the parameter `{name}` more than once"));
+            } else if !route.params.contains_key(*name) {
+                self.error(&path, format!("unknown parameter `{name}`"));
+            } else if !is_identifier(name) {
+                self.error(
+                    &path,
+                    format!("parameter `{name}` must be a valid identifier"),
+                );
+            }
+        }
+        for name in route.params.keys() {
+            if !used.contains(&name.as_str()) {
+                self.error(&format!("{params_path}.{name}"), "not used in `path`");
+            }
+        }
+    }
+
+    /// Checks that no two routes with the same method have paths matching the
+    /// same URLs, whether their templates are the same or they overlap, like
+    /// `/users/{id}` and `/users/me`. Routes without a method can be sent
+    /// with any.
+    fn validate_route_conflicts(&mut self) {
+        let routes = self
+            .api_docs
+            .routes
+            .iter()
+            .filter_map(|(name, route)| Some((name, route, route.path.as_deref()?)))
+            .collect::<Vec<_>>();
+        for (index, (name, route, route_path)) in routes.iter().enumerate() {
+            for (other_name, other, other_path) in &routes[..index] {
+                let same_method = route.method.is_none()
+                    || other.method.is_none()
+                    || route.method == other.method;
+                // Services are served from their own base URL.
+                if !same_method || route.service != other.service {
+                    continue;
+                }
+                let Some(example) = path_overlap(route_path, other_path) else {
+                    continue;
+                };
+                let path = format!("routes.{name}.path");
+                if example == normalize_path(route_path) && example == normalize_path(other_path) {
+                    self.error(&path, format!("is also the path of `routes.{other_name}`"));
+                } else {
+                    self.error(
+                        &path,
+                        format!(
+                            "overlaps with `{other_path}` of `routes.{other_name}`; both match \
+                             `{example}`"
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks that the fields of the query or headers model `name` can be
+    /// written as text, as can arrays of them in queries.
+    fn validate_parameters(
+        &mut self, path: &str, name: &str, models: &BTreeMap<String, ApiDocsModelDefinition>,
+        in_query: bool,
+    ) {
+        let Some(model) = models.get(name) else {
+            self.validate_model_name(path, name);
+            return;
+        };
+        for (field_name, field) in &model.fields {
+            let item = match field.r#type {
+                ApiDocsModelObjectType::Array if in_query => field.model.as_deref(),
+                _ => Some(field),
+            };
+            if item.is_some_and(|item| is_scalar(item.r#type)) {
+                continue;
+            }
+            let message = if in_query {
+                "query parameters must be strings, numbers, booleans, enums, literals or arrays \
+                 of them"
+            } else {
+                "headers must be strings, numbers, booleans, enums or literals"
+            };
+            self.error(&format!("{path}.{field_name}"), message);
+        }
+    }
+
+    fn validate_pagination(
+        &mut self, path: &str, route: &ApiDocsRoute, pagination: &Pagination,
+        models: &BTreeMap<String, ApiDocsModelDefinition>,
+    ) {
+        let Some(query) = &route.query else {
+            self.error(
+                path,
+                "needs the route to have a `query` to request pages with",
+            );
+            return;
+        };
+        match pagination {
+            Pagination::Cursor {
+                cursor,
+                next_cursor,
+            } => {
+                let cursor_path = format!("{path}.cursor");
+                if self
+                    .field(&cursor_path, models, query, cursor)
+                    .is_some_and(|field| field.required)
+                {
+                    self.error(
+                        &cursor_path,
+                        format!(
+                            "`{cursor}` of `{query}` must be optional, as the first page is \
+                             requested without it"
+                        ),
+                    );
+                }
+                let next_cursor_path = format!("{path}.next_cursor");
+                self.field(&next_cursor_path, models, &route.returns, next_cursor);
+            },
+            Pagination::Offset { offset, items } => {
+                let offset_path = format!("{path}.offset");
+                if self
+                    .field(&offset_path, models, query, offset)
+                    .is_some_and(|field| field.r#type != ApiDocsModelObjectType::Number)
+                {
+                    self.error(
+                        &offset_path,
+                        format!("`{offset}` of `{query}` must be a number"),
+                    );
+                }
+                let items_path = format!("{path}.items");
+                if self
+                    .field(&items_path, models, &route.returns, items)
+                    .is_some_and(|field| field.r#type != ApiDocsModelObjectType::Array)
+                {
+                    self.error(
+                        &items_path,
+                        format!("`{items}` of `{}` must be an array", route.returns),
+                    );
+                }
+            },
+        }
+    }
+
+    /// Returns the field `name` of `model`, reporting it at `path` if
+    /// missing. Unknown models are reported elsewhere.
+    fn field<'m>(
+        &mut self, path: &str, models: &'m BTreeMap<String, ApiDocsModelDefinition>, model: &str,
+        name: &str,
+    ) -> Option<&'m ApiDocsModel> {
+        let field = models.get(model)?.fields.get(name);
+        if field.is_none() {
+            self.error(path, format!("`{model}` has no field `{name}`"));
+        }
+        field
+    }
+
+    fn validate_model_name(&mut self, path: &str, name: &str) {
+        if !self.api_docs.models.contains_key(name) {
+            self.error(path, format!("unknown model `{name}`"));
+        }
+    }
+}
+
+/// Replaces the names of the parameters of a path template with `{}`, and
+/// removes its trailing slash.
+fn normalize_path(path: &str) -> String {
+    let path_param = regex::Regex::new(PATH_PARAM).expect("valid pattern");
+    let normalized = path_param.replace_all(path, "{}");
+    match normalized.strip_suffix('/') {
+        Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+        _ => normalized.into_owned(),
+    }
+}
+
+/// Returns a path matched by both path templates, if any, keeping their
+/// parameters where both have one.
+fn path_overlap(a: &str, b: &str) -> Option<String> {
+    let (a, b) = (normalize_path(a), normalize_path(b));
+    let (a, b) = (
+        a.split('/').collect::<Vec<_>>(),
+        b.split('/').collect::<Vec<_>>(),
+    );
+    if a.len() != b.len() {
+        return None;
+    }
+    let matches = |template: &str, segment: &str| {
+        let pattern = regex::escape(template).replace(r"\{\}", "[^/]+");
+        regex::Regex::new(&format!("^{pattern}$")).is_ok_and(|pattern| pattern.is_match(segment))
+    };
+    let segments = a
+        .iter()
+        .zip(&b)
+        .map(|(a, b)| match (a.contains("{}"), b.contains("{}")) {
+            _ if a == b => Some(*a),
+            (false, false) => None,
+            (true, false) => matches(a, b).then_some(*b),
+            (false, true) => matches(b, a).then_some(*a),
+            // Segments with parameters at different places, like `{}.json`
+            // and `v{}`, can both match some segments.
+            (true, true) => Some(if *a == "{}" { *b } else { *a }),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(segments.join("/"))
+}
+
+/// Whether `model` is a file or an array of files, as fields of multipart
+/// bodies can be.
+fn is_file(model: &ApiDocsModel) -> bool {
+    match model.r#type {
+        ApiDocsModelObjectType::File => true,
+        ApiDocsModelObjectType::Array => model.model.as_deref().is_some_and(is_file),
+        _ => false,
+    }
+}
+
+/// Whether models of `type` can be written in a URL.
+fn is_scalar(r#type: ApiDocsModelObjectType) -> bool {
+    matches!(
+        r#type,
+        ApiDocsModelObjectType::String
+            | ApiDocsModelObjectType::Number
+            | ApiDocsModelObjectType::Boolean
+            | ApiDocsModelObjectType::Enum
+            | ApiDocsModelObjectType::Literal
+    )
+}
+
+/// Returns the known models the model `name` inherits from, directly or
+/// through other models.
+pub(crate) fn ancestors<'a>(api_docs: &'a ApiDocs, name: &str) -> Vec<&'a str> {
+    let mut ancestors = Vec::new();
+    let mut pending = api_docs.models[name]
+        .extends
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    while let Some(next) = pending.pop() {
+        let Some(model) = api_docs.models.get(next) else {
+            continue;
+        };
+        if !ancestors.contains(&next) {
+            ancestors.push(next);
+            pending.extend(model.extends.iter().map(String::as_str));
+        }
+    }
+    ancestors
+}
+
+/// Checks whether a value of the model `name` always contains another one,
+/// which no finite value can. `models` have their inherited fields.
+fn contains_itself(models: &BTreeMap<String, ApiDocsModelDefinition>, name: &str) -> bool {
+    let mut pending = Vec::new();
+    for field in models[name].fields.values() {
+        collect_required_refs(field, &mut pending);
+    }
+    let mut visited = BTreeSet::new();
+    while let Some(next) = pending.pop() {
+        if next == name {
+            return true;
+        }
+        if !visited.insert(next) {
+            continue;
+        }
+        for field in models.get(next).into_iter().flat_map(|m| m.fields.values()) {
+            collect_required_refs(field, &mut pending);
+        }
+    }
+    false
+}
+
+/// Collects the models that are always present in a value of `model`.
+/// Arrays, maps and unions can hold no value of a model, so aren't followed.
+fn collect_required_refs<'a>(model: &'a ApiDocsModel, refs: &mut Vec<&'a str>) {
+    if !model.required || model.nullable {
+        return;
+    }
+    refs.extend(model.r#ref.as_deref());
+    for item in model.items.iter().flatten() {
+        collect_required_refs(item, refs);
+    }
+    for field in model.fields.iter().flat_map(|fields| fields.values()) {
+        collect_required_refs(field, refs);
+    }
+}
+
+/// Checks the JSON type of `default`. The values of refs and unions, and
+/// the items and fields of arrays and objects aren't checked.
+fn is_valid_default(model: &ApiDocsModel, default: &serde_json::Value) -> bool {
+    if default.is_null() {
+        return model.nullable;
+    }
+    match model.r#type {
+        ApiDocsModelObjectType::String => default.is_string(),
+        ApiDocsModelObjectType::Number => match model.format {
+            Some(ApiDocsModelFormat::Integer) => default.is_i64() || default.is_u64(),
+            _ => default.is_number(),
+        },
+        ApiDocsModelObjectType::Boolean => default.is_boolean(),
+        ApiDocsModelObjectType::Array | ApiDocsModelObjectType::Tuple => default.is_array(),
+        ApiDocsModelObjectType::Object | ApiDocsModelObjectType::Map => default.is_object(),
+        ApiDocsModelObjectType::Enum => model.members.iter().flatten().any(|m| m == default),
+        ApiDocsModelObjectType::Literal => model.value.as_ref() == Some(default),
+        ApiDocsModelObjectType::Ref | ApiDocsModelObjectType::Union => true,
+        ApiDocsModelObjectType::File => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Validates `api_docs`, rendering a diagnostic per line.
+    fn validate_json(api_docs: serde_json::Value) -> String {
+        let api_docs: ApiDocs = serde_json::from_value(api_docs).unwrap();
+        validate(&api_docs)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Validates a spec with the single model `user` made of `fields`.
+    fn validate_fields(fields: serde_json::Value) -> String {
+        validate_json(serde_json::json!({ "models": { "user": fields }, "routes": {} }))
+    }
+
+    #[test]
+    fn test_validate_collects_all_errors() {
+        k9::snapshot!(
+            validate_json(serde_json::json!({
+                "models": {
+                    "user": { "address": { "type": "Object", "required": true } },
+                    "post": { "author": { "type": "Ref", "required": true, "ref": "author" } }
+                },
+                "routes": {
+                    "get_post": { "accepts": "post_id", "returns": "post" }
+                }
+            })),
+            "
+error: post.author: unknown model `author`
+error: routes.get_post: unknown model `post_id`
+error: user.address: `fields` must be set for objects
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_field_shapes() {
+        k9::snapshot!(
+            validate_fields(serde_json::json!({
+                "address": { "type": "Object", "required": true },
+                "scores": { "type": "Map", "required": true },
+                "comments": { "type": "Array", "required": false },
+                "location": {
+                    "type": "Tuple",
+                    "required": true,
+                    "items": [
+                        { "type": "Number", "required": true },
+                        { "type": "Ref", "required": true }
+                    ]
+                },
+                "role": { "type": "Enum", "required": true },
+                "status": { "type": "Enum", "required": true, "members": [] },
+                "id": {
+                    "type": "Union",
+                    "required": true,
+                    "models": [
+                        { "type": "String", "required": true },
+                        { "type": "Array", "required": true }
+                    ]
+                },
+                "name": { "type": "String", "required": true, "fields": {}, "members": ["a"] }
+            })),
+            "
+error: user.address: `fields` must be set for objects
+error: user.comments: `model` must be set for arrays
+error: user.id|1: `model` must be set for arrays
+error: user.location[1]: `ref` must be set for refs
+error: user.name: `fields` is only allowed for objects, not `String`
+error: user.name: `members` is only allowed for enums, not `String`
+error: user.role: `members` must be set for enums
+error: user.scores: `values` must be set for maps
+error: user.status: `members` must not be empty
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_formats() {
+        k9::snapshot!(
+            validate_fields(serde_json::json!({
+                "age": { "type": "String", "format": "integer", "required": true },
+                "born": { "type": "Number", "format": "date", "required": true }
+            })),
+            "
+error: user.age: `integer` format is only allowed for numbers
+error: user.born: `date` format is only allowed for strings
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_constraints() {
+        k9::snapshot!(
+            validate_fields(serde_json::json!({
+                "code": {
+                    "type": "String",
+                    "required": true,
+                    "minimum": 1,
+                    "min_length": 4,
+                    "max_length": 2,
+                    "pattern": "("
+                }
+            })),
+            "
+error: user.code: `minimum` is only allowed for numbers
+error: user.code: `min_length` is greater than `max_length`
+error: user.code: `pattern` is not a valid regular expression
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_defaults_and_examples() {
+        k9::snapshot!(
+            validate_fields(serde_json::json!({
+                "theme": {
+                    "type": "Enum",
+                    "required": true,
+                    "members": ["light", "dark"],
+                    "default": "blue",
+                    "example": "red"
+                }
+            })),
+            r#"
+error: user.theme: `default` must be a valid `Enum`, found `"blue"`
+error: user.theme: `example` must be a valid `Enum`, found `"red"`
+"#
+        );
+    }
+
+    #[test]
+    fn test_validate_enum_and_literal_values() {
+        k9::snapshot!(
+            validate_fields(serde_json::json!({
+                "tags": {
+                    "type": "Array",
+                    "required": true,
+                    "model": { "type": "Enum", "required": true, "members": ["a", {}] }
+                },
+                "version": { "type": "Literal", "required": true, "value": [1] }
+            })),
+            "
+error: user.tags[]: enum members must be strings, numbers or booleans, found `{}`
+error: user.version: literals must be strings, numbers or booleans, found `[1]`
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_read_only_and_write_only() {
+        k9::snapshot!(
+            validate_fields(serde_json::json!({
+                "password": {
+                    "type": "String",
+                    "required": true,
+                    "read_only": true,
+                    "write_only": true
+                },
+                "settings": {
+                    "type": "Object",
+                    "required": true,
+                    "fields": { "theme": { "type": "String", "required": true, "read_only": true } }
+                }
+            })),
+            r#"
+error: user.password: `read_only` and `write_only` can't both be set
+error: user.settings.theme: only fields of models can be read-only or write-only
+"#
+        );
+    }
+
+    #[test]
+    fn test_validate_feature_flags() {
+        k9::snapshot!(
+            validate_fields(serde_json::json!({
+                "nickname": { "type": "String", "required": false, "feature": " " },
+                "settings": {
+                    "type": "Object",
+                    "required": true,
+                    "fields": {
+                        "beta": { "type": "Boolean", "required": false, "feature": "beta" }
+                    }
+                }
+            })),
+            "
+error: user.nickname: `feature` must not be empty
+error: user.settings.beta: only fields of models can be behind a feature flag
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_unions() {
+        k9::snapshot!(
+            validate_fields(serde_json::json!({
+                "shape": {
+                    "type": "Union",
+                    "required": true,
+                    "discriminator": "kind",
+                    "variants": {
+                        "circle": {
+                            "type": "Object",
+                            "required": true,
+                            "fields": { "kind": { "type": "String", "required": true } }
+                        },
+                        "dot": { "type": "String", "required": true }
+                    }
+                }
+            })),
+            "
+error: user.shape|circle: field `kind` clashes with the discriminator
+error: user.shape|dot: variants must be objects or refs
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_extends() {
+        k9::snapshot!(
+            validate_json(serde_json::json!({
+                "models": {
+                    "base_entity": {
+                        "extends": ["entity"],
+                        "fields": { "id": { "type": "Number", "required": true } }
+                    },
+                    "tree": { "extends": ["tree"], "fields": {} },
+                    "post": {
+                        "extends": ["base_entity"],
+                        "fields": { "id": { "type": "Number", "required": true } }
+                    }
+                },
+                "routes": {}
+            })),
+            "
+error: base_entity.extends[0]: unknown model `entity`
+error: post.id: redefines the field inherited from `base_entity`
+error: tree: extends itself
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_recursive_models() {
+        k9::snapshot!(
+            validate_json(serde_json::json!({
+                "models": {
+                    "category": {
+                        "meta": {
+                            "type": "Object",
+                            "required": true,
+                            "fields": {
+                                "parent": { "type": "Ref", "required": true, "ref": "category" }
+                            }
+                        },
+                        "children": {
+                            "type": "Array",
+                            "required": true,
+                            "model": { "type": "Ref", "required": true, "ref": "category" }
+                        }
+                    }
+                },
+                "routes": {}
+            })),
+            "error: category: contains itself through required fields; make one of them optional, nullable or an array"
+        );
+    }
+
+    #[test]
+    fn test_validate_route_models() {
+        k9::snapshot!(
+            validate_json(serde_json::json!({
+                "models": {
+                    "empty": {},
+                    "post": { "title": { "type": "String", "required": true } },
+                    "avatar": { "image": { "type": "File", "required": true } }
+                },
+                "routes": {
+                    "get_post": { "accepts": "post_id", "returns": "post" },
+                    "get_comment": {
+                        "accepts": "post",
+                        "returns": "empty",
+                        "method": "GET",
+                        "errors": { "404": "post", "200": "post", "422": "problem" }
+                    },
+                    "upload_avatar": { "accepts": "avatar", "returns": "avatar" }
+                }
+            })),
+            r#"
+error: routes.get_comment.accepts: `GET` requests can't have a body; move the fields of `post` to `query` or `params`
+error: routes.get_comment.errors.200: must be an error status code, from 400 to 599
+error: routes.get_comment.errors.422: unknown model `problem`
+error: routes.get_post: unknown model `post_id`
+error: routes.upload_avatar.accepts: `avatar` has files, which can only be sent with the `multipart/form-data` content type
+"#
+        );
+    }
+
+    #[test]
+    fn test_validate_route_paths() {
+        k9::snapshot!(validate_json(serde_json::json!({
+            "models": { "empty": {} },
+            "routes": {
+                "get_tag": {
+                    "accepts": "empty",
+                    "returns": "empty",
+                    "path": "/tags/{}/{name}/{name}/{x",
+                    "params": { "name": { "type": "String", "required": true } }
+                },
+                "get_comment": {
+                    "accepts": "empty",
+                    "returns": "empty",
+                    "path": "posts/{post_id}/comments/{id}",
+                    "params": {
+                        "post_id": { "type": "Number", "required": true },
+                        "slug": {
+                            "type": "Array",
+                            "required": true,
+                            "model": { "type": "String", "required": true }
+                        }
+                    }
+                }
+            }
+        })), "
+error: routes.get_comment.params.slug: path parameters must be required strings, numbers, booleans, enums or literals
+error: routes.get_comment.params.slug: not used in `path`
+error: routes.get_comment.path: must start with `/`
+error: routes.get_comment.path: unknown parameter `id`
+error: routes.get_tag.path: has unbalanced `{` or `}`
+error: routes.get_tag.path: has a parameter without a name
+error: routes.get_tag.path: uses the parameter `name` more than once
+");
+    }
+
+    #[test]
+    fn test_validate_route_query_and_headers() {
+        k9::snapshot!(validate_json(serde_json::json!({
+            "models": {
+                "empty": {},
+                "filter": {
+                    "author": { "type": "Ref", "required": true, "ref": "empty" },
+                    "comments": {
+                        "type": "Array",
+                        "required": false,
+                        "model": { "type": "Ref", "required": true, "ref": "empty" }
+                    },
+                    "tags": {
+                        "type": "Array",
+                        "required": false,
+                        "model": { "type": "String", "required": true }
+                    }
+                },
+                "tenant": {
+                    "tenant": { "type": "String", "required": true },
+                    "ids": {
+                        "type": "Array",
+                        "required": false,
+                        "model": { "type": "Number", "required": true }
+                    }
+                }
+            },
+            "routes": {
+                "list_posts": {
+                    "accepts": "empty",
+                    "returns": "empty",
+                    "method": "GET",
+                    "query": "filter",
+                    "headers": "tenant"
+                }
+            }
+        })), "
+error: routes.list_posts.headers.ids: headers must be strings, numbers, booleans, enums or literals
+error: routes.list_posts.query.author: query parameters must be strings, numbers, booleans, enums, literals or arrays of them
+error: routes.list_posts.query.comments: query parameters must be strings, numbers, booleans, enums, literals or arrays of them
+");
+    }
+
+    #[test]
+    fn test_validate_route_tags_and_scopes() {
+        k9::snapshot!(
+            validate_json(serde_json::json!({
+                "models": { "empty": {} },
+                "routes": {
+                    "get_comment": {
+                        "accepts": "empty",
+                        "returns": "empty",
+                        "tags": ["comments", " ", "comments"],
+                        "scopes": ["comments:read", "", "comments:read"]
+                    }
+                }
+            })),
+            "
+error: routes.get_comment.scopes[1]: must not be empty
+error: routes.get_comment.scopes[2]: repeats `comments:read`
+error: routes.get_comment.tags[1]: must not be empty
+error: routes.get_comment.tags[2]: repeats `comments`
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_pagination() {
+        k9::snapshot!(validate_json(serde_json::json!({
+            "models": {
+                "empty": {},
+                "page": { "id": { "type": "Number", "required": true } },
+                "post": { "id": { "type": "Number", "required": true } }
+            },
+            "routes": {
+                "get_tag": {
+                    "accepts": "empty",
+                    "returns": "post",
+                    "pagination": { "style": "cursor", "cursor": "after", "next_cursor": "next" }
+                },
+                "list_posts": {
+                    "accepts": "empty",
+                    "returns": "post",
+                    "query": "page",
+                    "pagination": { "style": "offset", "offset": "skip", "items": "id" }
+                },
+                "get_comment": {
+                    "accepts": "empty",
+                    "returns": "post",
+                    "query": "page",
+                    "pagination": { "style": "cursor", "cursor": "id", "next_cursor": "next" }
+                }
+            }
+        })), "
+error: routes.get_comment.pagination.cursor: `id` of `page` must be optional, as the first page is requested without it
+error: routes.get_comment.pagination.next_cursor: `post` has no field `next`
+error: routes.get_tag.pagination: needs the route to have a `query` to request pages with
+error: routes.list_posts.pagination.items: `id` of `post` must be an array
+error: routes.list_posts.pagination.offset: `page` has no field `skip`
+");
+    }
+
+    #[test]
+    fn test_validate_sockets() {
+        k9::snapshot!(
+            validate_json(serde_json::json!({
+                "models": { "post": {} },
+                "routes": {},
+                "sockets": {
+                    "chat": {
+                        "path": "chat",
+                        "sends": { "say": "message" },
+                        "receives": { "said": "post" }
+                    }
+                }
+            })),
+            "
+error: sockets.chat.path: must start with `/`
+error: sockets.chat.sends.say: unknown model `message`
+"
+        );
+    }
+
+    #[test]
+    fn test_validate_many_models_sorted() {
+        // More models than threads, on both sides of `routes` when sorted.
+        let mut models = serde_json::Map::new();
+        for index in 0..100 {
+            for prefix in ["a", "z"] {
+                models.insert(
+                    format!("{prefix}{index}"),
+                    serde_json::json!({ "address": { "type": "Object", "required": true } }),
+                );
+            }
+        }
+        let routes = (0..10)
+            .map(|index| {
+                let route = serde_json::json!({ "accepts": "missing", "returns": "a0" });
+                (format!("route{index}"), route)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let api_docs: ApiDocs =
+            serde_json::from_value(serde_json::json!({ "models": models, "routes": routes }))
+                .unwrap();
+
+        let render = |diagnostics: Vec<Diagnostic>| {
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        let diagnostics = render(validate(&api_docs));
+        assert_eq!(diagnostics.len(), 210);
+        let mut sorted = diagnostics.clone();
+        sorted.sort();
+        assert_eq!(diagnostics, sorted);
+        for _ in 0..3 {
+            assert_eq!(render(validate(&api_docs)), diagnostics);
+        }
+    }
+
+    #[test]
+    fn test_validate_route_conflicts() {
+        let id = serde_json::json!({ "id": { "type": "Number", "required": true } });
+        let route = |method: Option<&str>, path: &str| {
+            let mut route = serde_json::json!({ "accepts": "empty", "returns": "empty" });
+            route["path"] = path.into();
+            if path.contains("{id}") {
+                route["params"] = id.clone();
+            }
+            if let Some(method) = method {
+                route["method"] = method.into();
+            }
+            route
+        };
+        let mut billing_list_users = route(Some("GET"), "/users");
+        billing_list_users["service"] = "billing".into();
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": { "empty": {} },
+            "routes": {
+                "billing_list_users": billing_list_users,
+                "get_user": route(None, "/users/{id}"),
+                "get_user_by_id": route(Some("GET"), "/users/{id}/"),
+                "get_me": route(Some("GET"), "/users/me"),
+                "delete_me": route(Some("DELETE"), "/users/me"),
+                "export_user": route(Some("GET"), "/users/{id}.json"),
+                "list_users": route(Some("GET"), "/users")
+            }
+        }))
+        .unwrap();
+
+        let diagnostics = validate(&api_docs);
+        k9::snapshot!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            "
+error: routes.get_user.path: overlaps with `/users/me` of `routes.delete_me`; both match `/users/me`
+error: routes.get_user.path: overlaps with `/users/{id}.json` of `routes.export_user`; both match `/users/{}.json`
+error: routes.get_user.path: overlaps with `/users/me` of `routes.get_me`; both match `/users/me`
+error: routes.get_user_by_id.path: overlaps with `/users/{id}.json` of `routes.export_user`; both match `/users/{}.json`
+error: routes.get_user_by_id.path: overlaps with `/users/me` of `routes.get_me`; both match `/users/me`
+error: routes.get_user_by_id.path: is also the path of `routes.get_user`
+"
+        );
+    }
+}
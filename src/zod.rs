@@ -0,0 +1,398 @@
+//! Generation of Zod schemas, which validate payloads at runtime, along with
+//! the types inferred from them.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::config::TypeScriptConfig;
+use crate::diagnostics::Diagnostics;
+use crate::{
+    collect_refs, flatten_extends, recursive_models, render_comment, render_header,
+    render_property_name, typescript_models, validate, ApiDocs, ApiDocsModel,
+    ApiDocsModelDefinition, ApiDocsModelFormat, ApiDocsModelObject, ApiDocsModelObjectType,
+    TypeScriptRenderer,
+};
+
+/// Validates the spec, then renders a schema and an inferred type for every
+/// model.
+///
+/// Schemas are declared after the schemas they reference. References that
+/// can't be ordered that way because of cycles are wrapped in `z.lazy`.
+/// TypeScript can't infer the type of a schema referencing itself, so the
+/// types of recursive models are declared as interfaces instead.
+pub fn generate_zod(api_docs: &ApiDocs, config: &TypeScriptConfig) -> Result<String, Diagnostics> {
+    let errors = validate::validate(api_docs);
+    if !errors.is_empty() {
+        return Err(Diagnostics(errors));
+    }
+    Ok(render_zod(api_docs, config))
+}
+
+pub(crate) fn render_zod(api_docs: &ApiDocs, config: &TypeScriptConfig) -> String {
+    let models = flatten_extends(&typescript_models(&api_docs.models, config));
+    // Only the types Zod infers, not the options of the TypeScript target.
+    let interface_config = TypeScriptConfig {
+        tab_width: config.tab_width,
+        use_tabs: config.use_tabs,
+        ..Default::default()
+    };
+    let mut renderer = ZodRenderer {
+        config,
+        declared: BTreeSet::new(),
+        recursive: recursive_models(&models),
+        interfaces: TypeScriptRenderer::without_docs(&interface_config),
+    };
+    let mut schemas = Vec::new();
+    for name in declaration_order(&models) {
+        schemas.push(renderer.render_schema(name, &models[name]));
+        renderer.declared.insert(name);
+    }
+
+    format!(
+        "{}import {{ z }} from \"zod\";\n\n{}",
+        render_header(config),
+        schemas.join("\n")
+    )
+}
+
+struct ZodRenderer<'a> {
+    config: &'a TypeScriptConfig,
+    /// Models whose schema has already been rendered.
+    declared: BTreeSet<&'a str>,
+    /// Models that reference themselves, whose type can't be inferred.
+    recursive: BTreeSet<&'a str>,
+    interfaces: TypeScriptRenderer<'a>,
+}
+
+impl<'a> ZodRenderer<'a> {
+    fn render_schema(&self, name: &str, model: &ApiDocsModelDefinition) -> String {
+        let schema = self.render_object(&model.fields, 0);
+        let recursive = self.recursive.contains(name);
+        let name = heck::AsPascalCase(name).to_string();
+        let declarations = if recursive {
+            format!(
+                "{}export const {name}Schema: z.ZodType<{name}> = {schema};\n",
+                self.interfaces.render_interface(&name, &model.fields),
+            )
+        } else {
+            format!(
+                "export const {name}Schema = {schema};\nexport type {name} = z.infer<typeof \
+                 {name}Schema>;\n"
+            )
+        };
+        format!(
+            "{}{declarations}{}",
+            render_comment(model.comment_before.as_deref()),
+            render_comment(model.comment_after.as_deref()),
+        )
+    }
+
+    fn render_model(&self, model: &ApiDocsModel, level: usize) -> String {
+        let schema = match model.r#type {
+            ApiDocsModelObjectType::String => match model.format {
+                Some(ApiDocsModelFormat::Date) => "z.string().date()".to_string(),
+                Some(ApiDocsModelFormat::DateTime) => "z.string().datetime()".to_string(),
+                Some(ApiDocsModelFormat::Time) => "z.string().time()".to_string(),
+                Some(ApiDocsModelFormat::Uuid) => "z.string().uuid()".to_string(),
+                Some(ApiDocsModelFormat::Email) => "z.string().email()".to_string(),
+                Some(ApiDocsModelFormat::Uri) => "z.string().url()".to_string(),
+                _ => "z.string()".to_string(),
+            },
+            ApiDocsModelObjectType::Number => match model.format {
+                Some(ApiDocsModelFormat::Integer) => "z.number().int()".to_string(),
+                _ => "z.number()".to_string(),
+            },
+            ApiDocsModelObjectType::Boolean => "z.boolean()".to_string(),
+            ApiDocsModelObjectType::Array => format!(
+                "z.array({})",
+                self.render_model(
+                    model
+                        .model
+                        .as_ref()
+                        .expect("validated: `model` is set for arrays"),
+                    level
+                )
+            ),
+            ApiDocsModelObjectType::Map => format!(
+                "z.record(z.string(), {})",
+                self.render_model(
+                    model
+                        .values
+                        .as_ref()
+                        .expect("validated: `values` is set for maps"),
+                    level
+                )
+            ),
+            ApiDocsModelObjectType::Tuple => {
+                let items = model
+                    .items
+                    .as_ref()
+                    .expect("validated: `items` is set for tuples")
+                    .iter()
+                    .map(|item| self.render_model(item, level))
+                    .collect::<Vec<_>>();
+                format!("z.tuple([{}])", items.join(", "))
+            },
+            ApiDocsModelObjectType::Object => self.render_object(
+                model
+                    .fields
+                    .as_ref()
+                    .expect("validated: `fields` is set for objects"),
+                level,
+            ),
+            ApiDocsModelObjectType::Enum => render_enum(
+                model
+                    .members
+                    .as_ref()
+                    .expect("validated: `members` is set for enums"),
+            ),
+            ApiDocsModelObjectType::Literal => format!(
+                "z.literal({})",
+                model
+                    .value
+                    .as_ref()
+                    .expect("validated: `value` is set for literals")
+            ),
+            ApiDocsModelObjectType::File => "z.instanceof(Blob)".to_string(),
+            ApiDocsModelObjectType::Ref => {
+                let name = model
+                    .r#ref
+                    .as_deref()
+                    .expect("validated: `ref` is set for refs");
+                let schema = format!("{}Schema", heck::AsPascalCase(name));
+                if self.declared.contains(name) {
+                    schema
+                } else {
+                    format!("z.lazy(() => {schema})")
+                }
+            },
+            ApiDocsModelObjectType::Union if model.discriminator.is_some() => {
+                let discriminator = model.discriminator.as_deref().unwrap_or_default();
+                let schemas = model
+                    .variants
+                    .as_ref()
+                    .expect("validated: `variants` is set for discriminated unions")
+                    .iter()
+                    .map(|(tag, variant)| {
+                        format!(
+                            "{}.extend({{ {discriminator}: z.literal({}) }})",
+                            self.render_model(variant, level),
+                            serde_json::Value::from(tag.as_str())
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    "z.discriminatedUnion(\"{discriminator}\", [{}])",
+                    schemas.join(", ")
+                )
+            },
+            ApiDocsModelObjectType::Union => {
+                let schemas = model
+                    .models
+                    .as_ref()
+                    .expect("validated: `models` is set for unions")
+                    .iter()
+                    .map(|model| self.render_model(model, level))
+                    .collect::<Vec<_>>();
+                match schemas.as_slice() {
+                    [schema] => schema.clone(),
+                    _ => format!("z.union([{}])", schemas.join(", ")),
+                }
+            },
+        };
+
+        let schema = format!("{schema}{}", render_constraints(model));
+        let schema = if model.nullable {
+            format!("{schema}.nullable()")
+        } else {
+            schema
+        };
+        if model.required {
+            schema
+        } else {
+            format!("{schema}.optional()")
+        }
+    }
+
+    fn render_object(&self, fields: &ApiDocsModelObject, level: usize) -> String {
+        if fields.is_empty() {
+            return "z.object({})".to_string();
+        }
+        let fields = fields
+            .iter()
+            .map(|(name, model)| {
+                format!(
+                    "{}{}: {},\n",
+                    self.config.indent(level + 1),
+                    render_property_name(name),
+                    self.render_model(model, level + 1)
+                )
+            })
+            .collect::<String>();
+        format!("z.object({{\n{fields}{}}})", self.config.indent(level))
+    }
+}
+
+/// Renders the validation constraints of `model` as refinements.
+fn render_constraints(model: &ApiDocsModel) -> String {
+    let mut refinements = String::new();
+    for (method, limit) in [("min", model.min_length), ("max", model.max_length)] {
+        if let Some(limit) = limit {
+            refinements.push_str(&format!(".{method}({limit})"));
+        }
+    }
+    for (method, limit) in [("min", model.minimum), ("max", model.maximum)] {
+        if let Some(limit) = limit {
+            refinements.push_str(&format!(".{method}({limit})"));
+        }
+    }
+    if let Some(pattern) = &model.pattern {
+        refinements.push_str(&format!(
+            ".regex(new RegExp({}))",
+            serde_json::Value::from(pattern.as_str())
+        ));
+    }
+    refinements
+}
+
+fn render_enum(members: &[serde_json::Value]) -> String {
+    if let [member] = members {
+        return format!("z.literal({member})");
+    }
+    if members.iter().all(serde_json::Value::is_string) {
+        let members = members.iter().map(|member| member.to_string());
+        format!("z.enum([{}])", members.collect::<Vec<_>>().join(", "))
+    } else {
+        let literals = members.iter().map(|member| format!("z.literal({member})"));
+        format!("z.union([{}])", literals.collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// Orders models so that every model comes after the models it references,
+/// except where references form a cycle.
+fn declaration_order(models: &BTreeMap<String, ApiDocsModelDefinition>) -> Vec<&str> {
+    fn visit<'a>(
+        name: &'a str, models: &'a BTreeMap<String, ApiDocsModelDefinition>,
+        visited: &mut BTreeSet<&'a str>, order: &mut Vec<&'a str>,
+    ) {
+        if !visited.insert(name) {
+            return;
+        }
+        let mut refs = Vec::new();
+        for model in models[name].fields.values() {
+            collect_refs(model, &mut refs);
+        }
+        for r#ref in refs {
+            visit(r#ref, models, visited, order);
+        }
+        order.push(name);
+    }
+
+    let mut visited = BTreeSet::new();
+    let mut order = Vec::new();
+    for name in models.keys() {
+        visit(name, models, &mut visited, &mut order);
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_zod() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "post": {
+                    "author": { "type": "Ref", "required": true, "ref": "user" },
+                    "tags": {
+                        "type": "Array",
+                        "required": false,
+                        "model": { "type": "String", "required": true }
+                    }
+                },
+                "user": {
+                    "id": { "type": "Number", "required": true },
+                    "role": { "type": "Enum", "required": true, "members": ["admin", "member"] },
+                    "level": { "type": "Enum", "required": false, "members": [1, 2] },
+                    "address": {
+                        "type": "Object",
+                        "required": true,
+                        "fields": { "city": { "type": "String", "required": true } }
+                    },
+                    "best_friend": { "type": "Ref", "required": false, "ref": "user" }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_zod(&api_docs, &TypeScriptConfig::default()).unwrap(),
+            r#"
+import { z } from "zod";
+
+export interface User {
+  address: {
+    city: string;
+  };
+  best_friend?: User | undefined;
+  id: number;
+  level?: 1 | 2 | undefined;
+  role: "admin" | "member";
+}
+export const UserSchema: z.ZodType<User> = z.object({
+  address: z.object({
+    city: z.string(),
+  }),
+  best_friend: z.lazy(() => UserSchema).optional(),
+  id: z.number(),
+  level: z.union([z.literal(1), z.literal(2)]).optional(),
+  role: z.enum(["admin", "member"]),
+});
+
+export const PostSchema = z.object({
+  author: UserSchema,
+  tags: z.array(z.string()).optional(),
+});
+export type Post = z.infer<typeof PostSchema>;
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_zod_constraints() {
+        let api_docs: ApiDocs = serde_json::from_value(serde_json::json!({
+            "models": {
+                "user": {
+                    "name": { "type": "String", "required": true, "min_length": 1, "max_length": 64 },
+                    "age": { "type": "Number", "required": false, "minimum": 0, "maximum": 150.5 },
+                    "slug": { "type": "String", "required": true, "pattern": "^[a-z/]+$" },
+                    "tags": {
+                        "type": "Array",
+                        "required": true,
+                        "max_length": 10,
+                        "model": { "type": "String", "required": true }
+                    }
+                }
+            },
+            "routes": {}
+        }))
+        .unwrap();
+        k9::snapshot!(
+            generate_zod(&api_docs, &TypeScriptConfig::default()).unwrap(),
+            r#"
+import { z } from "zod";
+
+export const UserSchema = z.object({
+  age: z.number().min(0).max(150.5).optional(),
+  name: z.string().min(1).max(64),
+  slug: z.string().regex(new RegExp("^[a-z/]+$")),
+  tags: z.array(z.string()).max(10),
+});
+export type User = z.infer<typeof UserSchema>;
+
+"#
+        );
+    }
+}